@@ -1,6 +1,6 @@
 @echo off
 
-:: Examples: empty, exitwithcode, functions
+:: Examples: empty, exitwithcode, functions, strings, chars, hello, branches, variables, constants, statics, overflow, panic, assertions, arrays, slices, structs, enums, tuples
 set example_name=functions
 
 echo Building compiler project..
@@ -17,8 +17,8 @@ echo Compiling LLVM IR to object code..
 llc target\programs\%example_name%.ll -o target\programs\%example_name%.s
 if %errorlevel% neq 0 exit /b %errorlevel%
 
-echo Linking object code..
-gcc target\programs\%example_name%.s -mwindows -o target\programs\%example_name%.exe
+echo Linking object code and runtime..
+gcc target\programs\%example_name%.s runtime\yyn_runtime.c -o target\programs\%example_name%.exe
 if %errorlevel% neq 0 exit /b %errorlevel%
 
 echo Executing compiled YYN program..
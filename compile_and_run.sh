@@ -0,0 +1,25 @@
+#!/bin/sh
+set -e
+
+# Examples: empty, exitwithcode, strings, chars, hello
+# `exit` is lowered to the Windows API, so programs using it can only be linked on Windows
+example_name=${1:-hello}
+
+echo "Building compiler project.."
+cargo build
+
+mkdir -p target/programs
+
+echo "Compiling YYN to LLVM IR.."
+cargo run -- "programs/$example_name.yyn" "target/programs/$example_name.ll"
+
+echo "Compiling LLVM IR to object code.."
+llc -relocation-model=pic "target/programs/$example_name.ll" -o "target/programs/$example_name.s"
+
+echo "Linking object code and runtime.."
+gcc "target/programs/$example_name.s" runtime/yyn_runtime.c -o "target/programs/$example_name"
+
+echo "Executing compiled YYN program.."
+set +e
+"./target/programs/$example_name"
+echo "Exited with code $?"
@@ -0,0 +1,201 @@
+// Runtime support library for compiled yyn programs.
+// It is linked together with the object code generated by the compiler, all functions use the C ABI.
+
+#include <stdint.h>
+#include <stdlib.h>
+
+#ifdef _WIN32
+#include <io.h>
+#define write _write
+#else
+#include <unistd.h>
+#endif
+
+#define STDOUT 1
+#define STDERR 2
+
+// Exit code of a program that panicked, which distinguishes panics from explicit exits
+#define PANIC_EXIT_CODE 101
+
+static void yyn_write(int fd, const char *buf, int64_t len) {
+    while (len > 0) {
+        int64_t written = write(fd, buf, len);
+        if (written <= 0) {
+            return;
+        }
+        buf += written;
+        len -= written;
+    }
+}
+
+void yyn_print_str(const char *ptr, int64_t len) {
+    yyn_write(STDOUT, ptr, len);
+}
+
+static void yyn_write_int(int fd, int32_t value) {
+    // Enough space for the sign and all 10 digits of an int32_t
+    char buf[11];
+    int pos = sizeof(buf);
+
+    // Work with the negated value, because the negation of INT32_MIN does not fit into an int32_t
+    int32_t remaining = value < 0 ? value : -value;
+    do {
+        buf[--pos] = (char) ('0' - remaining % 10);
+        remaining /= 10;
+    } while (remaining != 0);
+
+    if (value < 0) {
+        buf[--pos] = '-';
+    }
+
+    yyn_write(fd, buf + pos, sizeof(buf) - pos);
+}
+
+void yyn_print_int(int32_t value) {
+    yyn_write_int(STDOUT, value);
+}
+
+static void yyn_write_bool(int fd, int32_t value) {
+    if (value) {
+        yyn_write(fd, "true", 4);
+    } else {
+        yyn_write(fd, "false", 5);
+    }
+}
+
+void yyn_print_bool(int32_t value) {
+    yyn_write_bool(STDOUT, value);
+}
+
+// Writes a unicode scalar value encoded as UTF-8
+static void yyn_write_char(int fd, uint32_t value) {
+    char buf[4];
+    int64_t len;
+
+    if (value < 0x80) {
+        buf[0] = (char) value;
+        len = 1;
+    } else if (value < 0x800) {
+        buf[0] = (char) (0xC0 | (value >> 6));
+        buf[1] = (char) (0x80 | (value & 0x3F));
+        len = 2;
+    } else if (value < 0x10000) {
+        buf[0] = (char) (0xE0 | (value >> 12));
+        buf[1] = (char) (0x80 | ((value >> 6) & 0x3F));
+        buf[2] = (char) (0x80 | (value & 0x3F));
+        len = 3;
+    } else {
+        buf[0] = (char) (0xF0 | (value >> 18));
+        buf[1] = (char) (0x80 | ((value >> 12) & 0x3F));
+        buf[2] = (char) (0x80 | ((value >> 6) & 0x3F));
+        buf[3] = (char) (0x80 | (value & 0x3F));
+        len = 4;
+    }
+
+    yyn_write(fd, buf, len);
+}
+
+void yyn_print_char(uint32_t value) {
+    yyn_write_char(STDOUT, value);
+}
+
+void yyn_print_newline(void) {
+    yyn_write(STDOUT, "\n", 1);
+}
+
+// Writes `panicked at <file>:<line>:<column>: ` to stderr
+static void yyn_write_panic_location(const char *file_ptr, int64_t file_len, int32_t line, int32_t column) {
+    const char prefix[] = "panicked at ";
+    yyn_write(STDERR, prefix, sizeof(prefix) - 1);
+    yyn_write(STDERR, file_ptr, file_len);
+    yyn_write(STDERR, ":", 1);
+    yyn_write_int(STDERR, line);
+    yyn_write(STDERR, ":", 1);
+    yyn_write_int(STDERR, column);
+    yyn_write(STDERR, ": ", 2);
+}
+
+// Prints `panicked at <file>:<line>:<column>: <message>` to stderr and exits, it never returns
+_Noreturn void yyn_panic(
+    const char *file_ptr, int64_t file_len, int32_t line, int32_t column, const char *msg_ptr, int64_t msg_len
+) {
+    yyn_write_panic_location(file_ptr, file_len, line, column);
+    yyn_write(STDERR, msg_ptr, msg_len);
+    yyn_write(STDERR, "\n", 1);
+
+    exit(PANIC_EXIT_CODE);
+}
+
+// Types of the values compared by `assert_eq`
+#define ASSERT_EQ_INT 0
+#define ASSERT_EQ_BOOL 1
+#define ASSERT_EQ_CHAR 2
+
+static void yyn_write_value(int fd, int32_t type, int32_t value) {
+    switch (type) {
+        case ASSERT_EQ_BOOL:
+            yyn_write_bool(fd, value);
+            break;
+        case ASSERT_EQ_CHAR:
+            yyn_write(fd, "'", 1);
+            yyn_write_char(fd, (uint32_t) value);
+            yyn_write(fd, "'", 1);
+            break;
+        default:
+            yyn_write_int(fd, value);
+            break;
+    }
+}
+
+// Panics after a failed `assert_eq`, printing the source text of the assertion and both compared values
+_Noreturn void yyn_assert_eq_failed(
+    const char *file_ptr, int64_t file_len, int32_t line, int32_t column, const char *text_ptr, int64_t text_len,
+    int32_t type, int32_t left, int32_t right
+) {
+    yyn_write_panic_location(file_ptr, file_len, line, column);
+    yyn_write(STDERR, "assertion `", 11);
+    yyn_write(STDERR, text_ptr, text_len);
+    yyn_write(STDERR, "` failed\n  left: ", 17);
+    yyn_write_value(STDERR, type, left);
+    yyn_write(STDERR, "\n right: ", 9);
+    yyn_write_value(STDERR, type, right);
+    yyn_write(STDERR, "\n", 1);
+
+    exit(PANIC_EXIT_CODE);
+}
+
+// Panics after indexing into an array with an index that is negative or not smaller than its length
+_Noreturn void yyn_index_out_of_bounds(
+    const char *file_ptr, int64_t file_len, int32_t line, int32_t column, int32_t len, int32_t index
+) {
+    const char message[] = "index out of bounds: the length is ";
+    const char separator[] = " but the index is ";
+
+    yyn_write_panic_location(file_ptr, file_len, line, column);
+    yyn_write(STDERR, message, sizeof(message) - 1);
+    yyn_write_int(STDERR, len);
+    yyn_write(STDERR, separator, sizeof(separator) - 1);
+    yyn_write_int(STDERR, index);
+    yyn_write(STDERR, "\n", 1);
+
+    exit(PANIC_EXIT_CODE);
+}
+
+// Panics after slicing an array or slice with a range that is not inside of it or whose start is after its end
+_Noreturn void yyn_range_out_of_bounds(
+    const char *file_ptr, int64_t file_len, int32_t line, int32_t column, int32_t len, int32_t start, int32_t end
+) {
+    const char message[] = "range out of bounds: the length is ";
+    const char separator[] = " but the range is ";
+
+    yyn_write_panic_location(file_ptr, file_len, line, column);
+    yyn_write(STDERR, message, sizeof(message) - 1);
+    yyn_write_int(STDERR, len);
+    yyn_write(STDERR, separator, sizeof(separator) - 1);
+    yyn_write_int(STDERR, start);
+    yyn_write(STDERR, "..", 2);
+    yyn_write_int(STDERR, end);
+    yyn_write(STDERR, "\n", 1);
+
+    exit(PANIC_EXIT_CODE);
+}
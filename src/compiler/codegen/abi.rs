@@ -0,0 +1,311 @@
+use anyhow::{bail, Result};
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::types::{AnyType, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, StructType};
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, FunctionValue, PointerValue};
+use inkwell::AddressSpace;
+
+use crate::compiler::codegen::types::{layout, struct_type};
+use crate::compiler::codegen::CodegenContext;
+use crate::compiler::parser::ast;
+use crate::compiler::symbol_table::Function;
+
+/// Calling convention of the target, which decides how structs are passed between functions. Only x86-64 targets are
+/// supported.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Abi {
+    /// The System V ABI of Linux, macOS and most other x86-64 targets
+    SysV,
+    /// The Microsoft x64 calling convention of Windows
+    Win64,
+}
+
+impl Abi {
+    /// Returns the calling convention of the target `triple`, like `x86_64-pc-windows-msvc`
+    pub fn from_triple(triple: &str) -> Result<Self> {
+        if !triple.starts_with("x86_64-") {
+            bail!("Unsupported target `{triple}`, only x86-64 targets are supported");
+        }
+
+        if triple.contains("-windows") {
+            Ok(Abi::Win64)
+        } else {
+            Ok(Abi::SysV)
+        }
+    }
+}
+
+/// How a parameter or a return value is passed between functions. Structs are passed like C structs with the same
+/// fields under the calling convention of the target, so that functions taking or returning structs can be called
+/// from C and the other way around. Structs only contain integers and pointers, so their bytes are always passed in
+/// integer registers or in memory. Values of all other types have no C equivalent and are passed as their LLVM type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PassMode<'ctx> {
+    /// Passed as the LLVM type of the value
+    Direct(BasicTypeEnum<'ctx>),
+    /// A struct that is passed in registers, whose bytes are reinterpreted as `abi_ty`: an integer of the same size
+    /// for up to 8 bytes and `{ i64, iN }` for up to 16 bytes under System V, which LLVM passes in one or two integer
+    /// registers. Win64 only passes structs of 1, 2, 4 or 8 bytes in a register.
+    Cast {
+        struct_ty: StructType<'ctx>,
+        abi_ty: BasicTypeEnum<'ctx>,
+    },
+    /// A struct that is passed as a pointer to a copy or returned by writing it to memory provided by the caller
+    /// (`sret`). System V passes the copy on the stack (`byval`), while under Win64 the caller makes the copy.
+    Indirect(StructType<'ctx>),
+}
+
+impl<'ctx> PassMode<'ctx> {
+    /// Returns how values of type `ty` are passed, or `None` if the type has no values
+    pub fn of(ty: &ast::Type, codegen: &CodegenContext<'ctx>) -> Option<Self> {
+        let llvm_ty = ty.as_llvm_type(codegen)?;
+        let ast::Type::Struct(name) = ty else {
+            return Some(PassMode::Direct(llvm_ty));
+        };
+
+        let struct_ty = struct_type(codegen, name);
+        let (size, _) = layout(codegen, ty);
+        let mode = match (codegen.abi, size) {
+            // Empty structs do not exist in C and are passed as they are
+            (_, 0) => PassMode::Direct(llvm_ty),
+            (Abi::SysV, 1..=8) | (Abi::Win64, 1 | 2 | 4 | 8) => PassMode::Cast {
+                struct_ty,
+                abi_ty: codegen
+                    .context
+                    .custom_width_int_type(size as u32 * 8)
+                    .into(),
+            },
+            (Abi::SysV, 9..=16) => {
+                let rest_ty = codegen.context.custom_width_int_type((size - 8) as u32 * 8);
+                let abi_ty = codegen
+                    .context
+                    .struct_type(&[codegen.context.i64_type().into(), rest_ty.into()], false);
+                PassMode::Cast {
+                    struct_ty,
+                    abi_ty: abi_ty.into(),
+                }
+            }
+            _ => PassMode::Indirect(struct_ty),
+        };
+
+        Some(mode)
+    }
+
+    /// LLVM type in which the value is passed
+    pub fn abi_type(&self) -> BasicTypeEnum<'ctx> {
+        match self {
+            PassMode::Direct(ty) => *ty,
+            PassMode::Cast { abi_ty, .. } => *abi_ty,
+            PassMode::Indirect(struct_ty) => struct_ty.ptr_type(AddressSpace::default()).into(),
+        }
+    }
+}
+
+/// How the parameters and the return value of a function are passed, see `PassMode`
+pub struct FnAbi<'ctx> {
+    pub params: Vec<PassMode<'ctx>>,
+    /// `None` if the function returns no value
+    pub ret: Option<PassMode<'ctx>>,
+}
+
+impl<'ctx> FnAbi<'ctx> {
+    pub fn of(function: &Function, codegen: &CodegenContext<'ctx>) -> Self {
+        let params = function
+            .param_tys()
+            .iter()
+            .map(|ty| {
+                PassMode::of(ty, codegen)
+                    .expect("type checking to only allow parameters of types with values")
+            })
+            .collect();
+
+        FnAbi {
+            params,
+            ret: PassMode::of(&function.return_ty, codegen),
+        }
+    }
+
+    /// Struct type of a return value that is written to memory pointed to by a hidden first parameter
+    pub fn sret_type(&self) -> Option<StructType<'ctx>> {
+        match self.ret {
+            Some(PassMode::Indirect(struct_ty)) => Some(struct_ty),
+            _ => None,
+        }
+    }
+
+    /// LLVM type of the value returned in registers, or `None` if the function returns nothing in registers
+    pub fn return_type(&self) -> Option<BasicTypeEnum<'ctx>> {
+        match self.ret {
+            Some(PassMode::Indirect(_)) | None => None,
+            Some(mode) => Some(mode.abi_type()),
+        }
+    }
+
+    /// LLVM types of the parameters, starting with the hidden `sret` pointer if there is one
+    pub fn param_types(&self) -> Vec<BasicMetadataTypeEnum<'ctx>> {
+        let sret_ptr_ty: Option<BasicTypeEnum> = self
+            .sret_type()
+            .map(|struct_ty| struct_ty.ptr_type(AddressSpace::default()).into());
+
+        sret_ptr_ty
+            .into_iter()
+            .chain(self.params.iter().map(PassMode::abi_type))
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Position of the LLVM parameter for the parameter at `index`, which comes after the hidden `sret` pointer
+    pub fn param_index(&self, index: usize) -> u32 {
+        index as u32 + u32::from(self.sret_type().is_some())
+    }
+
+    /// Attributes of the parameters that are passed in memory. LLVM needs them on the function and on every call.
+    /// Under Win64, structs passed in memory are plain pointers to a copy, which need no attribute.
+    pub fn attributes(&self, codegen: &CodegenContext<'ctx>) -> Vec<(AttributeLoc, Attribute)> {
+        let mut attributes = Vec::new();
+
+        if let Some(struct_ty) = self.sret_type() {
+            attributes.push((
+                AttributeLoc::Param(0),
+                type_attribute(codegen, "sret", struct_ty),
+            ));
+        }
+        for (index, mode) in self.params.iter().enumerate() {
+            if let (Abi::SysV, PassMode::Indirect(struct_ty)) = (codegen.abi, mode) {
+                let location = AttributeLoc::Param(self.param_index(index));
+                attributes.push((location, type_attribute(codegen, "byval", *struct_ty)));
+            }
+        }
+
+        attributes
+    }
+}
+
+fn type_attribute<'ctx>(
+    codegen: &CodegenContext<'ctx>,
+    name: &str,
+    struct_ty: StructType<'ctx>,
+) -> Attribute {
+    codegen.context.create_type_attribute(
+        Attribute::get_named_enum_kind_id(name),
+        struct_ty.as_any_type_enum(),
+    )
+}
+
+impl<'cx> CodegenContext<'cx> {
+    /// Calls `fn_value`, the function value of `function`, and passes `args` and the returned value as described by
+    /// `FnAbi`. Returns `None` if the function returns no value.
+    pub fn build_fn_call(
+        &self,
+        function: &Function,
+        fn_value: FunctionValue<'cx>,
+        args: Vec<BasicValueEnum<'cx>>,
+    ) -> Result<Option<BasicValueEnum<'cx>>> {
+        let abi = FnAbi::of(function, self);
+
+        let mut abi_args: Vec<BasicMetadataValueEnum> = Vec::new();
+        let sret_ptr = match abi.sret_type() {
+            Some(struct_ty) => Some(self.generate_variable_alloca(struct_ty.into(), "sret")?),
+            None => None,
+        };
+        abi_args.extend(sret_ptr.map(BasicMetadataValueEnum::from));
+        for (value, mode) in args.into_iter().zip(&abi.params) {
+            abi_args.push(self.build_abi_value(value, *mode)?.into());
+        }
+
+        let call = self.builder.build_call(fn_value, &abi_args, "call_fn")?;
+        for (location, attribute) in abi.attributes(self) {
+            call.add_attribute(location, attribute);
+        }
+
+        let Some(mode) = abi.ret else {
+            return Ok(None);
+        };
+        let abi_value = match sret_ptr {
+            Some(ptr) => ptr.into(),
+            None => call
+                .try_as_basic_value()
+                .left()
+                .expect("function with a return value to return it in registers"),
+        };
+
+        Ok(Some(self.build_value_from_abi(abi_value, mode)?))
+    }
+
+    /// Returns `value` from the current function, whose return value is passed with `mode`
+    pub fn build_abi_return(&self, value: BasicValueEnum<'cx>, mode: PassMode<'cx>) -> Result<()> {
+        if let PassMode::Indirect(_) = mode {
+            let sret_ptr = self
+                .current_function()
+                .get_first_param()
+                .expect("function returning in memory to have an `sret` parameter")
+                .into_pointer_value();
+            self.builder.build_store(sret_ptr, value)?;
+            self.builder.build_return(None)?;
+        } else {
+            let abi_value = self.build_abi_value(value, mode)?;
+            self.builder.build_return(Some(&abi_value))?;
+        }
+
+        Ok(())
+    }
+
+    /// Converts `value` into the LLVM value that is passed with `mode`
+    pub fn build_abi_value(
+        &self,
+        value: BasicValueEnum<'cx>,
+        mode: PassMode<'cx>,
+    ) -> Result<BasicValueEnum<'cx>> {
+        match mode {
+            PassMode::Direct(_) => Ok(value),
+            PassMode::Cast { struct_ty, abi_ty } => {
+                let (abi_ptr, struct_ptr) = self.build_cast_alloca(struct_ty, abi_ty)?;
+                self.builder.build_store(struct_ptr, value)?;
+                Ok(self.builder.build_load(abi_ty, abi_ptr, "abi_value")?)
+            }
+            PassMode::Indirect(struct_ty) => {
+                // The callee owns the copy, so that it cannot change the original value
+                let ptr = self.generate_variable_alloca(struct_ty.into(), "byval")?;
+                self.builder.build_store(ptr, value)?;
+                Ok(ptr.into())
+            }
+        }
+    }
+
+    /// Converts the LLVM value `abi_value` that was passed with `mode` back into a value of its type
+    pub fn build_value_from_abi(
+        &self,
+        abi_value: BasicValueEnum<'cx>,
+        mode: PassMode<'cx>,
+    ) -> Result<BasicValueEnum<'cx>> {
+        match mode {
+            PassMode::Direct(_) => Ok(abi_value),
+            PassMode::Cast { struct_ty, abi_ty } => {
+                let (abi_ptr, struct_ptr) = self.build_cast_alloca(struct_ty, abi_ty)?;
+                self.builder.build_store(abi_ptr, abi_value)?;
+                Ok(self.builder.build_load(struct_ty, struct_ptr, "value")?)
+            }
+            PassMode::Indirect(struct_ty) => {
+                Ok(self
+                    .builder
+                    .build_load(struct_ty, abi_value.into_pointer_value(), "value")?)
+            }
+        }
+    }
+
+    /// Allocates memory through which a struct is reinterpreted as `abi_ty`, and returns pointers to it of both types.
+    /// The memory has the size of `abi_ty`, which is never smaller than the struct.
+    fn build_cast_alloca(
+        &self,
+        struct_ty: StructType<'cx>,
+        abi_ty: BasicTypeEnum<'cx>,
+    ) -> Result<(PointerValue<'cx>, PointerValue<'cx>)> {
+        let abi_ptr = self.generate_variable_alloca(abi_ty, "abi_cast")?;
+        let struct_ptr = self.builder.build_pointer_cast(
+            abi_ptr,
+            struct_ty.ptr_type(AddressSpace::default()),
+            "struct_ptr",
+        )?;
+
+        Ok((abi_ptr, struct_ptr))
+    }
+}
@@ -0,0 +1,397 @@
+use anyhow::Result;
+use inkwell::basic_block::BasicBlock;
+use inkwell::types::StructType;
+use inkwell::values::{BasicValue, BasicValueEnum, IntValue, PointerValue};
+use inkwell::{AddressSpace, IntPredicate};
+
+use crate::compiler::codegen::types::{enum_type, tuple_type, variant_type};
+use crate::compiler::codegen::CodegenContext;
+use crate::compiler::parser::ast::{self, Expr, ExprKind, Match, Pattern, PatternKind, VariantRef};
+
+impl Match {
+    /// Generates a `switch` on the tag of an enum or on an `int`, `char` or `bool` value, which jumps to a case for
+    /// every variant or literal of the top-level patterns. Tuples have no `switch` and only a default case. In every
+    /// case the remaining patterns of the arms that fit it are tested in order. The body of every arm is generated only
+    /// once and the value of the taken arm is selected by a phi. Returns `None` if the match has no value or diverges.
+    pub fn codegen<'ctx>(
+        self,
+        codegen: &CodegenContext<'ctx>,
+        ty: &ast::Type,
+    ) -> Result<Option<BasicValueEnum<'ctx>>> {
+        let value_ty = self.value.ty();
+        let Some(value) = self.value.codegen(codegen)? else {
+            if value_ty == ast::Type::Never {
+                return Ok(None);
+            }
+
+            // Values of type `()` can only be matched by wildcards and bindings, so the first arm is always taken
+            let arm = self
+                .arms
+                .into_iter()
+                .next()
+                .expect("type checking to only allow exhaustive matches");
+            return generate_arm_body(codegen, arm.body);
+        };
+
+        // Patterns are tested on the value in memory, so that nested fields can be reached by pointers
+        let value_ptr = codegen.generate_variable_alloca(value.get_type(), "match_value")?;
+        codegen.builder.build_store(value_ptr, value)?;
+
+        let function = codegen.current_function();
+        let arm_blocks: Vec<_> = self
+            .arms
+            .iter()
+            .map(|_| codegen.context.append_basic_block(function, "match_arm"))
+            .collect();
+        let default_block = codegen
+            .context
+            .append_basic_block(function, "match_default");
+        let no_match_block = codegen.context.append_basic_block(function, "no_match");
+        let end_block = codegen.context.append_basic_block(function, "end_match");
+
+        // Arms whose top-level pattern is a wildcard or a binding fit every case
+        let case_values: Vec<_> = self
+            .arms
+            .iter()
+            .map(|arm| codegen.case_value(&arm.pattern))
+            .collect();
+        let mut cases: Vec<(IntValue, BasicBlock)> = Vec::new();
+        for case_value in case_values.iter().flatten() {
+            if cases.iter().all(|(other, _)| other != case_value) {
+                let case_block = codegen.context.append_basic_block(function, "match_case");
+                cases.push((*case_value, case_block));
+            }
+        }
+
+        if cases.is_empty() {
+            codegen.builder.build_unconditional_branch(default_block)?;
+        } else {
+            let switch_value = match value {
+                BasicValueEnum::StructValue(enum_value) => codegen
+                    .builder
+                    .build_extract_value(enum_value, 0, "tag")?
+                    .into_int_value(),
+                other => other.into_int_value(),
+            };
+            codegen
+                .builder
+                .build_switch(switch_value, default_block, &cases)?;
+        }
+
+        for (case_value, case_block) in cases
+            .iter()
+            .map(|(case_value, case_block)| (Some(*case_value), *case_block))
+            .chain([(None, default_block)])
+        {
+            codegen.builder.position_at_end(case_block);
+
+            for (i, arm) in self.arms.iter().enumerate() {
+                if case_values[i].is_some_and(|arm_value| Some(arm_value) != case_value) {
+                    continue;
+                }
+
+                // The top-level pattern already matched, only its fields are left to test
+                let next_block = codegen.context.append_basic_block(function, "match_next");
+                match &arm.pattern.kind {
+                    PatternKind::Variant(_, fields) | PatternKind::Tuple(fields) => {
+                        let (fields_ptr, fields_ty, field_tys) =
+                            codegen.build_fields_ptr(&arm.pattern, value_ptr, &value_ty)?;
+                        codegen.build_fields_test(
+                            fields,
+                            fields_ptr,
+                            fields_ty,
+                            &field_tys,
+                            arm_blocks[i],
+                            next_block,
+                        )?;
+                    }
+                    _ => {
+                        codegen.builder.build_unconditional_branch(arm_blocks[i])?;
+                    }
+                }
+                codegen.builder.position_at_end(next_block);
+            }
+
+            codegen.builder.build_unconditional_branch(no_match_block)?;
+        }
+
+        // Type checking guarantees that one of the arms matches
+        codegen.builder.position_at_end(no_match_block);
+        codegen.builder.build_unreachable()?;
+
+        let mut incoming = Vec::new();
+        for (arm, arm_block) in self.arms.into_iter().zip(arm_blocks) {
+            codegen.builder.position_at_end(arm_block);
+            codegen.build_pattern_bindings(&arm.pattern, value_ptr, &value_ty)?;
+
+            match generate_arm_body(codegen, arm.body)? {
+                Some(arm_value) => {
+                    let block = codegen
+                        .builder
+                        .get_insert_block()
+                        .expect("builder to be positioned inside of a function");
+                    incoming.push((arm_value, block));
+                    codegen.builder.build_unconditional_branch(end_block)?;
+                }
+                // A diverging arm must not reach the phi, because it has no value for it
+                None if ty.as_llvm_type(codegen).is_some() => {
+                    codegen.builder.build_unreachable()?;
+                }
+                None => codegen.build_branch_if_open(end_block)?,
+            }
+        }
+
+        codegen.builder.position_at_end(end_block);
+        let Some(llvm_ty) = ty.as_llvm_type(codegen) else {
+            return Ok(None);
+        };
+
+        let phi = codegen.builder.build_phi(llvm_ty, "match")?;
+        for (arm_value, block) in &incoming {
+            phi.add_incoming(&[(arm_value as &dyn BasicValue, *block)]);
+        }
+        Ok(Some(phi.as_basic_value()))
+    }
+}
+
+/// Generates the body of an arm, which is a compound if the `match` is used as a statement
+fn generate_arm_body<'ctx>(
+    codegen: &CodegenContext<'ctx>,
+    body: Expr,
+) -> Result<Option<BasicValueEnum<'ctx>>> {
+    match body.kind {
+        ExprKind::Compound(compound) => {
+            let _ = compound.codegen(codegen)?;
+            Ok(None)
+        }
+        kind => Expr { kind, ..body }.codegen(codegen),
+    }
+}
+
+impl<'cx> CodegenContext<'cx> {
+    /// Returns the value that is compared by the `switch` of a `match` for the top-level pattern, or `None` if the
+    /// pattern matches every value
+    fn case_value(&self, pattern: &Pattern) -> Option<IntValue<'cx>> {
+        match &pattern.kind {
+            PatternKind::Variant(variant, _) => Some(
+                self.context
+                    .i32_type()
+                    .const_int(variant.index().into(), false),
+            ),
+            PatternKind::Literal(literal) => Some(
+                self.generate_const_value(literal)
+                    .expect("literal patterns to have a value")
+                    .into_int_value(),
+            ),
+            PatternKind::Wildcard | PatternKind::Binding(_) | PatternKind::Tuple(_) => None,
+        }
+    }
+
+    /// Tests whether the value of type `ty` at `ptr` matches `pattern` and branches to `on_match` or `on_mismatch`
+    fn build_pattern_test(
+        &self,
+        pattern: &Pattern,
+        ptr: PointerValue<'cx>,
+        ty: &ast::Type,
+        on_match: BasicBlock<'cx>,
+        on_mismatch: BasicBlock<'cx>,
+    ) -> Result<()> {
+        match &pattern.kind {
+            PatternKind::Wildcard | PatternKind::Binding(_) => {
+                self.builder.build_unconditional_branch(on_match)?;
+            }
+            PatternKind::Literal(literal) => {
+                let expected = self
+                    .generate_const_value(literal)
+                    .expect("literal patterns to have a value")
+                    .into_int_value();
+                let value = self
+                    .builder
+                    .build_load(expected.get_type(), ptr, "value")?
+                    .into_int_value();
+                let is_equal = self.builder.build_int_compare(
+                    IntPredicate::EQ,
+                    value,
+                    expected,
+                    "is_equal",
+                )?;
+                self.builder
+                    .build_conditional_branch(is_equal, on_match, on_mismatch)?;
+            }
+            PatternKind::Variant(variant, fields) => {
+                let enum_ty = enum_type(self, &variant.enum_name);
+                let tag_ptr = self.builder.build_struct_gep(enum_ty, ptr, 0, "tag_ptr")?;
+                let tag = self
+                    .builder
+                    .build_load(self.context.i32_type(), tag_ptr, "tag")?
+                    .into_int_value();
+                let is_variant = self.builder.build_int_compare(
+                    IntPredicate::EQ,
+                    tag,
+                    self.context
+                        .i32_type()
+                        .const_int(variant.index().into(), false),
+                    "is_variant",
+                )?;
+
+                let fields_block = self
+                    .context
+                    .append_basic_block(self.current_function(), "match_fields");
+                self.builder
+                    .build_conditional_branch(is_variant, fields_block, on_mismatch)?;
+                self.builder.position_at_end(fields_block);
+
+                let (fields_ptr, fields_ty, field_tys) = self.build_fields_ptr(pattern, ptr, ty)?;
+                self.build_fields_test(
+                    fields,
+                    fields_ptr,
+                    fields_ty,
+                    &field_tys,
+                    on_match,
+                    on_mismatch,
+                )?;
+            }
+            PatternKind::Tuple(elements) => {
+                let (fields_ptr, fields_ty, field_tys) = self.build_fields_ptr(pattern, ptr, ty)?;
+                self.build_fields_test(
+                    elements,
+                    fields_ptr,
+                    fields_ty,
+                    &field_tys,
+                    on_match,
+                    on_mismatch,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tests the fields of type `fields_ty` at `fields_ptr` against the patterns `fields`, where `field_tys` are the
+    /// types of the fields
+    fn build_fields_test(
+        &self,
+        fields: &[Pattern],
+        fields_ptr: PointerValue<'cx>,
+        fields_ty: StructType<'cx>,
+        field_tys: &[ast::Type],
+        on_match: BasicBlock<'cx>,
+        on_mismatch: BasicBlock<'cx>,
+    ) -> Result<()> {
+        let Some((last, rest)) = fields.split_last() else {
+            self.builder.build_unconditional_branch(on_match)?;
+            return Ok(());
+        };
+
+        // Every field that matches continues with the test of the next field
+        for (i, field) in rest.iter().enumerate() {
+            let next_block = self
+                .context
+                .append_basic_block(self.current_function(), "match_field");
+            let field_ptr = self
+                .builder
+                .build_struct_gep(fields_ty, fields_ptr, i as u32, "field")?;
+            self.build_pattern_test(field, field_ptr, &field_tys[i], next_block, on_mismatch)?;
+            self.builder.position_at_end(next_block);
+        }
+
+        let last_ptr =
+            self.builder
+                .build_struct_gep(fields_ty, fields_ptr, rest.len() as u32, "field")?;
+        self.build_pattern_test(
+            last,
+            last_ptr,
+            &field_tys[rest.len()],
+            on_match,
+            on_mismatch,
+        )
+    }
+
+    /// Stores the parts of the value of type `ty` at `ptr` that are bound by `pattern` in their variables
+    pub fn build_pattern_bindings(
+        &self,
+        pattern: &Pattern,
+        ptr: PointerValue<'cx>,
+        ty: &ast::Type,
+    ) -> Result<()> {
+        match &pattern.kind {
+            PatternKind::Binding(variable) => {
+                // Values of type `()` are not stored
+                let Some(llvm_ty) = ty.as_llvm_type(self) else {
+                    return Ok(());
+                };
+
+                let name = variable.get().name.clone();
+                let value = self.builder.build_load(llvm_ty, ptr, &name)?;
+                let variable_ptr = self.generate_variable_alloca(llvm_ty, &name)?;
+                self.builder.build_store(variable_ptr, value)?;
+                self.variables
+                    .borrow_mut()
+                    .insert(variable.clone(), variable_ptr);
+            }
+            PatternKind::Variant(_, fields) | PatternKind::Tuple(fields) => {
+                let (fields_ptr, fields_ty, field_tys) = self.build_fields_ptr(pattern, ptr, ty)?;
+
+                for (i, (field, field_ty)) in fields.iter().zip(&field_tys).enumerate() {
+                    if field.bindings().is_empty() {
+                        continue;
+                    }
+
+                    let field_ptr = self
+                        .builder
+                        .build_struct_gep(fields_ty, fields_ptr, i as u32, "field")?;
+                    self.build_pattern_bindings(field, field_ptr, field_ty)?;
+                }
+            }
+            PatternKind::Wildcard | PatternKind::Literal(_) => {}
+        }
+
+        Ok(())
+    }
+
+    /// Returns a pointer to the fields of an enum value at `ptr`, which is known to be `variant`
+    pub fn build_variant_fields_ptr(
+        &self,
+        ptr: PointerValue<'cx>,
+        variant: &VariantRef,
+    ) -> Result<PointerValue<'cx>> {
+        let enum_ty = enum_type(self, &variant.enum_name);
+        let payload_ptr = self.builder.build_struct_gep(enum_ty, ptr, 1, "payload")?;
+        let variant_ty = variant_type(self, &variant.enum_name, variant.index());
+
+        Ok(self.builder.build_pointer_cast(
+            payload_ptr,
+            variant_ty.ptr_type(AddressSpace::default()),
+            "fields",
+        )?)
+    }
+
+    /// Returns a pointer to the fields of the value of type `ty` at `ptr` that are matched by the variant or tuple
+    /// `pattern`, together with the LLVM type and the types of the fields. A variant must already be known to match.
+    fn build_fields_ptr(
+        &self,
+        pattern: &Pattern,
+        ptr: PointerValue<'cx>,
+        ty: &ast::Type,
+    ) -> Result<(PointerValue<'cx>, StructType<'cx>, Vec<ast::Type>)> {
+        match (&pattern.kind, ty) {
+            (PatternKind::Variant(variant, _), _) => Ok((
+                self.build_variant_fields_ptr(ptr, variant)?,
+                variant_type(self, &variant.enum_name, variant.index()),
+                self.variant_field_tys(variant),
+            )),
+            (PatternKind::Tuple(_), ast::Type::Tuple(element_tys)) => {
+                Ok((ptr, tuple_type(self, element_tys), element_tys.clone()))
+            }
+            _ => unreachable!("only variant patterns and tuple patterns of tuples to have fields"),
+        }
+    }
+
+    fn variant_field_tys(&self, variant: &VariantRef) -> Vec<ast::Type> {
+        let enum_ref = self.sym.get_enum(&variant.enum_name);
+        let field_tys = enum_ref.get().variants[variant.index() as usize]
+            .fields
+            .clone();
+        field_tys
+    }
+}
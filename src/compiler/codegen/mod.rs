@@ -1,45 +1,112 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::Path;
 use std::rc::Rc;
 
 use anyhow::{anyhow, Result};
-use anyhow::{bail, Context as AnyhowContext};
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::basic_block::BasicBlock;
 use inkwell::builder::Builder;
 use inkwell::context::Context;
+use inkwell::intrinsics::Intrinsic;
 use inkwell::module::{Linkage, Module};
-use inkwell::types::{BasicMetadataTypeEnum, BasicType};
-use inkwell::values::FunctionValue;
+use inkwell::targets::{
+    CodeModel, InitializationConfig, RelocMode, Target, TargetMachine, TargetTriple,
+};
+use inkwell::types::{BasicMetadataTypeEnum, BasicTypeEnum, IntType};
+use inkwell::values::{
+    BasicMetadataValueEnum, BasicValueEnum, FunctionValue, GlobalValue, IntValue, PointerValue,
+    StructValue,
+};
+use inkwell::{AddressSpace, IntPredicate, OptimizationLevel};
 
-use crate::compiler::codegen::types::{CompoundReturnType, Type};
-use crate::compiler::parser::ast::{CompoundExpr, Expr, FunctionDefinition, Root};
+use crate::compiler::codegen::abi::{Abi, FnAbi, PassMode};
+use crate::compiler::codegen::runtime::RuntimeFunction;
+use crate::compiler::codegen::types::{enum_type, struct_type, tuple_type, variant_type, Type};
+use crate::compiler::options::Options;
+use crate::compiler::parser::ast::{
+    self, BinaryOp, Builtin, CompoundExpr, Expr, ExprKind, FunctionDefinition, Literal, Root,
+    StaticDefinition,
+};
 use crate::compiler::ref_arena::ArenaRef;
 use crate::compiler::semantic_analysis::AnalyzedAST;
-use crate::compiler::symbol_table::Function;
+use crate::compiler::span::Span;
 use crate::compiler::symbol_table::Sym;
+use crate::compiler::symbol_table::{ConstValue, Function, Static, Variable};
 
+mod abi;
+mod matching;
+mod runtime;
 mod types;
 
-pub fn generate(ast_root: AnalyzedAST, sym: Sym, llvm_ir_out: &Path) -> Result<()> {
+pub fn generate(
+    ast_root: AnalyzedAST,
+    sym: Sym,
+    src: &Path,
+    src_code: &str,
+    llvm_ir_out: &Path,
+    options: &Options,
+) -> Result<()> {
     let context = Rc::new(Context::create());
+    let triple = TargetMachine::get_default_triple();
+    let codegen = generate_module(&context, ast_root, sym, src, src_code, options, &triple)?;
+
+    eprintln!("--- LLVM IR ---");
+    codegen.module.print_to_stderr();
+    codegen.module.write_bitcode_to_path(llvm_ir_out);
 
+    Ok(())
+}
+
+/// Generates the LLVM module for the whole program inside of `context`, which is compiled for the target `triple`
+fn generate_module<'cx>(
+    context: &'cx Context,
+    ast_root: AnalyzedAST,
+    sym: Sym,
+    src: &Path,
+    src_code: &str,
+    options: &Options,
+    triple: &TargetTriple,
+) -> Result<CodegenContext<'cx>> {
     let builder = context.create_builder();
     let module = context.create_module("main_module");
 
+    // How structs are passed to C depends on the target, so it is fixed here instead of being left to `llc`
+    let abi = Abi::from_triple(&triple.as_str().to_string_lossy())?;
+    Target::initialize_native(&InitializationConfig::default())
+        .map_err(|err| anyhow!("Failed to initialize the native target: {err}"))?;
+    let target = Target::from_triple(triple).map_err(|err| anyhow!("Unsupported target: {err}"))?;
+    let target_machine = target
+        .create_target_machine(
+            triple,
+            "generic",
+            "",
+            OptimizationLevel::Default,
+            RelocMode::Default,
+            CodeModel::Default,
+        )
+        .ok_or_else(|| anyhow!("Failed to create a target machine"))?;
+    module.set_triple(triple);
+    module.set_data_layout(&target_machine.get_target_data().get_data_layout());
+
     let mut codegen = CodegenContext {
-        context: &context,
+        context,
         builder,
         module,
         sym,
+        abi,
         functions: HashMap::new(),
+        statics: HashMap::new(),
+        variables: RefCell::new(HashMap::new()),
+        source_file: src.display().to_string(),
+        source_code: src_code.to_owned(),
+        overflow_checks: options.overflow_checks(),
+        debug_assertions: options.debug_assertions(),
     };
 
     ast_root.ast.codegen(&mut codegen)?;
 
-    eprintln!("--- LLVM IR ---");
-    codegen.module.print_to_stderr();
-    codegen.module.write_bitcode_to_path(llvm_ir_out);
-
-    Ok(())
+    Ok(codegen)
 }
 
 /// Context for code generation
@@ -48,36 +115,545 @@ pub struct CodegenContext<'cx> {
     builder: Builder<'cx>,
     module: Module<'cx>,
     sym: Sym,
-    functions: HashMap<String, FunctionValue<'cx>>,
+    /// Calling convention of the target, which decides how structs are passed between functions
+    abi: Abi,
+    functions: HashMap<ArenaRef<Function>, FunctionValue<'cx>>,
+    statics: HashMap<ArenaRef<Static>, GlobalValue<'cx>>,
+    /// Stack memory of all variables that have a value, variables of type `()` are not stored
+    variables: RefCell<HashMap<ArenaRef<Variable>, PointerValue<'cx>>>,
+    /// Path of the compiled source file, which is reported by panics
+    source_file: String,
+    /// Source code of the compiled file, from which assertions take the text they print when they fail
+    source_code: String,
+    /// Whether `+`, `-` and `*` panic on overflow
+    overflow_checks: bool,
+    /// Whether assertions are checked, otherwise they are compiled out
+    debug_assertions: bool,
 }
 
 impl<'cx> CodegenContext<'cx> {
-    /// Fails if codegen data is already attached
+    /// Must be called only once per function. Functions cannot be defined twice, because duplicate definitions are
+    /// rejected during semantic analysis.
     pub fn generate_function_value<'ctx>(
         &mut self,
         fn_sym: &ArenaRef<Function>,
     ) -> Result<FunctionValue> {
+        assert!(
+            !self.functions.contains_key(fn_sym),
+            "function value of `{}` to be generated only once",
+            fn_sym.get().name
+        );
+
         // Get information from symbol table
-        let fn_sym = fn_sym.get();
+        let function = fn_sym.get();
 
-        // Check if function value is generated already
-        if self.functions.get(&fn_sym.name).is_some() {
-            bail!("Failed to attach new function value to function {}. It already has data attached to it.", fn_sym.name);
+        // Generate function value
+        let abi = FnAbi::of(&function, self);
+        // The C runtime expects `main` to return an exit code, so a `main` returning `()` returns 0 instead. A `main`
+        // returning `!` never returns, but it is declared with the same signature, because C calls it as `int main()`.
+        let is_main_without_exit_code = function.name == "main"
+            && matches!(function.return_ty, ast::Type::Unit | ast::Type::Never);
+        let return_ty = if is_main_without_exit_code {
+            Type::BasicType(self.context.i32_type().into())
+        } else {
+            match abi.return_type() {
+                Some(ty) => Type::BasicType(ty),
+                None => Type::Void(self.context.void_type()),
+            }
+        };
+        let fn_ty = return_ty.fn_type(&abi.param_types(), false);
+        let fn_value = self.module.add_function(&function.name, fn_ty, None);
+        for (location, attribute) in abi.attributes(self) {
+            fn_value.add_attribute(location, attribute);
         }
 
-        // Generate function value
-        let return_ty = Type::from_ast_type(&fn_sym.return_ty, self);
-        let fn_ty = return_ty.fn_type(&[], false);
-        let fn_value = self.module.add_function(&fn_sym.name, fn_ty, None);
+        if function.return_ty == ast::Type::Never {
+            let noreturn = Attribute::get_named_enum_kind_id("noreturn");
+            fn_value.add_attribute(
+                AttributeLoc::Function,
+                self.context.create_enum_attribute(noreturn, 0),
+            );
+        }
 
-        self.functions.insert(fn_sym.name.clone(), fn_value);
+        self.functions.insert(fn_sym.clone(), fn_value);
 
         Ok(fn_value)
     }
+
+    /// Places the bytes of a string literal in a private global constant and returns a `str` value (pointer + length) referencing it
+    pub fn generate_str_literal(&self, value: &str) -> StructValue<'cx> {
+        let bytes = self.context.const_string(value.as_bytes(), false);
+
+        let global = self
+            .module
+            .add_global(bytes.get_type(), None, "str_literal");
+        global.set_initializer(&bytes);
+        global.set_constant(true);
+        global.set_linkage(Linkage::Private);
+        global.set_unnamed_addr(true);
+
+        let ptr = global
+            .as_pointer_value()
+            .const_cast(self.context.i8_type().ptr_type(AddressSpace::default()));
+        let len = self.context.i64_type().const_int(value.len() as u64, false);
+
+        self.context.const_struct(&[ptr.into(), len.into()], false)
+    }
+
+    /// Returns the value of an expression that was evaluated at compile time, or `None` if it is of the unit type
+    pub fn generate_const_value(&self, value: &ConstValue) -> Option<BasicValueEnum<'cx>> {
+        match value {
+            ConstValue::Unit => None,
+            ConstValue::Int(value) => Some(
+                self.context
+                    .i32_type()
+                    .const_int(*value as u64, true)
+                    .into(),
+            ),
+            ConstValue::Str(value) => Some(self.generate_str_literal(value).into()),
+            ConstValue::Char(value) => Some(
+                self.context
+                    .i32_type()
+                    .const_int(*value as u64, false)
+                    .into(),
+            ),
+            ConstValue::Bool(value) => Some(
+                self.context
+                    .bool_type()
+                    .const_int(*value as u64, false)
+                    .into(),
+            ),
+            ConstValue::Array(element_ty, elements) => {
+                let values = elements.iter().map(|element| {
+                    self.generate_const_value(element)
+                        .expect("array elements to have a type with values")
+                });
+
+                let array = match element_ty
+                    .as_llvm_type(self)
+                    .expect("array elements to have a type with values")
+                {
+                    BasicTypeEnum::IntType(ty) => ty.const_array(
+                        &values
+                            .map(BasicValueEnum::into_int_value)
+                            .collect::<Vec<_>>(),
+                    ),
+                    BasicTypeEnum::StructType(ty) => ty.const_array(
+                        &values
+                            .map(BasicValueEnum::into_struct_value)
+                            .collect::<Vec<_>>(),
+                    ),
+                    BasicTypeEnum::ArrayType(ty) => ty.const_array(
+                        &values
+                            .map(BasicValueEnum::into_array_value)
+                            .collect::<Vec<_>>(),
+                    ),
+                    other => {
+                        unreachable!("array elements of LLVM type `{other}` to be unsupported")
+                    }
+                };
+
+                Some(array.into())
+            }
+            ConstValue::Struct(name, fields) => {
+                let values: Vec<_> = fields
+                    .iter()
+                    .map(|(_, value)| {
+                        self.generate_const_value(value)
+                            .expect("struct fields to have a type with values")
+                    })
+                    .collect();
+
+                Some(struct_type(self, name).const_named_struct(&values).into())
+            }
+            ConstValue::Tuple(elements) => {
+                let values: Vec<_> = elements
+                    .iter()
+                    .map(|element| {
+                        self.generate_const_value(element)
+                            .expect("tuple elements to have a type with values")
+                    })
+                    .collect();
+
+                Some(self.context.const_struct(&values, false).into())
+            }
+            ConstValue::Variant { .. } => {
+                unreachable!("values of enums to always be built at runtime")
+            }
+        }
+    }
+
+    /// Terminates the current block with `unreachable` after a diverging expression. Code generation continues in a new
+    /// block without predecessors, so that code following the diverging expression can still be generated.
+    pub fn generate_unreachable(&self) -> Result<()> {
+        self.builder.build_unreachable()?;
+
+        let block = self
+            .context
+            .append_basic_block(self.current_function(), "unreachable");
+        self.builder.position_at_end(block);
+
+        Ok(())
+    }
+
+    /// Panics unless `value` is a unicode scalar value, which excludes negative values, surrogates and values above
+    /// `char::MAX`
+    pub fn build_char_check(&self, value: IntValue<'cx>, span: Span) -> Result<()> {
+        let i32_ty = self.context.i32_type();
+
+        // Negative values are too large as well, because the comparison is unsigned
+        let too_large = self.builder.build_int_compare(
+            IntPredicate::UGT,
+            value,
+            i32_ty.const_int(char::MAX.into(), false),
+            "too_large",
+        )?;
+        // Surrogates are the values from 0xD800 to 0xDFFF, which only differ in their lowest 11 bits
+        let high_bits =
+            self.builder
+                .build_and(value, i32_ty.const_int(0xFFFF_F800, false), "high_bits")?;
+        let is_surrogate = self.builder.build_int_compare(
+            IntPredicate::EQ,
+            high_bits,
+            i32_ty.const_int(0xD800, false),
+            "is_surrogate",
+        )?;
+        let invalid = self
+            .builder
+            .build_or(too_large, is_surrogate, "invalid_char")?;
+
+        self.build_panic_if(
+            invalid,
+            "attempt to convert an `int` that is not a unicode scalar value to `char`",
+            span,
+        )
+    }
+
+    /// Branches to `target`, unless the current block is already terminated, e.g. by a return
+    pub fn build_branch_if_open(&self, target: BasicBlock<'cx>) -> Result<()> {
+        let block = self
+            .builder
+            .get_insert_block()
+            .expect("builder to be positioned inside of a function");
+
+        if block.get_terminator().is_none() {
+            self.builder.build_unconditional_branch(target)?;
+        }
+
+        Ok(())
+    }
+
+    /// Allocates stack memory for a variable in the entry block of the current function.
+    /// This way the memory is only allocated once, even if the variable is declared inside of a loop.
+    pub fn generate_variable_alloca(
+        &self,
+        ty: BasicTypeEnum<'cx>,
+        name: &str,
+    ) -> Result<PointerValue<'cx>> {
+        let entry_block = self
+            .current_function()
+            .get_first_basic_block()
+            .expect("current function to have an entry block");
+
+        let entry_builder = self.context.create_builder();
+        match entry_block.get_first_instruction() {
+            Some(instruction) => entry_builder.position_before(&instruction),
+            None => entry_builder.position_at_end(entry_block),
+        }
+
+        Ok(entry_builder.build_alloca(ty, name)?)
+    }
+
+    /// Returns from a function returning `()`. Only `main` returns an exit code of 0 instead of nothing.
+    pub fn build_unit_return(&self) -> Result<()> {
+        match self.current_function().get_type().get_return_type() {
+            Some(exit_code_ty) => {
+                let exit_code = exit_code_ty.into_int_type().const_zero();
+                self.builder.build_return(Some(&exit_code))?;
+            }
+            None => {
+                self.builder.build_return(None)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Calls the LLVM intrinsic `name`, which is overloaded for the type of its operands
+    fn build_int_intrinsic_call(
+        &self,
+        name: &str,
+        lhs: IntValue<'cx>,
+        rhs: IntValue<'cx>,
+    ) -> Result<BasicValueEnum<'cx>> {
+        let intrinsic = Intrinsic::find(name).expect("LLVM intrinsic to exist");
+        let declaration = intrinsic
+            .get_declaration(&self.module, &[lhs.get_type().into()])
+            .expect("LLVM intrinsic to be overloaded for ints");
+
+        let call = self
+            .builder
+            .build_call(declaration, &[lhs.into(), rhs.into()], name)?;
+        Ok(call
+            .try_as_basic_value()
+            .left()
+            .expect("LLVM intrinsic to return a value"))
+    }
+
+    /// Performs an arithmetic operation with one of the `llvm.*.with.overflow` intrinsics. If the result overflows, the
+    /// program panics with `message` at the location of `span`.
+    pub fn build_checked_arithmetic(
+        &self,
+        intrinsic: &str,
+        lhs: IntValue<'cx>,
+        rhs: IntValue<'cx>,
+        message: &str,
+        span: Span,
+    ) -> Result<IntValue<'cx>> {
+        let result = self
+            .build_int_intrinsic_call(intrinsic, lhs, rhs)?
+            .into_struct_value();
+        let value = self.builder.build_extract_value(result, 0, "value")?;
+        let overflowed = self.builder.build_extract_value(result, 1, "overflowed")?;
+
+        self.build_panic_if(overflowed.into_int_value(), message, span)?;
+        Ok(value.into_int_value())
+    }
+
+    /// Panics with `message` at the location of `span` if `condition` is true, otherwise code generation continues in a
+    /// new block
+    pub fn build_panic_if(
+        &self,
+        condition: IntValue<'cx>,
+        message: &str,
+        span: Span,
+    ) -> Result<()> {
+        self.build_diverging_branch(condition, || {
+            self.build_panic(self.generate_str_literal(message), span)
+        })
+    }
+
+    /// Branches to a new block if `condition` is true, in which `build_panic` generates a call that never returns.
+    /// Otherwise code generation continues in another new block.
+    fn build_diverging_branch(
+        &self,
+        condition: IntValue<'cx>,
+        build_panic: impl FnOnce() -> Result<()>,
+    ) -> Result<()> {
+        let function = self.current_function();
+        let panic_block = self.context.append_basic_block(function, "panic");
+        let continue_block = self.context.append_basic_block(function, "no_panic");
+        self.builder
+            .build_conditional_branch(condition, panic_block, continue_block)?;
+
+        self.builder.position_at_end(panic_block);
+        build_panic()?;
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(continue_block);
+        Ok(())
+    }
+
+    /// Calls the runtime to print the `str` value `message` together with the location of `span` and exit. The
+    /// current block still has to be terminated afterwards.
+    pub fn build_panic(&self, message: StructValue<'cx>, span: Span) -> Result<()> {
+        let mut args = self.build_panic_location_args(span)?;
+        args.extend(self.build_str_args(message, "message")?);
+
+        self.builder
+            .build_call(RuntimeFunction::Panic.get_or_declare(self), &args, "")?;
+
+        Ok(())
+    }
+
+    /// Panics if `lhs` and `rhs` of type `ty` are not equal. The runtime prints both values together with `text`, the
+    /// source code of the assertion.
+    pub fn build_assert_eq(
+        &self,
+        lhs: IntValue<'cx>,
+        rhs: IntValue<'cx>,
+        ty: ast::Type,
+        text: &str,
+        span: Span,
+    ) -> Result<()> {
+        let equal = self
+            .builder
+            .build_int_compare(IntPredicate::EQ, lhs, rhs, "equal")?;
+        let not_equal = self.builder.build_not(equal, "not_equal")?;
+
+        self.build_diverging_branch(not_equal, || {
+            // The runtime expects the type of the values as one of the `ASSERT_EQ_*` constants
+            let i32_ty = self.context.i32_type();
+            let type_tag = match ty {
+                ast::Type::Bool => 1,
+                ast::Type::Char => 2,
+                _ => 0,
+            };
+
+            let mut args = self.build_panic_location_args(span)?;
+            args.extend(self.build_str_args(self.generate_str_literal(text), "text")?);
+            args.push(i32_ty.const_int(type_tag, false).into());
+            for value in [lhs, rhs] {
+                let value = self
+                    .builder
+                    .build_int_z_extend_or_bit_cast(value, i32_ty, "value")?;
+                args.push(value.into());
+            }
+
+            self.builder.build_call(
+                RuntimeFunction::AssertEqFailed.get_or_declare(self),
+                &args,
+                "",
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Returns the number of elements of `value`, which is an array or slice of type `ty`
+    pub fn build_len(&self, value: BasicValueEnum<'cx>, ty: &ast::Type) -> Result<IntValue<'cx>> {
+        Ok(match ty {
+            ast::Type::Array(_, len) => self.context.i32_type().const_int((*len).into(), false),
+            _ => self
+                .builder
+                .build_extract_value(value.into_struct_value(), 1, "len")?
+                .into_int_value(),
+        })
+    }
+
+    /// Returns a pointer to the first element of `value`, which is an array or slice of type `ty`, together with its
+    /// number of elements. Elements can only be accessed at a dynamic index through memory, so arrays are copied to
+    /// the stack.
+    pub fn build_elements_ptr(
+        &self,
+        value: BasicValueEnum<'cx>,
+        ty: &ast::Type,
+    ) -> Result<(PointerValue<'cx>, IntValue<'cx>)> {
+        let len = self.build_len(value, ty)?;
+
+        let ptr = match ty {
+            ast::Type::Array(..) => {
+                let array_ty = value.get_type();
+                let array_ptr = self.generate_variable_alloca(array_ty, "array")?;
+                self.builder.build_store(array_ptr, value)?;
+
+                let zero = self.context.i32_type().const_zero();
+                // SAFETY: The first element of an array is always in bounds, even if the array is empty
+                unsafe {
+                    self.builder.build_in_bounds_gep(
+                        array_ty,
+                        array_ptr,
+                        &[zero, zero],
+                        "elements",
+                    )?
+                }
+            }
+            _ => self
+                .builder
+                .build_extract_value(value.into_struct_value(), 0, "elements")?
+                .into_pointer_value(),
+        };
+
+        Ok((ptr, len))
+    }
+
+    /// Panics if `index` is not smaller than `len`. Negative indices are out of bounds as well, because the comparison
+    /// is unsigned.
+    pub fn build_bounds_check(
+        &self,
+        index: IntValue<'cx>,
+        len: IntValue<'cx>,
+        span: Span,
+    ) -> Result<()> {
+        let out_of_bounds =
+            self.builder
+                .build_int_compare(IntPredicate::UGE, index, len, "out_of_bounds")?;
+
+        self.build_diverging_branch(out_of_bounds, || {
+            let mut args = self.build_panic_location_args(span)?;
+            args.extend([len.into(), index.into()]);
+
+            self.builder.build_call(
+                RuntimeFunction::IndexOutOfBounds.get_or_declare(self),
+                &args,
+                "",
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Panics unless `start <= end <= len`. Negative bounds are out of bounds as well, because the comparisons are
+    /// unsigned.
+    pub fn build_range_check(
+        &self,
+        start: IntValue<'cx>,
+        end: IntValue<'cx>,
+        len: IntValue<'cx>,
+        span: Span,
+    ) -> Result<()> {
+        let start_after_end =
+            self.builder
+                .build_int_compare(IntPredicate::UGT, start, end, "start_after_end")?;
+        let end_after_len =
+            self.builder
+                .build_int_compare(IntPredicate::UGT, end, len, "end_after_len")?;
+        let out_of_bounds =
+            self.builder
+                .build_or(start_after_end, end_after_len, "out_of_bounds")?;
+
+        self.build_diverging_branch(out_of_bounds, || {
+            let mut args = self.build_panic_location_args(span)?;
+            args.extend([len.into(), start.into(), end.into()]);
+
+            self.builder.build_call(
+                RuntimeFunction::RangeOutOfBounds.get_or_declare(self),
+                &args,
+                "",
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Returns the arguments describing the location of `span` for the panic functions of the runtime
+    fn build_panic_location_args(&self, span: Span) -> Result<Vec<BasicMetadataValueEnum<'cx>>> {
+        let i32_ty = self.context.i32_type();
+
+        let mut args = self.build_str_args(self.generate_str_literal(&self.source_file), "file")?;
+        args.push(i32_ty.const_int(span.start.line.into(), false).into());
+        args.push(i32_ty.const_int(span.start.column.into(), false).into());
+
+        Ok(args)
+    }
+
+    /// Splits a `str` value into its pointer and length, which is how strings are passed to the runtime
+    fn build_str_args(
+        &self,
+        value: StructValue<'cx>,
+        name: &str,
+    ) -> Result<Vec<BasicMetadataValueEnum<'cx>>> {
+        let ptr = self
+            .builder
+            .build_extract_value(value, 0, &format!("{name}_ptr"))?;
+        let len = self
+            .builder
+            .build_extract_value(value, 1, &format!("{name}_len"))?;
+
+        Ok(vec![ptr.into(), len.into()])
+    }
+
+    fn current_function(&self) -> FunctionValue<'cx> {
+        self.builder
+            .get_insert_block()
+            .and_then(|block| block.get_parent())
+            .expect("builder to be positioned inside of a function")
+    }
 }
 
 impl Root {
     fn codegen(self, codegen: &mut CodegenContext) -> Result<()> {
+        for static_def in &self.statics {
+            static_def.codegen(codegen);
+        }
+
         // First generate all LLVM function types so they are already available when building call expressions later
         // They are collected into the filed `codegen.functions`
         for f in &self.functions {
@@ -91,109 +667,1216 @@ impl Root {
     }
 }
 
+impl StaticDefinition {
+    /// Generates a global variable that is initialized with the initial value of the static. Statics are only visible
+    /// inside of the module and immutable statics are marked as constant, so that LLVM can fold loads of them.
+    fn codegen(&self, codegen: &mut CodegenContext) {
+        let static_sym = self.sym.get();
+        let initial_value = codegen
+            .generate_const_value(static_sym.initial_value())
+            .expect("statics to have a type with values");
+
+        let global = codegen
+            .module
+            .add_global(initial_value.get_type(), None, &static_sym.name);
+        global.set_initializer(&initial_value);
+        global.set_constant(!static_sym.is_mutable);
+        global.set_linkage(Linkage::Internal);
+
+        codegen.statics.insert(self.sym.clone(), global);
+    }
+}
+
 impl FunctionDefinition {
     fn codegen(self, codegen: &CodegenContext) -> Result<()> {
-        let fn_sym = self.sym.get();
-
-        let Some(fn_value) = codegen.functions.get(&fn_sym.name) else {
+        let Some(fn_value) = codegen.functions.get(&self.sym) else {
             panic!("LLVM Function value is not generated yet, but should have been. Lazy function value generation is not supported yet")
         };
 
         let block = codegen.context.append_basic_block(*fn_value, "");
         codegen.builder.position_at_end(block);
 
-        let actual_ret_ty = self.compound.codegen(codegen)?.into_type(codegen);
-        codegen.builder.clear_insertion_position();
+        // Parameters are stored like variables, so that they can be loaded in the same way
+        let abi = FnAbi::of(&self.sym.get(), codegen);
+        for (index, param) in self.sym.get().params.iter().enumerate() {
+            let abi_value = fn_value
+                .get_nth_param(abi.param_index(index))
+                .expect("function value to have a value for every parameter");
+            let value = codegen.build_value_from_abi(abi_value, abi.params[index])?;
+            let name = param.variable.get().name.clone();
+            let ptr = codegen.generate_variable_alloca(value.get_type(), &name)?;
+            codegen.builder.build_store(ptr, value)?;
+            codegen
+                .variables
+                .borrow_mut()
+                .insert(param.variable.clone(), ptr);
+        }
 
-        if actual_ret_ty.to_basic_type_enum() != fn_value.get_type().get_return_type() {
-            bail!(
-                "Expected function '{}' to return type '{:?}', but it actually returns a '{:?}'",
-                fn_sym.name,
-                fn_value.get_type().get_return_type(),
-                actual_ret_ty,
-            );
+        let return_ty = self.sym.get().return_ty.clone();
+        self.compound.codegen(codegen)?;
+
+        // Type checking guarantees that only functions returning `()` can reach the end of their body.
+        // In all other functions the last block can only be an unreachable block following a diverging expression.
+        let last_block = codegen
+            .builder
+            .get_insert_block()
+            .expect("builder to be positioned inside of the function");
+        if last_block.get_terminator().is_none() {
+            if return_ty == ast::Type::Unit {
+                codegen.build_unit_return()?;
+            } else {
+                codegen.builder.build_unreachable()?;
+            }
         }
+        codegen.builder.clear_insertion_position();
 
         Ok(())
     }
 }
 
 impl CompoundExpr {
-    /// Returns the type of value returned by this compound
-    fn codegen<'ctx>(self, codegen: &CodegenContext<'ctx>) -> Result<CompoundReturnType<'ctx>> {
+    /// Returns whether this compound never completes, because it returns or diverges.
+    /// In that case code generation of the surrounding compound is stopped as well, because all following code is unreachable.
+    fn codegen(self, codegen: &CodegenContext) -> Result<bool> {
         for e in self.expressions {
-            match e {
-                // If there is a return statement, cancel code generation of this compound, generate the return statement and return the type of the return value
-                Expr::Return(num) => {
-                    return Ok(CompoundReturnType::Explicit(generate_explicit_return(
-                        codegen, num,
-                    )?));
-                }
-                // If an inner compound explicitly returns a value, we stop generating code for this compound and return the type return value
-                Expr::Compound(inner_compound) => {
-                    if let CompoundReturnType::Explicit(ty) = inner_compound.codegen(codegen)? {
-                        return Ok(CompoundReturnType::Explicit(ty));
+            match e.kind {
+                // If there is a return statement, cancel code generation of this compound and generate the return statement
+                ExprKind::Return(value) => {
+                    generate_explicit_return(codegen, *value)?;
+                    return Ok(true);
+                }
+                // If an inner compound never completes, we stop generating code for this compound
+                ExprKind::Compound(inner_compound) => {
+                    if inner_compound.codegen(codegen)? {
+                        return Ok(true);
+                    }
+                }
+                // The code for all other expressions can simply be generated, as it does not affect control flow at a function level.
+                // Only if it diverges, the code generation for this compound is stopped.
+                kind => {
+                    let expr = Expr { kind, ..e };
+                    let diverges = expr.ty() == ast::Type::Never;
+                    let _ = expr.codegen(codegen)?;
+
+                    if diverges {
+                        return Ok(true);
                     }
                 }
-                // The code for all other expressions can simply be generated, as it does not affect control flow at a function level
-                other => other.codegen(codegen)?,
             }
         }
 
-        codegen.builder.build_return(None)?;
-        Ok(CompoundReturnType::ImplicitUnit)
+        Ok(false)
     }
 }
 
-fn generate_explicit_return<'ctx>(
-    codegen: &CodegenContext<'ctx>,
-    value: u32,
-) -> Result<Type<'ctx>> {
-    let return_ty = codegen.context.i32_type();
-    let return_val = return_ty.const_int(value as u64, false);
-    codegen.builder.build_return(Some(&return_val))?;
+fn generate_explicit_return(codegen: &CodegenContext, value: Expr) -> Result<()> {
+    let value_ty = value.ty();
+
+    match value.codegen(codegen)? {
+        Some(return_val) => {
+            let mode = PassMode::of(&value_ty, codegen)
+                .expect("returned value to have a type with values");
+            codegen.build_abi_return(return_val, mode)?;
+        }
+        None if value_ty == ast::Type::Unit => {
+            codegen.build_unit_return()?;
+        }
+        // The returned value diverges, so `unreachable` was generated already instead of returning
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// Generates a call to the runtime function printing a value of the type of `expr`
+fn generate_print(codegen: &CodegenContext, expr: Expr) -> Result<()> {
+    let ty = expr.ty();
+    let Some(value) = expr.codegen(codegen)? else {
+        // The printed value diverges, so there is nothing to print
+        return Ok(());
+    };
 
-    Ok(Type::BasicType(return_ty.as_basic_type_enum()))
+    let (runtime_fn, args): (_, Vec<BasicMetadataValueEnum>) = match ty {
+        ast::Type::Str => {
+            let str_value = value.into_struct_value();
+            let ptr = codegen
+                .builder
+                .build_extract_value(str_value, 0, "str_ptr")?;
+            let len = codegen
+                .builder
+                .build_extract_value(str_value, 1, "str_len")?;
+            (RuntimeFunction::PrintStr, vec![ptr.into(), len.into()])
+        }
+        ast::Type::Int => (RuntimeFunction::PrintInt, vec![value.into()]),
+        ast::Type::Char => (RuntimeFunction::PrintChar, vec![value.into()]),
+        ast::Type::Bool => {
+            let value = codegen.builder.build_int_z_extend(
+                value.into_int_value(),
+                codegen.context.i32_type(),
+                "bool_to_int",
+            )?;
+            (RuntimeFunction::PrintBool, vec![value.into()])
+        }
+        ast::Type::Unit
+        | ast::Type::Array(..)
+        | ast::Type::Slice(_)
+        | ast::Type::Tuple(_)
+        | ast::Type::Struct(_)
+        | ast::Type::Enum(_) => {
+            unreachable!("type checking to only allow printing values of printable types")
+        }
+        ast::Type::Never => unreachable!("values of type `!` to not exist"),
+    };
+
+    codegen
+        .builder
+        .build_call(runtime_fn.get_or_declare(codegen), &args, "")?;
+
+    Ok(())
 }
 
 impl Expr {
-    fn codegen(self, codegen: &CodegenContext) -> Result<()> {
-        match self {
-            Expr::Exit(exit_code) => {
+    /// Returns the value of this expression or `None` if it is of the unit type or diverges
+    fn codegen<'ctx>(self, codegen: &CodegenContext<'ctx>) -> Result<Option<BasicValueEnum<'ctx>>> {
+        let ty = self.ty();
+        let span = self.span;
+
+        // Expressions that were evaluated at compile time are replaced by their value
+        if let Some(value) = &self.value {
+            return Ok(codegen.generate_const_value(value));
+        }
+
+        match self.kind {
+            ExprKind::Constant(constant) => {
+                Ok(codegen.generate_const_value(constant.get().value()))
+            }
+            ExprKind::Exit(exit_code) => {
                 let void_ty = codegen.context.void_type();
                 let i_ty = codegen.context.i32_type();
                 let fn_type = void_ty.fn_type(&[BasicMetadataTypeEnum::from(i_ty)], false);
 
-                let fn_val =
-                    codegen
-                        .module
-                        .add_function("ExitProcess@4", fn_type, Some(Linkage::External));
+                let fn_val = codegen
+                    .module
+                    .get_function("ExitProcess@4")
+                    .unwrap_or_else(|| {
+                        codegen.module.add_function(
+                            "ExitProcess@4",
+                            fn_type,
+                            Some(Linkage::External),
+                        )
+                    });
 
                 codegen.builder.build_call(
                     fn_val,
                     &[i_ty.const_int(exit_code as u64, false).into()],
                     "tmpexitprocess",
                 )?;
+                codegen.generate_unreachable()?;
 
-                Ok(())
+                Ok(None)
             }
-            Expr::FnCall(name) => {
-                let fn_value = codegen
-                    .module
-                    .get_function(&name)
-                    .context(anyhow!("Unknown function `{name}` referenced"))?;
+            ExprKind::FnCall(call) => {
+                let function = call.function().clone();
+                let fn_value = *codegen
+                    .functions
+                    .get(&function)
+                    .expect("function value to be generated before any function bodies");
+
+                let mut args = Vec::new();
+                for arg in call.args {
+                    let Some(value) = arg.codegen(codegen)? else {
+                        // The argument diverges, so the function is never called
+                        return Ok(None);
+                    };
+                    args.push(value);
+                }
+
+                let value = codegen.build_fn_call(&function.get(), fn_value, args)?;
+                if ty == ast::Type::Never {
+                    codegen.generate_unreachable()?;
+                }
+
+                Ok(value)
+            }
+            ExprKind::Literal(Literal::Int(value)) => Ok(Some(
+                codegen
+                    .context
+                    .i32_type()
+                    .const_int(value as u64, false)
+                    .into(),
+            )),
+            ExprKind::Literal(Literal::Str(value)) => {
+                Ok(Some(codegen.generate_str_literal(&value).into()))
+            }
+            ExprKind::Literal(Literal::Char(value)) => Ok(Some(
+                codegen
+                    .context
+                    .i32_type()
+                    .const_int(value as u64, false)
+                    .into(),
+            )),
+            ExprKind::Literal(Literal::Bool(value)) => Ok(Some(
+                codegen
+                    .context
+                    .bool_type()
+                    .const_int(value as u64, false)
+                    .into(),
+            )),
+            ExprKind::Binary(lhs, op, rhs) if !op.is_comparison() => {
+                let int_ty = codegen.context.i32_type();
+                let lhs = generate_int_operand(codegen, *lhs, int_ty)?;
+                let rhs = generate_int_operand(codegen, *rhs, int_ty)?;
+
+                let checked_intrinsic = match op {
+                    BinaryOp::Add => {
+                        Some(("llvm.sadd.with.overflow", "attempt to add with overflow"))
+                    }
+                    BinaryOp::Subtract => Some((
+                        "llvm.ssub.with.overflow",
+                        "attempt to subtract with overflow",
+                    )),
+                    BinaryOp::Multiply => Some((
+                        "llvm.smul.with.overflow",
+                        "attempt to multiply with overflow",
+                    )),
+                    _ => None,
+                };
+                if let (Some((intrinsic, message)), true) =
+                    (checked_intrinsic, codegen.overflow_checks)
+                {
+                    let result =
+                        codegen.build_checked_arithmetic(intrinsic, lhs, rhs, message, span)?;
+                    return Ok(Some(result.into()));
+                }
+
+                // Division by zero and `int::MIN / -1` are undefined behavior in LLVM, so they are always checked
+                if let BinaryOp::Divide | BinaryOp::Remainder = op {
+                    let (zero_message, overflow_message) = if op == BinaryOp::Divide {
+                        (
+                            "attempt to divide by zero",
+                            "attempt to divide with overflow",
+                        )
+                    } else {
+                        (
+                            "attempt to calculate the remainder with a divisor of zero",
+                            "attempt to calculate the remainder with overflow",
+                        )
+                    };
+
+                    let is_zero = codegen.builder.build_int_compare(
+                        IntPredicate::EQ,
+                        rhs,
+                        int_ty.const_zero(),
+                        "is_zero",
+                    )?;
+                    codegen.build_panic_if(is_zero, zero_message, span)?;
+
+                    let is_min = codegen.builder.build_int_compare(
+                        IntPredicate::EQ,
+                        lhs,
+                        int_ty.const_int(i32::MIN as u64, true),
+                        "is_min",
+                    )?;
+                    let is_minus_one = codegen.builder.build_int_compare(
+                        IntPredicate::EQ,
+                        rhs,
+                        int_ty.const_all_ones(),
+                        "is_minus_one",
+                    )?;
+                    let overflows = codegen
+                        .builder
+                        .build_and(is_min, is_minus_one, "overflows")?;
+                    codegen.build_panic_if(overflows, overflow_message, span)?;
+                }
+
+                let result = match op {
+                    BinaryOp::Add => codegen.builder.build_int_add(lhs, rhs, "add")?,
+                    BinaryOp::Subtract => codegen.builder.build_int_sub(lhs, rhs, "sub")?,
+                    BinaryOp::Multiply => codegen.builder.build_int_mul(lhs, rhs, "mul")?,
+                    BinaryOp::Divide => codegen.builder.build_int_signed_div(lhs, rhs, "div")?,
+                    BinaryOp::Remainder => codegen.builder.build_int_signed_rem(lhs, rhs, "rem")?,
+                    _ => unreachable!("comparisons to be generated separately"),
+                };
 
-                codegen.builder.build_call(fn_value, &[], "call_fn")?;
-                Ok(())
+                Ok(Some(result.into()))
             }
-            Expr::Return(_) => {
+            ExprKind::Binary(lhs, op, rhs) => {
+                let operand_ty = if lhs.ty() == ast::Type::Never {
+                    rhs.ty()
+                } else {
+                    lhs.ty()
+                };
+                let operand_int_ty = int_type(&operand_ty, codegen);
+
+                // Booleans are ordered as unsigned values (`false < true`). Chars are always valid unicode scalar values
+                // and therefore fit into the positive range of an i32, so they can be compared like ints.
+                let is_signed = operand_ty != ast::Type::Bool;
+
+                let lhs = generate_int_operand(codegen, *lhs, operand_int_ty)?;
+                let rhs = generate_int_operand(codegen, *rhs, operand_int_ty)?;
+
+                let predicate = match (op, is_signed) {
+                    (BinaryOp::Equals, _) => IntPredicate::EQ,
+                    (BinaryOp::NotEquals, _) => IntPredicate::NE,
+                    (BinaryOp::LessThan, true) => IntPredicate::SLT,
+                    (BinaryOp::LessThan, false) => IntPredicate::ULT,
+                    (BinaryOp::LessThanOrEquals, true) => IntPredicate::SLE,
+                    (BinaryOp::LessThanOrEquals, false) => IntPredicate::ULE,
+                    (BinaryOp::GreaterThan, true) => IntPredicate::SGT,
+                    (BinaryOp::GreaterThan, false) => IntPredicate::UGT,
+                    (BinaryOp::GreaterThanOrEquals, true) => IntPredicate::SGE,
+                    (BinaryOp::GreaterThanOrEquals, false) => IntPredicate::UGE,
+                    _ => unreachable!("arithmetic to be generated separately"),
+                };
+
+                let result = codegen
+                    .builder
+                    .build_int_compare(predicate, lhs, rhs, "cmp")?;
+                Ok(Some(result.into()))
+            }
+            ExprKind::BuiltinCall(builtin @ (Builtin::Print | Builtin::Println), args) => {
+                for arg in args {
+                    generate_print(codegen, arg)?;
+                }
+
+                if builtin == Builtin::Println {
+                    codegen.builder.build_call(
+                        RuntimeFunction::PrintNewline.get_or_declare(codegen),
+                        &[],
+                        "",
+                    )?;
+                }
+
+                Ok(None)
+            }
+            // Disabled assertions are compiled out together with their arguments
+            ExprKind::BuiltinCall(Builtin::Assert | Builtin::AssertEq, _)
+                if !codegen.debug_assertions =>
+            {
+                Ok(None)
+            }
+            ExprKind::BuiltinCall(Builtin::Assert, args) => {
+                let [condition]: [Expr; 1] = args
+                    .try_into()
+                    .expect("type checking to only allow one argument");
+                let message = format!(
+                    "assertion failed: {}",
+                    condition.span.source_text(&codegen.source_code)
+                );
+
+                let condition =
+                    generate_int_operand(codegen, condition, codegen.context.bool_type())?;
+                let failed = codegen.builder.build_not(condition, "failed")?;
+                codegen.build_panic_if(failed, &message, span)?;
+
+                Ok(None)
+            }
+            ExprKind::BuiltinCall(Builtin::AssertEq, args) => {
+                let [lhs, rhs]: [Expr; 2] = args
+                    .try_into()
+                    .expect("type checking to only allow two arguments");
+                let text = format!(
+                    "{} == {}",
+                    lhs.span.source_text(&codegen.source_code),
+                    rhs.span.source_text(&codegen.source_code)
+                );
+                let operand_ty = if lhs.ty() == ast::Type::Never {
+                    rhs.ty()
+                } else {
+                    lhs.ty()
+                };
+                let operand_int_ty = int_type(&operand_ty, codegen);
+
+                let lhs = generate_int_operand(codegen, lhs, operand_int_ty)?;
+                let rhs = generate_int_operand(codegen, rhs, operand_int_ty)?;
+                codegen.build_assert_eq(lhs, rhs, operand_ty, &text, span)?;
+
+                Ok(None)
+            }
+            ExprKind::BuiltinCall(Builtin::Panic, args) => {
+                let [message]: [Expr; 1] = args
+                    .try_into()
+                    .expect("type checking to only allow one argument");
+
+                // A diverging message never panics
+                if let Some(message) = message.codegen(codegen)? {
+                    codegen.build_panic(message.into_struct_value(), span)?;
+                }
+                codegen.generate_unreachable()?;
+
+                Ok(None)
+            }
+            ExprKind::BuiltinCall(builtin, args) => {
+                let int_ty = codegen.context.i32_type();
+                let [lhs, rhs]: [Expr; 2] = args
+                    .try_into()
+                    .expect("type checking to only allow two arguments");
+                let lhs = generate_int_operand(codegen, lhs, int_ty)?;
+                let rhs = generate_int_operand(codegen, rhs, int_ty)?;
+
+                let result = match builtin {
+                    Builtin::WrappingAdd => codegen.builder.build_int_add(lhs, rhs, "add")?,
+                    Builtin::CheckedAdd => codegen.build_checked_arithmetic(
+                        "llvm.sadd.with.overflow",
+                        lhs,
+                        rhs,
+                        "attempt to add with overflow",
+                        span,
+                    )?,
+                    Builtin::SaturatingAdd => codegen
+                        .build_int_intrinsic_call("llvm.sadd.sat", lhs, rhs)?
+                        .into_int_value(),
+                    Builtin::Print
+                    | Builtin::Println
+                    | Builtin::Panic
+                    | Builtin::Assert
+                    | Builtin::AssertEq => {
+                        unreachable!("builtins without a result to be generated separately")
+                    }
+                };
+
+                Ok(Some(result.into()))
+            }
+            ExprKind::Array(elements) => {
+                let mut values = Vec::new();
+                for element in elements {
+                    let Some(value) = element.codegen(codegen)? else {
+                        // The element diverges, so the array is never completed
+                        return Ok(None);
+                    };
+                    values.push(value);
+                }
+
+                let array_ty = ty
+                    .as_llvm_type(codegen)
+                    .expect("arrays to have a type with values")
+                    .into_array_type();
+                let mut array = array_ty.get_undef();
+                for (i, value) in values.into_iter().enumerate() {
+                    array = codegen
+                        .builder
+                        .build_insert_value(array, value, i as u32, "array")?
+                        .into_array_value();
+                }
+
+                Ok(Some(array.into()))
+            }
+            ExprKind::ArrayRepeat(element, len) => {
+                let Some(value) = element.codegen(codegen)? else {
+                    return Ok(None);
+                };
+                let array_ty = ty
+                    .as_llvm_type(codegen)
+                    .expect("arrays to have a type with values")
+                    .into_array_type();
+                if len == 0 {
+                    return Ok(Some(array_ty.get_undef().into()));
+                }
+
+                // The element is stored into every slot of the array by a loop, so that the code does not grow with the length
+                let array_ptr = codegen.generate_variable_alloca(array_ty.into(), "repeat")?;
+                let i32_ty = codegen.context.i32_type();
+                let function = codegen.current_function();
+                let entry_block = codegen
+                    .builder
+                    .get_insert_block()
+                    .expect("builder to be positioned inside of a function");
+                let loop_block = codegen.context.append_basic_block(function, "repeat_loop");
+                let end_block = codegen.context.append_basic_block(function, "repeat_end");
+
+                codegen.builder.build_unconditional_branch(loop_block)?;
+                codegen.builder.position_at_end(loop_block);
+                let index = codegen.builder.build_phi(i32_ty, "index")?;
+                let index_value = index.as_basic_value().into_int_value();
+
+                // SAFETY: The index is always smaller than the length of the array
+                let element_ptr = unsafe {
+                    codegen.builder.build_in_bounds_gep(
+                        array_ty,
+                        array_ptr,
+                        &[i32_ty.const_zero(), index_value],
+                        "element",
+                    )?
+                };
+                codegen.builder.build_store(element_ptr, value)?;
+
+                let next_index = codegen.builder.build_int_add(
+                    index_value,
+                    i32_ty.const_int(1, false),
+                    "next_index",
+                )?;
+                let is_done = codegen.builder.build_int_compare(
+                    IntPredicate::EQ,
+                    next_index,
+                    i32_ty.const_int(len.into(), false),
+                    "is_done",
+                )?;
+                codegen
+                    .builder
+                    .build_conditional_branch(is_done, end_block, loop_block)?;
+                index.add_incoming(&[
+                    (&i32_ty.const_zero(), entry_block),
+                    (&next_index, loop_block),
+                ]);
+
+                codegen.builder.position_at_end(end_block);
+                let array = codegen.builder.build_load(array_ty, array_ptr, "array")?;
+                Ok(Some(array))
+            }
+            ExprKind::Struct(literal) => {
+                let struct_ty = struct_type(codegen, &literal.name);
+
+                // Field values are generated in source order, but inserted at the position of their declaration
+                let mut value = struct_ty.get_undef();
+                for field_init in literal.fields {
+                    let index = field_init.field.index();
+                    let Some(field_value) = field_init.value.codegen(codegen)? else {
+                        // The field value diverges, so the struct is never completed
+                        return Ok(None);
+                    };
+                    value = codegen
+                        .builder
+                        .build_insert_value(value, field_value, index, "struct")?
+                        .into_struct_value();
+                }
+
+                Ok(Some(value.into()))
+            }
+            ExprKind::Tuple(elements) => {
+                let mut values = Vec::new();
+                for element in elements {
+                    let Some(value) = element.codegen(codegen)? else {
+                        // The element diverges, so the tuple is never completed
+                        return Ok(None);
+                    };
+                    values.push(value);
+                }
+
+                let tuple_ty = ty
+                    .as_llvm_type(codegen)
+                    .expect("tuples to have a type with values")
+                    .into_struct_type();
+                let mut tuple = tuple_ty.get_undef();
+                for (i, value) in values.into_iter().enumerate() {
+                    tuple = codegen
+                        .builder
+                        .build_insert_value(tuple, value, i as u32, "tuple")?
+                        .into_struct_value();
+                }
+
+                Ok(Some(tuple.into()))
+            }
+            ExprKind::Variant(literal) => {
+                let variant = literal.variant;
+                let mut values = Vec::new();
+                for arg in literal.args {
+                    let Some(value) = arg.codegen(codegen)? else {
+                        // The field value diverges, so the variant is never completed
+                        return Ok(None);
+                    };
+                    values.push(value);
+                }
+
+                // The fields are stored through a pointer to the payload, which has the type of the variant
+                let enum_ty = enum_type(codegen, &variant.enum_name);
+                let ptr = codegen.generate_variable_alloca(enum_ty.into(), "variant")?;
+                let tag_ptr = codegen.builder.build_struct_gep(enum_ty, ptr, 0, "tag")?;
+                codegen.builder.build_store(
+                    tag_ptr,
+                    codegen
+                        .context
+                        .i32_type()
+                        .const_int(variant.index().into(), false),
+                )?;
+
+                if !values.is_empty() {
+                    let variant_ty = variant_type(codegen, &variant.enum_name, variant.index());
+                    let mut fields = variant_ty.get_undef();
+                    for (i, value) in values.into_iter().enumerate() {
+                        fields = codegen
+                            .builder
+                            .build_insert_value(fields, value, i as u32, "fields")?
+                            .into_struct_value();
+                    }
+
+                    let fields_ptr = codegen.build_variant_fields_ptr(ptr, &variant)?;
+                    codegen.builder.build_store(fields_ptr, fields)?;
+                }
+
+                let value = codegen.builder.build_load(enum_ty, ptr, "variant")?;
+                Ok(Some(value))
+            }
+            ExprKind::Match(match_expr) => match_expr.codegen(codegen, &ty),
+            ExprKind::Field(value, field_ref) => {
+                let Some(value) = value.codegen(codegen)? else {
+                    return Ok(None);
+                };
+
+                let field = codegen.builder.build_extract_value(
+                    value.into_struct_value(),
+                    field_ref.index(),
+                    &field_ref.name,
+                )?;
+                Ok(Some(field))
+            }
+            ExprKind::Index(array, index) => {
+                let array_ty = array.ty();
+                let Some(array) = array.codegen(codegen)? else {
+                    return Ok(None);
+                };
+                let Some(index) = index.codegen(codegen)? else {
+                    return Ok(None);
+                };
+                let index = index.into_int_value();
+
+                let (elements_ptr, len) = codegen.build_elements_ptr(array, &array_ty)?;
+                codegen.build_bounds_check(index, len, span)?;
+
+                let element_ty = ty
+                    .as_llvm_type(codegen)
+                    .expect("array elements to have a type with values");
+                // SAFETY: The bounds check above guarantees that the index is smaller than the length
+                let element_ptr = unsafe {
+                    codegen.builder.build_in_bounds_gep(
+                        element_ty,
+                        elements_ptr,
+                        &[index],
+                        "element",
+                    )?
+                };
+                let element = codegen
+                    .builder
+                    .build_load(element_ty, element_ptr, "element")?;
+
+                Ok(Some(element))
+            }
+            ExprKind::Slice(array, start, end) => {
+                let array_ty = array.ty();
+                let Some(array) = array.codegen(codegen)? else {
+                    return Ok(None);
+                };
+                let (elements_ptr, len) = codegen.build_elements_ptr(array, &array_ty)?;
+
+                let zero = codegen.context.i32_type().const_zero();
+                let Some(start) = generate_range_bound(codegen, start, zero)? else {
+                    return Ok(None);
+                };
+                let Some(end) = generate_range_bound(codegen, end, len)? else {
+                    return Ok(None);
+                };
+                codegen.build_range_check(start, end, len, span)?;
+
+                let element_ty = array_ty
+                    .element_ty()
+                    .and_then(|element_ty| element_ty.as_llvm_type(codegen))
+                    .expect("array elements to have a type with values");
+                // SAFETY: The range check above guarantees that the start is not larger than the length
+                let start_ptr = unsafe {
+                    codegen.builder.build_in_bounds_gep(
+                        element_ty,
+                        elements_ptr,
+                        &[start],
+                        "slice_start",
+                    )?
+                };
+                let slice_len = codegen.builder.build_int_sub(end, start, "slice_len")?;
+
+                let slice_ty = ty
+                    .as_llvm_type(codegen)
+                    .expect("slices to have a type with values")
+                    .into_struct_type();
+                let slice = codegen.builder.build_insert_value(
+                    slice_ty.get_undef(),
+                    start_ptr,
+                    0,
+                    "slice",
+                )?;
+                let slice = codegen
+                    .builder
+                    .build_insert_value(slice, slice_len, 1, "slice")?;
+
+                Ok(Some(slice.into_struct_value().into()))
+            }
+            ExprKind::Len(array) => {
+                let array_ty = array.ty();
+                let Some(array) = array.codegen(codegen)? else {
+                    return Ok(None);
+                };
+
+                Ok(Some(codegen.build_len(array, &array_ty)?.into()))
+            }
+            ExprKind::For(for_loop) => {
+                let iterable_ty = for_loop.iterable.ty();
+                let Some(iterable) = for_loop.iterable.codegen(codegen)? else {
+                    return Ok(None);
+                };
+                let (elements_ptr, len) = codegen.build_elements_ptr(iterable, &iterable_ty)?;
+
+                let element_ty = iterable_ty
+                    .element_ty()
+                    .and_then(|element_ty| element_ty.as_llvm_type(codegen))
+                    .expect("array elements to have a type with values");
+                let i32_ty = codegen.context.i32_type();
+                let index_ptr = codegen.generate_variable_alloca(i32_ty.into(), "for_index")?;
+                codegen
+                    .builder
+                    .build_store(index_ptr, i32_ty.const_zero())?;
+
+                let name = for_loop.variable.get().name.clone();
+                let variable_ptr = codegen.generate_variable_alloca(element_ty, &name)?;
+                codegen
+                    .variables
+                    .borrow_mut()
+                    .insert(for_loop.variable, variable_ptr);
+
+                let function = codegen.current_function();
+                let condition_block = codegen
+                    .context
+                    .append_basic_block(function, "for_condition");
+                let body_block = codegen.context.append_basic_block(function, "for_body");
+                let end_block = codegen.context.append_basic_block(function, "end_for");
+
+                codegen
+                    .builder
+                    .build_unconditional_branch(condition_block)?;
+                codegen.builder.position_at_end(condition_block);
+                let index = codegen
+                    .builder
+                    .build_load(i32_ty, index_ptr, "index")?
+                    .into_int_value();
+                let has_next =
+                    codegen
+                        .builder
+                        .build_int_compare(IntPredicate::ULT, index, len, "has_next")?;
+                codegen
+                    .builder
+                    .build_conditional_branch(has_next, body_block, end_block)?;
+
+                // The index is incremented right away, because the body may end in a different block
+                codegen.builder.position_at_end(body_block);
+                let next_index = codegen.builder.build_int_add(
+                    index,
+                    i32_ty.const_int(1, false),
+                    "next_index",
+                )?;
+                codegen.builder.build_store(index_ptr, next_index)?;
+
+                // SAFETY: The condition guarantees that the index is smaller than the length
+                let element_ptr = unsafe {
+                    codegen.builder.build_in_bounds_gep(
+                        element_ty,
+                        elements_ptr,
+                        &[index],
+                        "element",
+                    )?
+                };
+                let element = codegen
+                    .builder
+                    .build_load(element_ty, element_ptr, "element")?;
+                codegen.builder.build_store(variable_ptr, element)?;
+
+                let _ = for_loop.body.codegen(codegen)?;
+                codegen.build_branch_if_open(condition_block)?;
+
+                codegen.builder.position_at_end(end_block);
+                Ok(None)
+            }
+            ExprKind::Cast(value, target_ty) => {
+                let value_ty = value.ty();
+                let value = generate_int_operand(codegen, *value, int_type(&value_ty, codegen))?;
+
+                let result = match (value_ty, target_ty) {
+                    (ast::Type::Bool, _) => codegen.builder.build_int_z_extend(
+                        value,
+                        codegen.context.i32_type(),
+                        "bool_to_int",
+                    )?,
+                    // `int` and `char` share the same representation, but only some `int` values are valid `char`s
+                    (ast::Type::Int, ast::Type::Char) => {
+                        codegen.build_char_check(value, span)?;
+                        value
+                    }
+                    _ => value,
+                };
+
+                Ok(Some(result.into()))
+            }
+            ExprKind::If(condition, then_branch, else_branch) => {
+                let function = codegen.current_function();
+                let condition =
+                    generate_int_operand(codegen, *condition, codegen.context.bool_type())?;
+
+                let then_block = codegen.context.append_basic_block(function, "then");
+                let else_block = else_branch
+                    .as_ref()
+                    .map(|_| codegen.context.append_basic_block(function, "else"));
+                let end_block = codegen.context.append_basic_block(function, "end_if");
+
+                codegen.builder.build_conditional_branch(
+                    condition,
+                    then_block,
+                    else_block.unwrap_or(end_block),
+                )?;
+
+                codegen.builder.position_at_end(then_block);
+                let _ = then_branch.codegen(codegen)?;
+                codegen.build_branch_if_open(end_block)?;
+
+                if let (Some(else_branch), Some(else_block)) = (else_branch, else_block) {
+                    codegen.builder.position_at_end(else_block);
+                    let _ = else_branch.codegen(codegen)?;
+                    codegen.build_branch_if_open(end_block)?;
+                }
+
+                codegen.builder.position_at_end(end_block);
+                Ok(None)
+            }
+            ExprKind::While(condition, body) => {
+                let function = codegen.current_function();
+                let condition_block = codegen
+                    .context
+                    .append_basic_block(function, "while_condition");
+                let body_block = codegen.context.append_basic_block(function, "while_body");
+                let end_block = codegen.context.append_basic_block(function, "end_while");
+
+                codegen
+                    .builder
+                    .build_unconditional_branch(condition_block)?;
+                codegen.builder.position_at_end(condition_block);
+                let condition =
+                    generate_int_operand(codegen, *condition, codegen.context.bool_type())?;
+                codegen
+                    .builder
+                    .build_conditional_branch(condition, body_block, end_block)?;
+
+                codegen.builder.position_at_end(body_block);
+                let _ = body.codegen(codegen)?;
+                codegen.build_branch_if_open(condition_block)?;
+
+                codegen.builder.position_at_end(end_block);
+                Ok(None)
+            }
+            ExprKind::Loop(body) => {
+                let body_block = codegen
+                    .context
+                    .append_basic_block(codegen.current_function(), "loop_body");
+
+                codegen.builder.build_unconditional_branch(body_block)?;
+                codegen.builder.position_at_end(body_block);
+                let _ = body.codegen(codegen)?;
+                codegen.build_branch_if_open(body_block)?;
+
+                // A loop is never left normally, so the code after it is unreachable
+                let after_block = codegen
+                    .context
+                    .append_basic_block(codegen.current_function(), "unreachable");
+                codegen.builder.position_at_end(after_block);
+                Ok(None)
+            }
+            ExprKind::Let(let_expr) => {
+                let value_ty = let_expr.value.ty();
+                let Some(value) = let_expr.value.codegen(codegen)? else {
+                    return Ok(None);
+                };
+
+                match &let_expr.pattern.kind {
+                    ast::PatternKind::Binding(variable) => {
+                        let name = variable.get().name.clone();
+                        let ptr = codegen.generate_variable_alloca(value.get_type(), &name)?;
+                        codegen.builder.build_store(ptr, value)?;
+                        codegen.variables.borrow_mut().insert(variable.clone(), ptr);
+                    }
+                    ast::PatternKind::Wildcard => {}
+                    // The parts of the value are bound by pointers into a copy of it in memory
+                    _ => {
+                        let ptr =
+                            codegen.generate_variable_alloca(value.get_type(), "let_value")?;
+                        codegen.builder.build_store(ptr, value)?;
+                        codegen.build_pattern_bindings(&let_expr.pattern, ptr, &value_ty)?;
+                    }
+                }
+
+                Ok(None)
+            }
+            ExprKind::Static(static_ref) => {
+                let global = codegen
+                    .statics
+                    .get(&static_ref)
+                    .expect("statics to be generated before any function bodies");
+                let static_sym = static_ref.get();
+                let ty = static_sym
+                    .ty
+                    .as_llvm_type(codegen)
+                    .expect("statics to have a type with values");
+
+                let value =
+                    codegen
+                        .builder
+                        .build_load(ty, global.as_pointer_value(), &static_sym.name)?;
+                Ok(Some(value))
+            }
+            ExprKind::Assign(assignment) => {
+                let global = *codegen
+                    .statics
+                    .get(assignment.target())
+                    .expect("statics to be generated before any function bodies");
+                let mut target_ty = assignment.target().get().ty.clone();
+
+                // A diverging value is never assigned
+                let Some(value) = assignment.value.codegen(codegen)? else {
+                    return Ok(None);
+                };
+
+                // The assigned field is reached by following the fields from the static through every level of nesting
+                let mut ptr = global.as_pointer_value();
+                for field_ref in &assignment.fields {
+                    let index = field_ref.index();
+                    let (llvm_ty, field_ty) = match &target_ty {
+                        ast::Type::Struct(name) => {
+                            let struct_ref = codegen.sym.get_struct(name);
+                            let field_ty = struct_ref.get().fields[index as usize].ty.clone();
+                            (struct_type(codegen, name), field_ty)
+                        }
+                        ast::Type::Tuple(element_tys) => (
+                            tuple_type(codegen, element_tys),
+                            element_tys[index as usize].clone(),
+                        ),
+                        _ => unreachable!(
+                            "type checking to only allow fields of structs and tuples, got `{target_ty}`"
+                        ),
+                    };
+
+                    ptr = codegen
+                        .builder
+                        .build_struct_gep(llvm_ty, ptr, index, &field_ref.name)?;
+                    target_ty = field_ty;
+                }
+                codegen.builder.build_store(ptr, value)?;
+
+                Ok(None)
+            }
+            ExprKind::Variable(variable_ref) => {
+                let variable = variable_ref.variable();
+
+                // Values of type `()` are not stored
+                let Some(ty) = variable.get().ty().as_llvm_type(codegen) else {
+                    return Ok(None);
+                };
+                let ptr = *codegen
+                    .variables
+                    .borrow()
+                    .get(variable)
+                    .expect("variable to be generated before it is used");
+
+                let value = codegen.builder.build_load(ty, ptr, &variable.get().name)?;
+                Ok(Some(value))
+            }
+            ExprKind::Return(_) => {
                 unimplemented!("return expression is not handled in this function")
             }
-            Expr::Compound(_) => {
+            ExprKind::Compound(_) => {
                 unimplemented!("compound is not be handled in this function")
             }
         }
     }
 }
 
+/// Generates an operand of a comparison, arithmetic operation or cast, which is always a value of type `int`, `char`,
+/// `bool` or `!`.
+/// Because values of type `!` do not exist, they are replaced by an undefined value of type `ty`.
+fn generate_int_operand<'ctx>(
+    codegen: &CodegenContext<'ctx>,
+    expr: Expr,
+    ty: IntType<'ctx>,
+) -> Result<IntValue<'ctx>> {
+    Ok(expr
+        .codegen(codegen)?
+        .map_or(ty.get_undef(), |value| value.into_int_value()))
+}
+
+/// Generates a bound of a range, which is `default` if it is omitted. Returns `None` if the bound diverges.
+fn generate_range_bound<'ctx>(
+    codegen: &CodegenContext<'ctx>,
+    bound: Option<Box<Expr>>,
+    default: IntValue<'ctx>,
+) -> Result<Option<IntValue<'ctx>>> {
+    match bound {
+        Some(bound) => Ok(bound.codegen(codegen)?.map(BasicValueEnum::into_int_value)),
+        None => Ok(Some(default)),
+    }
+}
+
+/// LLVM type of an operand of a comparison or cast
+fn int_type<'ctx>(ty: &ast::Type, codegen: &CodegenContext<'ctx>) -> IntType<'ctx> {
+    match ty {
+        ast::Type::Bool => codegen.context.bool_type(),
+        // Values of type `!` do not exist, so any type can be used
+        _ => codegen.context.i32_type(),
+    }
+}
+
 impl Function {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use anyhow::Result;
+    use inkwell::context::Context;
+    use inkwell::targets::TargetTriple;
+
+    use crate::compiler::codegen::generate_module;
+    use crate::compiler::lexer::tokenize_with_spans;
+    use crate::compiler::options::Options;
+    use crate::compiler::parser::parse;
+    use crate::compiler::semantic_analysis::analyse;
+    use crate::compiler::symbol_table::Sym;
+
+    /// Compiles `src` for x86-64 Linux and returns the generated LLVM IR as text
+    fn generate_ir(src: &str) -> Result<String> {
+        generate_ir_for(src, "x86_64-unknown-linux-gnu")
+    }
+
+    /// Compiles `src` for the target `triple` and returns the generated LLVM IR as text
+    fn generate_ir_for(src: &str, triple: &str) -> Result<String> {
+        let options = Options::default();
+        let sym = Sym::new();
+        let ast = parse(tokenize_with_spans(src)?, sym.clone())?;
+        let analyzed = analyse(ast, sym.clone(), &options)?;
+
+        let context = Context::create();
+        let codegen = generate_module(
+            &context,
+            analyzed,
+            sym,
+            Path::new("test.yyn"),
+            src,
+            &options,
+            &TargetTriple::create(triple),
+        )?;
+        Ok(codegen.module.print_to_string().to_string())
+    }
+
+    #[test]
+    pub fn checks_int_to_char_casts() -> Result<()> {
+        let message = "not a unicode scalar value";
+
+        let ir = generate_ir(
+            "static mut CODE: int = 55296
+fun main() { println(CODE as char) }",
+        )?;
+        assert!(ir.contains(message));
+        assert!(ir.contains("yyn_panic"));
+
+        // Casts of constants are checked at compile time instead
+        let ir = generate_ir("fun main() { println(97 as char) }")?;
+        assert!(!ir.contains(message));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn declares_main_like_c() -> Result<()> {
+        let ir = generate_ir("fun main() {}")?;
+        assert!(ir.contains("define i32 @main()"));
+
+        // C calls every `main` as `int main()`, even one that never returns
+        let ir = generate_ir("fun main() -> ! {\n exit 1\n}")?;
+        assert!(ir.contains("define i32 @main()"));
+        assert!(!ir.contains("ret "));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn passes_arguments() -> Result<()> {
+        let ir = generate_ir(
+            "fun count(values: [int], extra: int) -> int {
+ return values.len() + extra
+}
+fun main() {
+ let values = [1, 2, 3]
+ println(count(values[1..], 1))
+}",
+        )?;
+        assert!(ir.contains("define i32 @count({ i32*, i32 } %0, i32 %1)"));
+        assert!(ir.contains("call i32 @count({ i32*, i32 }"));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn passes_small_structs_in_registers() -> Result<()> {
+        let ir = generate_ir(
+            "struct Point { x: int, y: int }
+struct Triple { a: int, b: int, c: int }
+fun swap(point: Point) -> Point {
+ return Point { x: point.y, y: point.x }
+}
+fun rotate(triple: Triple) -> Triple {
+ return Triple { a: triple.b, b: triple.c, c: triple.a }
+}
+fun main() {
+ println(swap(Point { x: 1, y: 2 }).x)
+ println(rotate(Triple { a: 1, b: 2, c: 3 }).a)
+}",
+        )?;
+        assert!(ir.contains("define i64 @swap(i64 %0)"));
+        assert!(ir.contains("call i64 @swap(i64"));
+        assert!(ir.contains("define { i64, i32 } @rotate({ i64, i32 } %0)"));
+        assert!(ir.contains("call { i64, i32 } @rotate({ i64, i32 }"));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn passes_large_structs_in_memory() -> Result<()> {
+        let ir = generate_ir(
+            "struct Big { a: int, b: int, c: int, d: int, e: int }
+fun double(big: Big) -> Big {
+ return Big { a: big.a * 2, b: big.b * 2, c: big.c * 2, d: big.d * 2, e: big.e * 2 }
+}
+fun main() {
+ println(double(Big { a: 1, b: 2, c: 3, d: 4, e: 5 }).e)
+}",
+        )?;
+        assert!(ir.contains("define void @double(%Big* sret(%Big) %0, %Big* byval(%Big) %1)"));
+        assert!(ir.contains("call void @double(%Big* sret(%Big)"));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn passes_structs_with_the_target_abi() -> Result<()> {
+        let src = "struct Point { x: int, y: int }
+struct Triple { a: int, b: int, c: int }
+fun swap(point: Point) -> Point {
+ return Point { x: point.y, y: point.x }
+}
+fun rotate(triple: Triple) -> Triple {
+ return Triple { a: triple.b, b: triple.c, c: triple.a }
+}
+fun main() {
+ println(swap(Point { x: 1, y: 2 }).x)
+ println(rotate(Triple { a: 1, b: 2, c: 3 }).a)
+}";
+
+        // Win64 passes only structs of 1, 2, 4 or 8 bytes in registers, the caller copies all others
+        let ir = generate_ir_for(src, "x86_64-pc-windows-msvc")?;
+        assert!(ir.contains("target triple = \"x86_64-pc-windows-msvc\""));
+        assert!(ir.contains("define i64 @swap(i64 %0)"));
+        assert!(ir.contains("define void @rotate(%Triple* sret(%Triple) %0, %Triple* %1)"));
+        assert!(ir.contains("call void @rotate(%Triple* sret(%Triple)"));
+        assert!(!ir.contains("byval"));
+
+        let error = generate_ir_for(src, "aarch64-unknown-linux-gnu")
+            .expect_err("only x86-64 targets to be supported")
+            .to_string();
+        assert_eq!(
+            error,
+            "Unsupported target `aarch64-unknown-linux-gnu`, only x86-64 targets are supported"
+        );
+
+        Ok(())
+    }
+}
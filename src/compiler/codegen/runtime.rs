@@ -0,0 +1,92 @@
+use inkwell::module::Linkage;
+use inkwell::types::{BasicMetadataTypeEnum, FunctionType};
+use inkwell::values::FunctionValue;
+use inkwell::AddressSpace;
+
+use crate::compiler::codegen::CodegenContext;
+
+/// Functions of the yyn runtime (`runtime/yyn_runtime.c`), which is linked together with every compiled program
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RuntimeFunction {
+    /// `void yyn_print_str(const char *ptr, int64_t len)`
+    PrintStr,
+    /// `void yyn_print_int(int32_t value)`
+    PrintInt,
+    /// `void yyn_print_bool(int32_t value)`
+    PrintBool,
+    /// `void yyn_print_char(uint32_t value)`
+    PrintChar,
+    /// `void yyn_print_newline(void)`
+    PrintNewline,
+    /// `void yyn_panic(const char *file_ptr, int64_t file_len, int32_t line, int32_t column, const char *msg_ptr,
+    /// int64_t msg_len)`, which never returns
+    Panic,
+    /// `void yyn_assert_eq_failed(const char *file_ptr, int64_t file_len, int32_t line, int32_t column,
+    /// const char *text_ptr, int64_t text_len, int32_t type, int32_t left, int32_t right)`, which never returns
+    AssertEqFailed,
+    /// `void yyn_index_out_of_bounds(const char *file_ptr, int64_t file_len, int32_t line, int32_t column, int32_t len,
+    /// int32_t index)`, which never returns
+    IndexOutOfBounds,
+    /// `void yyn_range_out_of_bounds(const char *file_ptr, int64_t file_len, int32_t line, int32_t column, int32_t len,
+    /// int32_t start, int32_t end)`, which never returns
+    RangeOutOfBounds,
+}
+
+impl RuntimeFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuntimeFunction::PrintStr => "yyn_print_str",
+            RuntimeFunction::PrintInt => "yyn_print_int",
+            RuntimeFunction::PrintBool => "yyn_print_bool",
+            RuntimeFunction::PrintChar => "yyn_print_char",
+            RuntimeFunction::PrintNewline => "yyn_print_newline",
+            RuntimeFunction::Panic => "yyn_panic",
+            RuntimeFunction::AssertEqFailed => "yyn_assert_eq_failed",
+            RuntimeFunction::IndexOutOfBounds => "yyn_index_out_of_bounds",
+            RuntimeFunction::RangeOutOfBounds => "yyn_range_out_of_bounds",
+        }
+    }
+
+    fn fn_type<'ctx>(&self, codegen: &CodegenContext<'ctx>) -> FunctionType<'ctx> {
+        let void_ty = codegen.context.void_type();
+        let i32_ty = BasicMetadataTypeEnum::from(codegen.context.i32_type());
+
+        let ptr_ty = BasicMetadataTypeEnum::from(
+            codegen.context.i8_type().ptr_type(AddressSpace::default()),
+        );
+        let len_ty = BasicMetadataTypeEnum::from(codegen.context.i64_type());
+
+        match self {
+            RuntimeFunction::PrintStr => void_ty.fn_type(&[ptr_ty, len_ty], false),
+            RuntimeFunction::PrintInt | RuntimeFunction::PrintBool | RuntimeFunction::PrintChar => {
+                void_ty.fn_type(&[i32_ty], false)
+            }
+            RuntimeFunction::PrintNewline => void_ty.fn_type(&[], false),
+            RuntimeFunction::Panic => {
+                void_ty.fn_type(&[ptr_ty, len_ty, i32_ty, i32_ty, ptr_ty, len_ty], false)
+            }
+            RuntimeFunction::AssertEqFailed => void_ty.fn_type(
+                &[
+                    ptr_ty, len_ty, i32_ty, i32_ty, ptr_ty, len_ty, i32_ty, i32_ty, i32_ty,
+                ],
+                false,
+            ),
+            RuntimeFunction::IndexOutOfBounds => {
+                void_ty.fn_type(&[ptr_ty, len_ty, i32_ty, i32_ty, i32_ty, i32_ty], false)
+            }
+            RuntimeFunction::RangeOutOfBounds => void_ty.fn_type(
+                &[ptr_ty, len_ty, i32_ty, i32_ty, i32_ty, i32_ty, i32_ty],
+                false,
+            ),
+        }
+    }
+
+    /// Returns the declaration of this function in the current module, adding it first if necessary
+    pub fn get_or_declare<'ctx>(&self, codegen: &CodegenContext<'ctx>) -> FunctionValue<'ctx> {
+        codegen.module.get_function(self.name()).unwrap_or_else(|| {
+            codegen
+                .module
+                .add_function(self.name(), self.fn_type(codegen), Some(Linkage::External))
+        })
+    }
+}
@@ -1,4 +1,7 @@
-use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType, VoidType};
+use inkwell::types::{
+    BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType, StructType, VoidType,
+};
+use inkwell::AddressSpace;
 
 use crate::compiler::codegen::CodegenContext;
 use crate::compiler::parser::ast;
@@ -20,23 +23,6 @@ impl<'ctx> Type<'ctx> {
             Type::BasicType(ty) => ty.fn_type(param_types, is_var_args),
         }
     }
-
-    pub fn to_basic_type_enum(&self) -> Option<BasicTypeEnum<'ctx>> {
-        match self {
-            Type::BasicType(ty) => Some(*ty),
-            Type::Void(_) => None,
-        }
-    }
-
-    pub fn from_ast_type<'a, 'b>(
-        ast_ty: &'a ast::Type,
-        codegen: &'b CodegenContext<'ctx>,
-    ) -> Type<'ctx> {
-        match ast_ty {
-            ast::Type::Unit => Self::Void(codegen.context.void_type()),
-            ast::Type::Int => Self::BasicType(codegen.context.i32_type().as_basic_type_enum()),
-        }
-    }
 }
 
 impl ast::Type {
@@ -46,21 +32,191 @@ impl ast::Type {
     ) -> Option<BasicTypeEnum<'ctx>> {
         match self {
             ast::Type::Int => Some(codegen.context.i32_type().into()),
-            ast::Type::Unit => None,
+            ast::Type::Str => Some(str_type(codegen).into()),
+            ast::Type::Char => Some(codegen.context.i32_type().into()),
+            ast::Type::Bool => Some(codegen.context.bool_type().into()),
+            ast::Type::Array(element_ty, len) => element_ty
+                .as_llvm_type(codegen)
+                .map(|element_ty| element_ty.array_type(*len).into()),
+            ast::Type::Slice(element_ty) => element_ty
+                .as_llvm_type(codegen)
+                .map(|element_ty| slice_type(codegen, element_ty).into()),
+            ast::Type::Tuple(element_tys) => Some(tuple_type(codegen, element_tys).into()),
+            ast::Type::Struct(name) => Some(struct_type(codegen, name).into()),
+            ast::Type::Enum(name) => Some(enum_type(codegen, name).into()),
+            ast::Type::Unit | ast::Type::Never => None,
         }
     }
 }
 
-pub enum CompoundReturnType<'ctx> {
-    Explicit(Type<'ctx>),
-    ImplicitUnit,
+/// A `str` is represented as a pair of a pointer to its first byte and its length in bytes: `{ i8*, i64 }`
+pub fn str_type<'ctx>(codegen: &CodegenContext<'ctx>) -> StructType<'ctx> {
+    let ptr_ty = codegen.context.i8_type().ptr_type(AddressSpace::default());
+    let len_ty = codegen.context.i64_type();
+
+    codegen
+        .context
+        .struct_type(&[ptr_ty.into(), len_ty.into()], false)
 }
 
-impl<'ctx> CompoundReturnType<'ctx> {
-    pub fn into_type(self, codegen: &CodegenContext<'ctx>) -> Type<'ctx> {
-        match self {
-            CompoundReturnType::Explicit(ty) => ty,
-            CompoundReturnType::ImplicitUnit => Type::Void(codegen.context.void_type()),
+/// A slice is represented as a pair of a pointer to its first element and its number of elements: `{ T*, i32 }`
+pub fn slice_type<'ctx>(
+    codegen: &CodegenContext<'ctx>,
+    element_ty: BasicTypeEnum<'ctx>,
+) -> StructType<'ctx> {
+    let ptr_ty = element_ty.ptr_type(AddressSpace::default());
+    let len_ty = codegen.context.i32_type();
+
+    codegen
+        .context
+        .struct_type(&[ptr_ty.into(), len_ty.into()], false)
+}
+
+/// A tuple is represented as an LLVM literal struct type with a field for every element: `{ T, U }`
+pub fn tuple_type<'ctx>(
+    codegen: &CodegenContext<'ctx>,
+    element_tys: &[ast::Type],
+) -> StructType<'ctx> {
+    let element_types: Vec<_> = element_tys
+        .iter()
+        .map(|ty| {
+            ty.as_llvm_type(codegen)
+                .expect("tuples to only contain elements of types with values")
+        })
+        .collect();
+
+    codegen.context.struct_type(&element_types, false)
+}
+
+/// A struct is represented as an LLVM named struct type with the same name, whose fields are in the order of their
+/// declaration. The type is created when it is used for the first time.
+pub fn struct_type<'ctx>(codegen: &CodegenContext<'ctx>, name: &str) -> StructType<'ctx> {
+    if let Some(struct_ty) = codegen.context.get_struct_type(name) {
+        return struct_ty;
+    }
+
+    // LLVM creates named struct types without fields, they are set once the types of the fields are known
+    let struct_ty = codegen.context.opaque_struct_type(name);
+    let field_types: Vec<_> = codegen
+        .sym
+        .get_struct(name)
+        .get()
+        .fields
+        .iter()
+        .map(|field| {
+            field
+                .ty
+                .as_llvm_type(codegen)
+                .expect("type checking to only allow fields of types with values")
+        })
+        .collect();
+    struct_ty.set_body(&field_types, false);
+
+    struct_ty
+}
+
+/// An enum is represented as an LLVM named struct type with the same name, which contains the position of the variant
+/// as a tag followed by the fields of the variant: `{ i32, [N x i64] }`. The fields are accessed through the named
+/// struct type of their variant, see `variant_type`. The `i64` array makes the payload large enough and aligned for the
+/// fields of every variant.
+pub fn enum_type<'ctx>(codegen: &CodegenContext<'ctx>, name: &str) -> StructType<'ctx> {
+    if let Some(enum_ty) = codegen.context.get_struct_type(name) {
+        return enum_ty;
+    }
+
+    let payload_words = payload_size(codegen, name).div_ceil(8) as u32;
+    let enum_ty = codegen.context.opaque_struct_type(name);
+    enum_ty.set_body(
+        &[
+            codegen.context.i32_type().into(),
+            codegen.context.i64_type().array_type(payload_words).into(),
+        ],
+        false,
+    );
+
+    enum_ty
+}
+
+/// The fields of a variant are represented as an LLVM named struct type called `Enum.Variant`, which is stored in the
+/// payload of the enum
+pub fn variant_type<'ctx>(
+    codegen: &CodegenContext<'ctx>,
+    enum_name: &str,
+    index: u32,
+) -> StructType<'ctx> {
+    let enum_ref = codegen.sym.get_enum(enum_name);
+    let enum_sym = enum_ref.get();
+    let variant = &enum_sym.variants[index as usize];
+
+    let name = format!("{enum_name}.{}", variant.name);
+    if let Some(variant_ty) = codegen.context.get_struct_type(&name) {
+        return variant_ty;
+    }
+
+    let variant_ty = codegen.context.opaque_struct_type(&name);
+    let field_types: Vec<_> = variant
+        .fields
+        .iter()
+        .map(|ty| {
+            ty.as_llvm_type(codegen)
+                .expect("type checking to only allow fields of types with values")
+        })
+        .collect();
+    variant_ty.set_body(&field_types, false);
+
+    variant_ty
+}
+
+/// Size in bytes of the largest variant of the enum called `name`
+fn payload_size(codegen: &CodegenContext, name: &str) -> u64 {
+    let enum_ref = codegen.sym.get_enum(name);
+    let enum_sym = enum_ref.get();
+
+    enum_sym
+        .variants
+        .iter()
+        .map(|variant| fields_layout(codegen, &variant.fields).0)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Size and alignment in bytes of values of type `ty`. Only x86-64 targets are supported, so pointers have 64 bits.
+pub fn layout(codegen: &CodegenContext, ty: &ast::Type) -> (u64, u64) {
+    match ty {
+        ast::Type::Int | ast::Type::Char => (4, 4),
+        ast::Type::Bool => (1, 1),
+        // A pointer followed by a length
+        ast::Type::Str | ast::Type::Slice(_) => (16, 8),
+        ast::Type::Array(element_ty, len) => {
+            let (size, align) = layout(codegen, element_ty);
+            (size * u64::from(*len), align)
+        }
+        ast::Type::Struct(name) => {
+            let struct_ref = codegen.sym.get_struct(name);
+            let field_tys: Vec<_> = struct_ref
+                .get()
+                .fields
+                .iter()
+                .map(|field| field.ty.clone())
+                .collect();
+            fields_layout(codegen, &field_tys)
         }
+        ast::Type::Tuple(element_tys) => fields_layout(codegen, element_tys),
+        ast::Type::Enum(name) => (8 + payload_size(codegen, name).next_multiple_of(8), 8),
+        ast::Type::Unit | ast::Type::Never => (0, 1),
     }
 }
+
+/// Size and alignment in bytes of a struct with fields of types `field_tys`, including padding
+fn fields_layout(codegen: &CodegenContext, field_tys: &[ast::Type]) -> (u64, u64) {
+    let mut size = 0;
+    let mut max_align = 1;
+
+    for ty in field_tys {
+        let (field_size, align) = layout(codegen, ty);
+        size = size.next_multiple_of(align) + field_size;
+        max_align = max_align.max(align);
+    }
+
+    (size.next_multiple_of(max_align), max_align)
+}
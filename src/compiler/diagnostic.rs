@@ -0,0 +1,28 @@
+use std::fmt::{Display, Formatter};
+
+use crate::compiler::semantic_analysis::lints::Lint;
+use crate::compiler::span::Span;
+
+/// A problem in the source code that does not prevent compilation, unless its lint is denied
+#[derive(Debug)]
+pub struct Warning {
+    pub lint: Lint,
+    pub span: Span,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(lint: Lint, span: Span, message: impl Into<String>) -> Self {
+        Self {
+            lint,
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "warning[{}]: {}: {}", self.lint, self.span, self.message)
+    }
+}
@@ -1,20 +1,33 @@
 use std::iter;
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 
-use token::Token;
+use token::{SpannedToken, Token};
+
+use crate::compiler::span::{Location, Span};
 
 pub mod token;
 
 pub fn tokenize(src: &str) -> Result<Vec<Token>> {
+    Ok(tokenize_with_spans(src)?
+        .into_iter()
+        .map(|spanned_token| spanned_token.token)
+        .collect())
+}
+
+/// Same as [`tokenize`], but every token also carries its location in the source code
+pub fn tokenize_with_spans(src: &str) -> Result<Vec<SpannedToken>> {
     let mut lexer = Lexer {
         remaining_src_code: src,
+        location: Location::default(),
     };
     iter::from_fn(|| lexer.next_token().transpose()).collect()
 }
 
 pub struct Lexer<'a> {
     remaining_src_code: &'a str,
+    /// Location of the next char in `remaining_src_code`
+    location: Location,
 }
 
 impl<'a> Lexer<'a> {
@@ -28,9 +41,26 @@ impl<'a> Lexer<'a> {
         let first_char = chars.next();
         self.remaining_src_code = chars.as_str();
 
+        match first_char {
+            Some('\n') => {
+                self.location.line += 1;
+                self.location.column = 1;
+            }
+            Some(_) => self.location.column += 1,
+            None => {}
+        }
+
         first_char
     }
 
+    fn eat_char_if(&mut self, expected: char) -> Option<char> {
+        if self.peek_char() == Some(expected) {
+            self.eat_char()
+        } else {
+            None
+        }
+    }
+
     fn expect_char(&mut self, expected: char) -> Result<()> {
         let c = self.eat_char();
         if c != Some(expected) {
@@ -56,31 +86,205 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    fn try_read_number(&mut self) -> Option<u32> {
-        self.peek_char()
-            .filter(|c| c.is_numeric())
-            .map(|first_char| {
+    /// Skips a line comment (`// ...`) or a block comment (`/* ... */`), returns whether a comment was skipped.
+    /// Block comments can be nested. Doc comments (`/// ...`) are not skipped, because they are tokens.
+    fn try_skip_comment(&mut self) -> Result<bool> {
+        let src = self.remaining_src_code;
+
+        if src.starts_with("//") && !is_doc_comment(src) {
+            while self.peek_char().is_some_and(|c| c != '\n') {
                 let _ = self.eat_char();
-                let mut number_value = String::from(first_char);
+            }
 
-                while let Some(c) = self.peek_char() {
-                    if c.is_numeric() {
-                        let _ = self.eat_char();
-                        number_value.push(c);
-                    } else {
+            Ok(true)
+        } else if src.starts_with("/*") {
+            let start = self.location;
+            let mut depth = 0_u32;
+
+            loop {
+                if self.remaining_src_code.starts_with("/*") {
+                    self.eat_char();
+                    self.eat_char();
+                    depth += 1;
+                } else if self.remaining_src_code.starts_with("*/") {
+                    self.eat_char();
+                    self.eat_char();
+                    depth -= 1;
+                    if depth == 0 {
                         break;
                     }
+                } else if self.eat_char().is_none() {
+                    bail!("{start}: Unterminated block comment, reached end of file instead");
                 }
+            }
 
-                number_value
-                    .parse::<u32>()
-                    .expect("number to be valid because it consists of only numerics")
-            })
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Reads the text of a doc comment (`/// ...`) up to the end of the line. A single leading space is removed.
+    fn read_doc_comment(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek_char().filter(|&c| c != '\n' && c != '\r') {
+            let _ = self.eat_char();
+            text.push(c);
+        }
+
+        text.strip_prefix(' ').map(str::to_owned).unwrap_or(text)
+    }
+
+    /// Reads a string literal (`"..."`), resolving all escape sequences
+    fn try_read_string(&mut self) -> Result<Option<String>> {
+        let start = self.location;
+
+        if self.peek_char() != Some('"') {
+            return Ok(None);
+        }
+        let _ = self.eat_char();
+
+        let mut value = String::new();
+        loop {
+            match self.eat_char() {
+                Some('"') => break,
+                Some('\\') => value.push(self.read_escape_sequence()?),
+                Some(c) => value.push(c),
+                None => bail!("{start}: Unterminated string literal, reached end of file instead"),
+            }
+        }
+
+        Ok(Some(value))
+    }
+
+    /// Reads a char literal (`'a'`), resolving its escape sequence. It must contain exactly one unicode scalar value.
+    fn try_read_char(&mut self) -> Result<Option<char>> {
+        let start = self.location;
+
+        if self.peek_char() != Some('\'') {
+            return Ok(None);
+        }
+        let _ = self.eat_char();
+
+        let value = match self.eat_char() {
+            Some('\\') => self.read_escape_sequence()?,
+            Some('\'') => bail!("{start}: Empty char literal"),
+            Some('\n') | None => bail!("{start}: Unterminated char literal"),
+            Some(c) => c,
+        };
+
+        match self.eat_char() {
+            Some('\'') => Ok(Some(value)),
+            Some('\n') | None => bail!("{start}: Unterminated char literal"),
+            Some(_) => bail!("{start}: Char literals must contain exactly one character, use a string literal instead"),
+        }
+    }
+
+    /// Reads an escape sequence, the leading backslash must already be consumed.
+    /// Supported are `\n`, `\r`, `\t`, `\0`, `\\`, `\"`, `\'` and unicode escapes like `\u{1F600}`.
+    fn read_escape_sequence(&mut self) -> Result<char> {
+        let start = self.location;
+
+        Ok(match self.eat_char() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('u') => {
+                self.expect_char('{')?;
+
+                let mut digits = String::new();
+                while let Some(c) = self.peek_char().filter(|&c| c != '}') {
+                    let _ = self.eat_char();
+                    if !c.is_ascii_hexdigit() && c != '_' {
+                        bail!("{start}: Invalid character `{c}` in unicode escape sequence");
+                    }
+                    if c != '_' {
+                        digits.push(c);
+                    }
+                }
+                self.expect_char('}')?;
+
+                if digits.is_empty() || digits.len() > 6 {
+                    bail!("{start}: Unicode escape sequences must have between 1 and 6 hexadecimal digits");
+                }
+
+                let value = u32::from_str_radix(&digits, 16).expect(
+                    "digits to be valid because they consist of at most 6 hexadecimal digits",
+                );
+                if (0xD800..=0xDFFF).contains(&value) {
+                    bail!("{start}: Invalid unicode escape sequence, `{value:X}` is a surrogate code point and not a unicode scalar value");
+                }
+                char::from_u32(value).ok_or_else(|| {
+                    anyhow!("{start}: Invalid unicode escape sequence, `{value:X}` is larger than the maximum unicode scalar value `10FFFF`")
+                })?
+            }
+            Some(other) => bail!("{start}: Unknown escape sequence `\\{other}`"),
+            None => bail!("{start}: Expected escape sequence, reached end of file instead"),
+        })
+    }
+
+    /// Reads an integer literal. Decimal, hexadecimal (`0x`), octal (`0o`) and binary (`0b`) literals are supported.
+    /// Digits may be separated by `_`. Only ASCII digits are accepted.
+    fn try_read_number(&mut self) -> Result<Option<u32>> {
+        let start = self.location;
+
+        let Some(first_char) = self.peek_char().filter(char::is_ascii_digit) else {
+            return Ok(None);
+        };
+        let _ = self.eat_char();
+
+        let (radix, radix_name) = match (first_char, self.peek_char()) {
+            ('0', Some('x')) => (16, "hexadecimal"),
+            ('0', Some('o')) => (8, "octal"),
+            ('0', Some('b')) => (2, "binary"),
+            _ => (10, "decimal"),
+        };
+
+        let mut literal = String::from(first_char);
+        let mut digits = String::new();
+        if radix == 10 {
+            digits.push(first_char);
+        } else {
+            literal.extend(self.eat_char());
+        }
+
+        // Alphanumeric chars are consumed as well, so that a literal like `12ab` is reported as a whole
+        while let Some(c) = self.peek_char() {
+            if !c.is_alphanumeric() && c != '_' {
+                break;
+            }
+            let _ = self.eat_char();
+            literal.push(c);
+
+            match c {
+                '_' => {}
+                c if c.is_ascii() && c.is_digit(radix) => digits.push(c),
+                invalid_digit => {
+                    bail!("{start}: Invalid digit `{invalid_digit}` in {radix_name} literal `{literal}`")
+                }
+            }
+        }
+
+        if digits.is_empty() {
+            bail!("{start}: Expected at least one digit in {radix_name} literal `{literal}`");
+        }
+
+        match u32::from_str_radix(&digits, radix) {
+            Ok(number) => Ok(Some(number)),
+            Err(_) => bail!(
+                "{start}: Integer literal `{literal}` is too large, the maximum value is {}",
+                u32::MAX
+            ),
+        }
     }
 
     fn try_read_identifier(&mut self) -> Option<String> {
         self.peek_char()
-            .filter(|c| c.is_alphabetic())
+            .filter(|c| c.is_alphabetic() || *c == '_')
             .map(|first_char| {
                 let _ = self.eat_char();
                 let mut identifier = String::from(first_char);
@@ -99,10 +303,24 @@ impl<'a> Lexer<'a> {
             })
     }
 
-    pub fn next_token(&mut self) -> Result<Option<Token>> {
-        use Token::*;
+    pub fn next_token(&mut self) -> Result<Option<SpannedToken>> {
+        loop {
+            self.skip_whitespaces();
+            if !self.try_skip_comment()? {
+                break;
+            }
+        }
 
-        self.skip_whitespaces();
+        let start = self.location;
+
+        Ok(self.read_token(start)?.map(|token| SpannedToken {
+            token,
+            span: Span::new(start, self.location),
+        }))
+    }
+
+    fn read_token(&mut self, start: Location) -> Result<Option<Token>> {
+        use Token::*;
 
         if let Some(identifier_name) = self.try_read_identifier() {
             let keyword = token::Keyword::try_from_str(identifier_name.as_str());
@@ -116,10 +334,18 @@ impl<'a> Lexer<'a> {
             return Ok(Some(token));
         }
 
-        if let Some(number) = self.try_read_number() {
+        if let Some(number) = self.try_read_number()? {
             return Ok(Some(Number(number)));
         }
 
+        if let Some(string) = self.try_read_string()? {
+            return Ok(Some(StringLiteral(string)));
+        }
+
+        if let Some(value) = self.try_read_char()? {
+            return Ok(Some(CharLiteral(value)));
+        }
+
         // Read all other token types
         self.eat_char()
             .map(|c| {
@@ -130,22 +356,46 @@ impl<'a> Lexer<'a> {
                     ')' => RightParentheses,
                     '{' => LeftBrace,
                     '}' => RightBrace,
-                    ';' | '\n' => NewLine,
-                    '-' => {
-                        self.expect_char('>')?;
-                        RightArrow
+                    ',' => Comma,
+                    ':' => self.eat_char_if(':').map_or(Colon, |_| DoubleColon),
+                    '#' => Hash,
+                    ';' => Semicolon,
+                    '.' => self.eat_char_if('.').map_or(Dot, |_| DoubleDot),
+                    '\n' => NewLine,
+                    '-' => self.eat_char_if('>').map_or(Minus, |_| RightArrow),
+                    '+' => Plus,
+                    '*' => Star,
+                    '%' => Percent,
+                    '=' if self.eat_char_if('>').is_some() => FatArrow,
+                    '=' => self.eat_char_if('=').map_or(Equals, |_| DoubleEquals),
+                    '!' => self.eat_char_if('=').map_or(ExclamationMark, |_| NotEquals),
+                    '<' => self.eat_char_if('=').map_or(LessThan, |_| LessThanOrEquals),
+                    '>' => self
+                        .eat_char_if('=')
+                        .map_or(GreaterThan, |_| GreaterThanOrEquals),
+                    // Line and block comments are already skipped, so two slashes have to start a doc comment
+                    '/' if self.remaining_src_code.starts_with('/') => {
+                        self.expect_char('/')?;
+                        self.expect_char('/')?;
+                        DocComment(self.read_doc_comment())
                     }
+                    '/' => Slash,
                     '\r' => {
                         self.expect_char('\n')?;
                         NewLine
                     }
-                    invalid_char => bail!("Invalid character `{invalid_char}`"),
+                    invalid_char => bail!("{start}: Invalid character `{invalid_char}`"),
                 })
             })
             .transpose()
     }
 }
 
+/// Doc comments start with exactly three slashes. Four or more slashes make a regular line comment.
+fn is_doc_comment(src: &str) -> bool {
+    src.starts_with("///") && !src.starts_with("////")
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
@@ -183,7 +433,7 @@ mod tests {
 
     #[test]
     pub fn identifiers() -> Result<()> {
-        let tokens = tokenize("some identifier123 a_b")?;
+        let tokens = tokenize("some identifier123 a_b _unused")?;
 
         assert_eq!(
             tokens.as_slice(),
@@ -191,6 +441,204 @@ mod tests {
                 Token::Identifier("some".to_owned()),
                 Token::Identifier("identifier123".to_owned()),
                 Token::Identifier("a_b".to_owned()),
+                Token::Identifier("_unused".to_owned()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn numbers() -> Result<()> {
+        let tokens = tokenize("0 42 1_000_000 0x1F 0o17 0b1010 4294967295")?;
+
+        assert_eq!(
+            tokens.as_slice(),
+            &[
+                Token::Number(0),
+                Token::Number(42),
+                Token::Number(1_000_000),
+                Token::Number(0x1F),
+                Token::Number(0o17),
+                Token::Number(0b1010),
+                Token::Number(u32::MAX),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn invalid_numbers() {
+        assert!(tokenize("99999999999").is_err());
+        assert!(tokenize("0x").is_err());
+        assert!(tokenize("0b102").is_err());
+        assert!(tokenize("12ab").is_err());
+        assert!(tokenize("\u{663}").is_err());
+        assert!(tokenize("1\u{663}").is_err());
+    }
+
+    #[test]
+    pub fn comments() -> Result<()> {
+        let tokens = tokenize(
+            "a // line comment\n/* block /* nested */ comment */ b\n/// doc comment\n//// not a doc comment",
+        )?;
+
+        assert_eq!(
+            tokens.as_slice(),
+            &[
+                Token::Identifier("a".to_owned()),
+                Token::NewLine,
+                Token::Identifier("b".to_owned()),
+                Token::NewLine,
+                Token::DocComment("doc comment".to_owned()),
+                Token::NewLine,
+            ]
+        );
+
+        assert!(tokenize("/* /* */").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn strings() -> Result<()> {
+        let tokens = tokenize(r#""hello" "a\n\t\"b\"" "\u{1F600}" """#)?;
+
+        assert_eq!(
+            tokens.as_slice(),
+            &[
+                Token::StringLiteral("hello".to_owned()),
+                Token::StringLiteral("a\n\t\"b\"".to_owned()),
+                Token::StringLiteral("😀".to_owned()),
+                Token::StringLiteral(String::new()),
+            ]
+        );
+
+        assert!(tokenize(r#""unterminated"#).is_err());
+        assert!(tokenize(r#""\q""#).is_err());
+        assert!(tokenize(r#""\u{D800}""#).is_err());
+        assert!(tokenize(r#""\u{}""#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn chars() -> Result<()> {
+        let tokens = tokenize(r"'a' '\n' '\'' '\u{1F600}' 'ä'")?;
+
+        assert_eq!(
+            tokens.as_slice(),
+            &[
+                Token::CharLiteral('a'),
+                Token::CharLiteral('\n'),
+                Token::CharLiteral('\''),
+                Token::CharLiteral('😀'),
+                Token::CharLiteral('ä'),
+            ]
+        );
+
+        assert!(tokenize("''").is_err());
+        assert!(tokenize("'ab'").is_err());
+        assert!(tokenize("'a").is_err());
+        assert!(tokenize(r"'\u{DFFF}'").is_err());
+        assert!(tokenize(r"'\u{110000}'").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn comparisons() -> Result<()> {
+        let tokens = tokenize("== != < <= > >= ! =")?;
+
+        assert_eq!(
+            tokens.as_slice(),
+            &[
+                Token::DoubleEquals,
+                Token::NotEquals,
+                Token::LessThan,
+                Token::LessThanOrEquals,
+                Token::GreaterThan,
+                Token::GreaterThanOrEquals,
+                Token::ExclamationMark,
+                Token::Equals,
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn arithmetic() -> Result<()> {
+        let tokens = tokenize("1 + 2 - 3 * 4 / 5 % 6 -> 7-8")?;
+
+        assert_eq!(
+            tokens.as_slice(),
+            &[
+                Token::Number(1),
+                Token::Plus,
+                Token::Number(2),
+                Token::Minus,
+                Token::Number(3),
+                Token::Star,
+                Token::Number(4),
+                Token::Slash,
+                Token::Number(5),
+                Token::Percent,
+                Token::Number(6),
+                Token::RightArrow,
+                Token::Number(7),
+                Token::Minus,
+                Token::Number(8),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn ranges() -> Result<()> {
+        let tokens = tokenize("a[1..3].len() ..")?;
+
+        assert_eq!(
+            tokens.as_slice(),
+            &[
+                Token::Identifier("a".to_owned()),
+                Token::LeftSquareBracket,
+                Token::Number(1),
+                Token::DoubleDot,
+                Token::Number(3),
+                Token::RightSquareBracket,
+                Token::Dot,
+                Token::Identifier("len".to_owned()),
+                Token::LeftParentheses,
+                Token::RightParentheses,
+                Token::DoubleDot,
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn match_arms() -> Result<()> {
+        let tokens = tokenize("Shape::Rect(w, _) => w == 1")?;
+
+        assert_eq!(
+            tokens.as_slice(),
+            &[
+                Token::Identifier("Shape".to_owned()),
+                Token::DoubleColon,
+                Token::Identifier("Rect".to_owned()),
+                Token::LeftParentheses,
+                Token::Identifier("w".to_owned()),
+                Token::Comma,
+                Token::Identifier("_".to_owned()),
+                Token::RightParentheses,
+                Token::FatArrow,
+                Token::Identifier("w".to_owned()),
+                Token::DoubleEquals,
+                Token::Number(1),
             ]
         );
 
@@ -1,9 +1,30 @@
+use crate::compiler::span::Span;
+
 #[derive(Debug, PartialEq)]
 pub enum Keyword {
     Fun,
     Exit,
     Int,
     Return,
+    Str,
+    Char,
+    Bool,
+    True,
+    False,
+    As,
+    If,
+    Else,
+    While,
+    Loop,
+    Let,
+    Const,
+    Static,
+    Mut,
+    For,
+    In,
+    Struct,
+    Enum,
+    Match,
 }
 
 impl Keyword {
@@ -15,23 +36,78 @@ impl Keyword {
             "exit" => Some(Exit),
             "int" => Some(Int),
             "return" => Some(Return),
+            "str" => Some(Str),
+            "char" => Some(Char),
+            "bool" => Some(Bool),
+            "true" => Some(True),
+            "false" => Some(False),
+            "as" => Some(As),
+            "if" => Some(If),
+            "else" => Some(Else),
+            "while" => Some(While),
+            "loop" => Some(Loop),
+            "let" => Some(Let),
+            "const" => Some(Const),
+            "static" => Some(Static),
+            "mut" => Some(Mut),
+            "for" => Some(For),
+            "in" => Some(In),
+            "struct" => Some(Struct),
+            "enum" => Some(Enum),
+            "match" => Some(Match),
             _ => None,
         }
     }
 }
 
-// TODO add location information (line:column)
 #[derive(Debug, PartialEq)]
 pub enum Token {
     Keyword(Keyword),
     Identifier(String),
     Number(u32),
+    /// Value of a string literal with all escape sequences already resolved
+    StringLiteral(String),
+    /// Value of a char literal with its escape sequence already resolved
+    CharLiteral(char),
     LeftBrace,
     RightBrace,
     LeftParentheses,
     RightParentheses,
     LeftSquareBracket,
     RightSquareBracket,
+    Comma,
+    Colon,
+    /// Separates an enum from its variant like in `Shape::Circle`
+    DoubleColon,
+    /// Separates the element type and length of an array, outside of brackets it ends a statement like a new line
+    Semicolon,
+    Dot,
+    /// Separates the bounds of a range like `1..3`
+    DoubleDot,
+    Hash,
     NewLine,
     RightArrow,
+    /// Separates the pattern of a match arm from its body
+    FatArrow,
+    ExclamationMark,
+    Equals,
+    DoubleEquals,
+    NotEquals,
+    LessThan,
+    LessThanOrEquals,
+    GreaterThan,
+    GreaterThanOrEquals,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    /// Text of a `///` comment, documenting the item that follows it
+    DocComment(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
 }
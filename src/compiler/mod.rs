@@ -2,29 +2,59 @@ use std::path::Path;
 
 use anyhow::{Context, Result};
 
+use options::{Emit, Options};
 use symbol_table::Sym;
 
+pub use semantic_analysis::call_graph::CallGraph;
+pub use semantic_analysis::AnalyzedAST;
+
 mod codegen;
+mod diagnostic;
 mod lexer;
+pub mod options;
 mod parser;
 mod ref_arena;
 mod semantic_analysis;
+mod span;
 pub mod symbol_table;
 
-// TODO add compiler options/flags
+/// Analyses the yyn source code `src_code` without generating code, e.g. to inspect its call graph
+pub fn analyse(src_code: &str, options: &Options) -> Result<AnalyzedAST> {
+    let tokens = lexer::tokenize_with_spans(src_code)?;
+    let sym = Sym::new();
+    let ast_root = parser::parse(tokens, sym.clone())?;
 
-pub fn compile(src: &Path, llvm_ir_out: &Path) -> Result<()> {
+    semantic_analysis::analyse(ast_root, sym, options)
+}
+
+pub fn compile(src: &Path, out: &Path, options: &Options) -> Result<()> {
     let src_code = std::fs::read_to_string(src).context("Failed to read source code file")?;
 
-    let tokens = lexer::tokenize(&src_code)?;
-    dbg!(&tokens);
+    let tokens = lexer::tokenize_with_spans(&src_code)?;
+    if options.emit == Emit::Tokens {
+        return std::fs::write(out, format!("{tokens:#?}\n")).context("Failed to write tokens");
+    }
 
     let sym = Sym::new();
 
     let ast_root = parser::parse(tokens, sym.clone())?;
-    dbg!(&ast_root);
-
-    let analyzed_ast_root = semantic_analysis::analyse(ast_root, sym.clone())?;
-
-    codegen::generate(analyzed_ast_root, sym.clone(), llvm_ir_out)
+    if options.emit == Emit::Ast {
+        return std::fs::write(out, format!("{ast_root:#?}\n")).context("Failed to write AST");
+    }
+
+    let analyzed_ast_root = semantic_analysis::analyse(ast_root, sym.clone(), options)?;
+    for warning in &analyzed_ast_root.warnings {
+        eprintln!("{warning}");
+    }
+
+    match options.emit {
+        Emit::LlvmIr => {
+            codegen::generate(analyzed_ast_root, sym.clone(), src, &src_code, out, options)
+        }
+        Emit::CallGraph => std::fs::write(out, analyzed_ast_root.call_graph.to_dot())
+            .context("Failed to write call graph"),
+        Emit::Tokens | Emit::Ast => {
+            unreachable!("tokens and syntax tree to be written before semantic analysis")
+        }
+    }
 }
@@ -0,0 +1,178 @@
+use anyhow::{anyhow, bail, Result};
+
+use crate::compiler::semantic_analysis::lints::{Lint, LintConfig, LintLevel};
+
+/// Options changing the behavior of the compiler, which are set through command line flags
+#[derive(Debug, Default)]
+pub struct Options {
+    pub lints: LintConfig,
+    /// Compile a library instead of an executable, which does not need a `main` function
+    pub lib: bool,
+    pub emit: Emit,
+    /// Build for release instead of debugging, which disables overflow checks unless they are enabled explicitly
+    pub release: bool,
+    /// Explicitly enables or disables overflow checks, see [`Options::overflow_checks`]
+    pub overflow_checks: Option<bool>,
+    /// Explicitly enables or disables assertions, see [`Options::debug_assertions`]
+    pub debug_assertions: Option<bool>,
+}
+
+/// What the compiler writes to its output file
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Emit {
+    #[default]
+    LlvmIr,
+    /// The call graph of the program in the DOT format of Graphviz
+    CallGraph,
+    /// The tokens of the source code, for debugging the lexer
+    Tokens,
+    /// The syntax tree before semantic analysis, for debugging the parser
+    Ast,
+}
+
+impl Emit {
+    /// File extension of the output file, if none is specified
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Emit::LlvmIr => "ll",
+            Emit::CallGraph => "dot",
+            Emit::Tokens => "tokens",
+            Emit::Ast => "ast",
+        }
+    }
+}
+
+impl Options {
+    /// Whether `+`, `-` and `*` panic on integer overflow at runtime instead of wrapping around.
+    /// Overflow checks are enabled by default in debug builds.
+    pub fn overflow_checks(&self) -> bool {
+        self.overflow_checks.unwrap_or(!self.release)
+    }
+
+    /// Whether `assert` and `assert_eq` are checked at runtime. Otherwise they are compiled out and their arguments are
+    /// not evaluated. Assertions are enabled by default in debug builds.
+    pub fn debug_assertions(&self) -> bool {
+        self.debug_assertions.unwrap_or(!self.release)
+    }
+
+    /// Applies a command line flag like `--deny=shadowing`, whose leading `--` is already stripped
+    pub fn apply_flag(&mut self, flag: &str) -> Result<()> {
+        match flag.split_once('=').unwrap_or((flag, "")) {
+            ("lib", "") => self.lib = true,
+            ("release", "") => self.release = true,
+            // Aliases of `-C overflow-checks=on|off` from before there were code generation options
+            ("overflow-checks", "") => self.overflow_checks = Some(true),
+            ("no-overflow-checks", "") => self.overflow_checks = Some(false),
+            ("emit", "llvm-ir") => self.emit = Emit::LlvmIr,
+            ("emit", "callgraph") => self.emit = Emit::CallGraph,
+            ("emit", "tokens") => self.emit = Emit::Tokens,
+            ("emit", "ast") => self.emit = Emit::Ast,
+            ("emit", other) => {
+                bail!(
+                    "Unknown output `{other}`, expected `llvm-ir`, `callgraph`, `tokens` or `ast`"
+                )
+            }
+            (name, value) => {
+                let Some(level) = LintLevel::try_from_name(name) else {
+                    bail!("Unknown flag `--{flag}`");
+                };
+                let lint =
+                    Lint::try_from_name(value).ok_or_else(|| anyhow!("Unknown lint `{value}`"))?;
+                self.lints.set_level(lint, level);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies a code generation option like `-C overflow-checks=off`, whose leading `-C` is already stripped
+    pub fn apply_codegen_option(&mut self, option: &str) -> Result<()> {
+        let Some((name, value)) = option.split_once('=') else {
+            bail!("Missing value for `-C {option}`, expected `{option}=on` or `{option}=off`");
+        };
+        let enabled = match value {
+            "on" => true,
+            "off" => false,
+            other => bail!("Unknown value `{other}` for `-C {name}`, expected `on` or `off`"),
+        };
+        match name {
+            "overflow-checks" => self.overflow_checks = Some(enabled),
+            "debug-assertions" => self.debug_assertions = Some(enabled),
+            other => bail!("Unknown code generation option `-C {other}`"),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::compiler::options::{Emit, Options};
+
+    #[test]
+    pub fn overflow_checks() -> Result<()> {
+        let mut options = Options::default();
+        assert!(options.overflow_checks());
+
+        options.apply_flag("release")?;
+        assert!(!options.overflow_checks());
+
+        options.apply_codegen_option("overflow-checks=on")?;
+        assert!(options.overflow_checks());
+
+        options.apply_codegen_option("overflow-checks=off")?;
+        assert!(!options.overflow_checks());
+
+        options.apply_flag("overflow-checks")?;
+        assert!(options.overflow_checks());
+
+        options.apply_flag("no-overflow-checks")?;
+        assert!(!options.overflow_checks());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn debug_assertions() -> Result<()> {
+        let mut options = Options::default();
+        assert!(options.debug_assertions());
+
+        options.apply_flag("release")?;
+        assert!(!options.debug_assertions());
+
+        options.apply_codegen_option("debug-assertions=on")?;
+        assert!(options.debug_assertions());
+
+        options.apply_codegen_option("debug-assertions=off")?;
+        assert!(!options.debug_assertions());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn emit() -> Result<()> {
+        let mut options = Options::default();
+        assert_eq!(options.emit, Emit::LlvmIr);
+
+        options.apply_flag("emit=tokens")?;
+        assert_eq!(options.emit, Emit::Tokens);
+        options.apply_flag("emit=ast")?;
+        assert_eq!(options.emit.extension(), "ast");
+        assert!(options.apply_flag("emit=mir").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn invalid_options() {
+        let mut options = Options::default();
+        assert!(options
+            .apply_codegen_option("debug-assertions=maybe")
+            .is_err());
+        assert!(options.apply_codegen_option("overflow-checks").is_err());
+        assert!(options.apply_codegen_option("opt-level=3").is_err());
+        assert!(options.apply_flag("debug-assertions=off").is_err());
+    }
+}
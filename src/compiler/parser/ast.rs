@@ -1,33 +1,669 @@
+use std::fmt::{Display, Formatter};
+
 use crate::compiler::ref_arena::ArenaRef;
-use crate::compiler::symbol_table::Function;
+use crate::compiler::span::{Location, Span};
+use crate::compiler::symbol_table::{
+    ConstValue, Constant, Enum, Function, Scope, Static, Struct, Variable,
+};
 
 #[derive(Debug)]
 pub struct Root {
     pub(crate) functions: Vec<FunctionDefinition>,
+    pub(crate) constants: Vec<ConstantDefinition>,
+    pub(crate) statics: Vec<StaticDefinition>,
+    pub(crate) structs: Vec<StructDefinition>,
+    pub(crate) enums: Vec<EnumDefinition>,
+}
+
+impl Root {
+    /// Returns the function `main`, which is the entry point of an executable
+    pub fn main_function(&self) -> Option<&FunctionDefinition> {
+        self.functions.iter().find(|f| f.sym.get().name == "main")
+    }
 }
 
 #[derive(Debug)]
 pub struct CompoundExpr {
     pub expressions: Vec<Expr>,
+    /// Span from the opening to the closing brace
+    pub span: Span,
+    /// The scope opened by this compound, it is `None` until the compound is resolved during semantic analysis
+    pub scope: Option<ArenaRef<Scope>>,
+}
+
+impl CompoundExpr {
+    /// Span of the closing brace, which is always the last char of a compound
+    pub fn closing_brace_span(&self) -> Span {
+        let end = self.span.end;
+        Span::new(Location::new(end.line, end.column - 1), end)
+    }
+
+    /// Calls `f` for all expressions inside of this compound, including nested ones, in source order
+    pub fn visit(&self, f: &mut impl FnMut(&Expr)) {
+        self.expressions.iter().for_each(|expr| expr.visit(f));
+    }
+}
+
+#[derive(Debug)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+    /// Type of this expression, it is `None` until the expression is type checked during semantic analysis
+    pub ty: Option<Type>,
+    /// Value of this expression if it was evaluated at compile time during semantic analysis
+    pub value: Option<ConstValue>,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            ty: None,
+            value: None,
+        }
+    }
+
+    /// Returns the type of this expression. Must only be called after type checking.
+    pub fn ty(&self) -> Type {
+        self.ty
+            .clone()
+            .expect("expression to be annotated with a type during type checking")
+    }
+
+    /// Calls `f` for this expression and all expressions nested inside of it, in source order
+    pub fn visit(&self, f: &mut impl FnMut(&Expr)) {
+        f(self);
+
+        match &self.kind {
+            ExprKind::Array(elements) | ExprKind::Tuple(elements) => {
+                elements.iter().for_each(|element| element.visit(f))
+            }
+            ExprKind::ArrayRepeat(element, _) => element.visit(f),
+            ExprKind::Assign(assignment) => assignment.value.visit(f),
+            ExprKind::Binary(lhs, _, rhs) | ExprKind::Index(lhs, rhs) => {
+                lhs.visit(f);
+                rhs.visit(f);
+            }
+            ExprKind::BuiltinCall(_, args) | ExprKind::FnCall(FnCall { args, .. }) => {
+                args.iter().for_each(|arg| arg.visit(f))
+            }
+            ExprKind::Cast(value, _)
+            | ExprKind::Field(value, _)
+            | ExprKind::Len(value)
+            | ExprKind::Return(value) => value.visit(f),
+            ExprKind::Struct(literal) => literal
+                .fields
+                .iter()
+                .for_each(|field_init| field_init.value.visit(f)),
+            ExprKind::Variant(literal) => literal.args.iter().for_each(|arg| arg.visit(f)),
+            ExprKind::Match(match_expr) => {
+                match_expr.value.visit(f);
+                for arm in &match_expr.arms {
+                    // The block of an arm belongs to the syntax of the arm, so only its contents are visited
+                    match &arm.body.kind {
+                        ExprKind::Compound(body) => body.visit(f),
+                        _ => arm.body.visit(f),
+                    }
+                }
+            }
+            ExprKind::Compound(compound) | ExprKind::Loop(compound) => compound.visit(f),
+            ExprKind::If(condition, then_branch, else_branch) => {
+                condition.visit(f);
+                then_branch.visit(f);
+                if let Some(else_branch) = else_branch {
+                    else_branch.visit(f);
+                }
+            }
+            ExprKind::For(for_loop) => {
+                for_loop.iterable.visit(f);
+                for_loop.body.visit(f);
+            }
+            ExprKind::Let(let_expr) => let_expr.value.visit(f),
+            ExprKind::Slice(array, start, end) => {
+                array.visit(f);
+                start.iter().chain(end).for_each(|bound| bound.visit(f));
+            }
+            ExprKind::While(condition, body) => {
+                condition.visit(f);
+                body.visit(f);
+            }
+            ExprKind::Constant(_)
+            | ExprKind::Exit(_)
+            | ExprKind::Literal(_)
+            | ExprKind::Static(_)
+            | ExprKind::Variable(_) => {}
+        }
+    }
 }
 
 #[derive(Debug)]
-pub enum Expr {
+pub enum ExprKind {
+    /// An array literal listing all of its elements like `[1, 2, 3]`
+    Array(Vec<Expr>),
+    /// An array literal repeating one element like `[0; 16]`
+    ArrayRepeat(Box<Expr>, u32),
+    Assign(Assignment),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    BuiltinCall(Builtin, Vec<Expr>),
+    Cast(Box<Expr>, Type),
     Compound(Box<CompoundExpr>),
+    /// Reference to a constant, which is a `Variable` before it is resolved during name resolution
+    Constant(ArenaRef<Constant>),
     Exit(u32),
-    FnCall(String),
-    Return(u32),
+    /// Access of a field of a struct like `point.x` or of an element of a tuple like `pair.0`
+    Field(Box<Expr>, FieldRef),
+    FnCall(FnCall),
+    For(For),
+    /// `if` with a condition, the `then` branch and an optional `else` branch. An `else if` is represented as an `else`
+    /// branch that only contains another `if`.
+    If(Box<Expr>, Box<CompoundExpr>, Option<Box<CompoundExpr>>),
+    /// Indexing into an array like `a[i]`, which panics if the index is out of bounds
+    Index(Box<Expr>, Box<Expr>),
+    /// Length of an array or slice like `a.len()`
+    Len(Box<Expr>),
+    Let(Let),
+    Literal(Literal),
+    /// An endless loop, that can only be left by returning or diverging
+    Loop(Box<CompoundExpr>),
+    Match(Match),
+    Return(Box<Expr>),
+    /// Slicing an array or slice like `a[1..3]`, which panics if the range is out of bounds. The bounds are optional and
+    /// default to the start and the end.
+    Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
+    /// Reference to a static, which is a `Variable` before it is resolved during name resolution
+    Static(ArenaRef<Static>),
+    Struct(StructLiteral),
+    Variable(VariableRef),
+    /// A tuple literal like `(1, true)`, a tuple with a single element is written with a trailing comma like `(1,)`
+    Tuple(Vec<Expr>),
+    /// A variant of an enum like `Shape::Circle(3)` or `Shape::Empty`
+    Variant(VariantLiteral),
+    While(Box<Expr>, Box<CompoundExpr>),
+}
+
+impl ExprKind {
+    /// Returns the variables declared by the pattern of a `let`, a `for` or the patterns of a `match` together with the
+    /// spans of their names
+    pub fn declared_variables(&self) -> Vec<(&ArenaRef<Variable>, Span)> {
+        match self {
+            ExprKind::Let(let_expr) => let_expr.pattern.bindings(),
+            ExprKind::For(for_loop) => vec![(&for_loop.variable, for_loop.span)],
+            ExprKind::Match(match_expr) => match_expr
+                .arms
+                .iter()
+                .flat_map(|arm| arm.pattern.bindings())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FnCall {
+    pub name: String,
+    pub args: Vec<Expr>,
+    /// The called function, it is `None` until the call is resolved during semantic analysis
+    pub function: Option<ArenaRef<Function>>,
+}
+
+impl FnCall {
+    /// Returns the called function. Must only be called after name resolution.
+    pub fn function(&self) -> &ArenaRef<Function> {
+        self.function
+            .as_ref()
+            .expect("function call to be resolved during name resolution")
+    }
+}
+
+/// Declaration of new variables like `let (a, b) = f()`, which are initialized by matching the value against an
+/// irrefutable pattern
+#[derive(Debug)]
+pub struct Let {
+    pub pattern: Pattern,
+    /// Declared type of the value, which is inferred from the value if it is omitted
+    pub ty: Option<Type>,
+    pub value: Box<Expr>,
+}
+
+/// Loop over the elements of an array or slice like `for x in values { ... }`
+#[derive(Debug)]
+pub struct For {
+    /// Variable that is bound to the current element in the body
+    pub variable: ArenaRef<Variable>,
+    /// Span of the variable name
+    pub span: Span,
+    pub iterable: Box<Expr>,
+    pub body: Box<CompoundExpr>,
+}
+
+/// A struct literal like `Point { x: 1, y: 2 }`, which initializes every field of the struct
+#[derive(Debug)]
+pub struct StructLiteral {
+    /// Name of the struct type
+    pub name: String,
+    /// Initial values of the fields in source order
+    pub fields: Vec<FieldInit>,
+}
+
+/// Initial value of a field in a struct literal like `x: 1`
+#[derive(Debug)]
+pub struct FieldInit {
+    pub field: FieldRef,
+    pub value: Expr,
+}
+
+/// A variant of an enum like `Shape::Rect(1, 2)`, which initializes every field of the variant
+#[derive(Debug)]
+pub struct VariantLiteral {
+    pub variant: VariantRef,
+    /// Values of the fields, which is empty for variants without fields
+    pub args: Vec<Expr>,
+}
+
+/// Reference to a variant of an enum like `Shape::Circle`
+#[derive(Debug)]
+pub struct VariantRef {
+    pub enum_name: String,
+    pub name: String,
+    /// Span of the whole path like `Shape::Circle`
+    pub span: Span,
+    /// Position of the variant in its enum, it is `None` until the variant is resolved during name resolution
+    pub index: Option<u32>,
+}
+
+impl VariantRef {
+    /// Returns the position of the variant in its enum. Must only be called after name resolution.
+    pub fn index(&self) -> u32 {
+        self.index
+            .expect("variant to be resolved during name resolution")
+    }
+}
+
+impl Display for VariantRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}::{}", self.enum_name, self.name)
+    }
+}
+
+/// A `match` like `match shape { Shape::Circle(r) => r * r, _ => 0 }`. The first arm whose pattern matches the value
+/// is evaluated and its value is the value of the whole `match`.
+#[derive(Debug)]
+pub struct Match {
+    pub value: Box<Expr>,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    /// An expression or, if the `match` is a statement, a compound
+    pub body: Expr,
+}
+
+#[derive(Debug)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum PatternKind {
+    /// `_`, which matches any value
+    Wildcard,
+    /// A name, which matches any value and binds it to a new variable
+    Binding(ArenaRef<Variable>),
+    /// A literal like `1`, `'a'` or `true`, which matches values that are equal to it
+    Literal(ConstValue),
+    /// A variant of an enum like `Shape::Rect(w, _)` with a pattern for every field
+    Variant(VariantRef, Vec<Pattern>),
+    /// A tuple like `(a, _)` with a pattern for every element
+    Tuple(Vec<Pattern>),
+}
+
+impl Pattern {
+    /// Returns the variables bound by this pattern and all patterns nested inside of it, together with their spans
+    pub fn bindings(&self) -> Vec<(&ArenaRef<Variable>, Span)> {
+        match &self.kind {
+            PatternKind::Binding(variable) => vec![(variable, self.span)],
+            PatternKind::Variant(_, fields) | PatternKind::Tuple(fields) => {
+                fields.iter().flat_map(Pattern::bindings).collect()
+            }
+            PatternKind::Wildcard | PatternKind::Literal(_) => Vec::new(),
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            PatternKind::Wildcard => write!(f, "_"),
+            PatternKind::Binding(variable) => write!(f, "{}", variable.get().name),
+            PatternKind::Literal(value) => write!(f, "{value}"),
+            PatternKind::Variant(variant, fields) if fields.is_empty() => write!(f, "{variant}"),
+            PatternKind::Variant(variant, fields) => {
+                let fields: Vec<_> = fields.iter().map(Pattern::to_string).collect();
+                write!(f, "{variant}({})", fields.join(", "))
+            }
+            PatternKind::Tuple(elements) => write!(f, "({})", format_tuple(elements)),
+        }
+    }
+}
+
+/// Reference to a field of a struct by its name or to an element of a tuple by its position like `0`
+#[derive(Debug)]
+pub struct FieldRef {
+    pub name: String,
+    /// Span of the field name
+    pub span: Span,
+    /// Position of the field in its struct or tuple, it is `None` until the field is resolved during type checking
+    pub index: Option<u32>,
+}
+
+impl FieldRef {
+    pub fn new(name: String, span: Span) -> Self {
+        Self {
+            name,
+            span,
+            index: None,
+        }
+    }
+
+    /// Returns the position of the field in its struct. Must only be called after type checking.
+    pub fn index(&self) -> u32 {
+        self.index
+            .expect("field to be resolved during type checking")
+    }
+}
+
+/// Assignment of a new value to a `static mut` or one of its fields
+#[derive(Debug)]
+pub struct Assignment {
+    pub name: String,
+    /// The assigned static, it is `None` until the assignment is resolved during semantic analysis
+    pub target: Option<ArenaRef<Static>>,
+    /// The assigned field of the static like `x` in `ORIGIN.x = 1`, with one entry for every level of nesting. It is
+    /// empty if the whole static is assigned.
+    pub fields: Vec<FieldRef>,
+    /// Span of the assigned name
+    pub span: Span,
+    pub value: Box<Expr>,
+}
+
+impl Assignment {
+    /// Returns the assigned static. Must only be called after name resolution.
+    pub fn target(&self) -> &ArenaRef<Static> {
+        self.target
+            .as_ref()
+            .expect("assignment to be resolved during name resolution")
+    }
+}
+
+#[derive(Debug)]
+pub struct VariableRef {
+    pub name: String,
+    /// The referenced variable, it is `None` until the reference is resolved during semantic analysis
+    pub variable: Option<ArenaRef<Variable>>,
+}
+
+impl VariableRef {
+    /// Returns the referenced variable. Must only be called after name resolution.
+    pub fn variable(&self) -> &ArenaRef<Variable> {
+        self.variable
+            .as_ref()
+            .expect("variable reference to be resolved during name resolution")
+    }
+}
+
+#[derive(Debug)]
+pub enum Literal {
+    Int(u32),
+    Str(String),
+    Char(char),
+    Bool(bool),
+}
+
+/// Functions that are provided by the compiler and its runtime instead of being defined in yyn source code
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Builtin {
+    Print,
+    Println,
+    /// Aborts the program with a message and the source location of the call
+    Panic,
+    /// Panics if its condition is false
+    Assert,
+    /// Panics if its two arguments are not equal, printing both of them
+    AssertEq,
+    /// Adds two ints, wrapping around on overflow
+    WrappingAdd,
+    /// Adds two ints, panicking on overflow even if overflow checks are disabled
+    CheckedAdd,
+    /// Adds two ints, clamping the result to the range of `int` on overflow
+    SaturatingAdd,
+}
+
+impl Builtin {
+    pub const ALL: [Builtin; 8] = [
+        Builtin::Print,
+        Builtin::Println,
+        Builtin::Panic,
+        Builtin::Assert,
+        Builtin::AssertEq,
+        Builtin::WrappingAdd,
+        Builtin::CheckedAdd,
+        Builtin::SaturatingAdd,
+    ];
+
+    pub fn try_from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|builtin| builtin.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Builtin::Print => "print",
+            Builtin::Println => "println",
+            Builtin::Panic => "panic",
+            Builtin::Assert => "assert",
+            Builtin::AssertEq => "assert_eq",
+            Builtin::WrappingAdd => "wrapping_add",
+            Builtin::CheckedAdd => "checked_add",
+            Builtin::SaturatingAdd => "saturating_add",
+        }
+    }
+
+    /// Whether calling this builtin does more than computing its result, so it cannot be evaluated at compile time.
+    /// A `panic` or a failed assertion can be evaluated, it is reported as a compile time error instead.
+    pub fn has_side_effects(&self) -> bool {
+        matches!(self, Builtin::Print | Builtin::Println)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BinaryOp {
+    Equals,
+    NotEquals,
+    LessThan,
+    LessThanOrEquals,
+    GreaterThan,
+    GreaterThanOrEquals,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl BinaryOp {
+    /// Whether this operator compares its operands and results in a `bool`
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Equals
+                | BinaryOp::NotEquals
+                | BinaryOp::LessThan
+                | BinaryOp::LessThanOrEquals
+                | BinaryOp::GreaterThan
+                | BinaryOp::GreaterThanOrEquals
+        )
+    }
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            BinaryOp::Equals => "==",
+            BinaryOp::NotEquals => "!=",
+            BinaryOp::LessThan => "<",
+            BinaryOp::LessThanOrEquals => "<=",
+            BinaryOp::GreaterThan => ">",
+            BinaryOp::GreaterThanOrEquals => ">=",
+            BinaryOp::Add => "+",
+            BinaryOp::Subtract => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/",
+            BinaryOp::Remainder => "%",
+        };
+
+        write!(f, "{symbol}")
+    }
 }
 
 #[derive(Debug)]
 pub struct FunctionDefinition {
     pub sym: ArenaRef<Function>,
+    /// Span of the function name
+    pub span: Span,
+
+    /// Text of the `///` comments preceding this function, lines are separated by `\n`
+    pub doc: Option<String>,
+    pub attributes: Vec<Attribute>,
 
     pub compound: CompoundExpr,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// A constant like `const ANSWER: int = 6 * 7`, whose value is evaluated at compile time
+#[derive(Debug)]
+pub struct ConstantDefinition {
+    pub sym: ArenaRef<Constant>,
+    /// Span of the constant name
+    pub span: Span,
+
+    /// Text of the `///` comments preceding this constant, lines are separated by `\n`
+    pub doc: Option<String>,
+
+    pub value: Expr,
+}
+
+/// A global variable like `static mut COUNTER: int = 0`, whose initial value is evaluated at compile time
+#[derive(Debug)]
+pub struct StaticDefinition {
+    pub sym: ArenaRef<Static>,
+    /// Span of the static name
+    pub span: Span,
+
+    /// Text of the `///` comments preceding this static, lines are separated by `\n`
+    pub doc: Option<String>,
+
+    pub value: Expr,
+}
+
+/// A struct type like `struct Point { x: int, y: int }`
+#[derive(Debug)]
+pub struct StructDefinition {
+    pub sym: ArenaRef<Struct>,
+    /// Span of the struct name
+    pub span: Span,
+
+    /// Text of the `///` comments preceding this struct, lines are separated by `\n`
+    pub doc: Option<String>,
+}
+
+/// An enum type like `enum Shape { Circle(int), Rect(int, int), Empty }`
+#[derive(Debug)]
+pub struct EnumDefinition {
+    pub sym: ArenaRef<Enum>,
+    /// Span of the enum name
+    pub span: Span,
+
+    /// Text of the `///` comments preceding this enum, lines are separated by `\n`
+    pub doc: Option<String>,
+}
+
+/// An attribute like `#[allow(unused_functions)]` in front of a function definition
+#[derive(Debug)]
+pub struct Attribute {
+    pub name: String,
+    pub args: Vec<String>,
+    /// Span from the `#` to the closing square bracket
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
     Unit,
     Int,
+    Str,
+    /// A unicode scalar value
+    Char,
+    Bool,
+    /// Type of expressions that never complete, like `exit`. It can be used in place of any other type.
+    Never,
+    /// A fixed number of elements of the same type like `[int; 4]`
+    Array(Box<Type>, u32),
+    /// A view into any number of consecutive elements of an array like `[int]`, represented as a pointer to the first
+    /// element and the number of elements
+    Slice(Box<Type>),
+    /// A fixed number of elements of possibly different types like `(int, bool)`
+    Tuple(Vec<Type>),
+    /// A struct declared like `struct Point { x: int, y: int }`, which is referenced by its name. Every named type is a
+    /// `Struct` before it is resolved during name resolution.
+    Struct(String),
+    /// An enum declared like `enum Shape { Circle(int), Empty }`, which is referenced by its name
+    Enum(String),
+}
+
+impl Type {
+    /// Whether a value of this type can be used where a value of type `expected` is expected
+    pub fn coerces_to(&self, expected: &Type) -> bool {
+        self == expected || *self == Type::Never
+    }
+
+    /// Returns the type of the elements of an array or slice
+    pub fn element_ty(&self) -> Option<&Type> {
+        match self {
+            Type::Array(element_ty, _) | Type::Slice(element_ty) => Some(element_ty),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Type::Unit => "()",
+            Type::Int => "int",
+            Type::Str => "str",
+            Type::Char => "char",
+            Type::Bool => "bool",
+            Type::Never => "!",
+            Type::Array(element_ty, len) => return write!(f, "[{element_ty}; {len}]"),
+            Type::Slice(element_ty) => return write!(f, "[{element_ty}]"),
+            Type::Tuple(element_tys) => return write!(f, "({})", format_tuple(element_tys)),
+            Type::Struct(name) | Type::Enum(name) => name.as_str(),
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// Formats the elements of a tuple separated by commas, with a trailing comma for a single element like `1,`
+pub fn format_tuple(elements: &[impl Display]) -> String {
+    match elements {
+        [element] => format!("{element},"),
+        _ => elements
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
 }
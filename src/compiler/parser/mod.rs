@@ -2,16 +2,21 @@ use std::collections::VecDeque;
 
 use anyhow::{anyhow, bail, Result};
 
-use crate::compiler::lexer::token::{Keyword, Token};
-use crate::compiler::parser::ast::Type;
-use crate::compiler::symbol_table::Function;
+use crate::compiler::lexer::token::{Keyword, SpannedToken, Token};
+use crate::compiler::parser::ast::{ExprKind, Type};
+use crate::compiler::span::Span;
 use crate::compiler::symbol_table::Sym;
+use crate::compiler::symbol_table::{
+    ConstValue, Constant, Enum, Field, Function, Parameter, Static, Struct, Variable, Variant,
+};
 
 pub mod ast;
 
-pub fn parse(tokens: Vec<Token>, sym: Sym) -> Result<ast::Root> {
+pub fn parse(tokens: Vec<SpannedToken>, sym: Sym) -> Result<ast::Root> {
     let parser = Parser {
         tokens: VecDeque::from(tokens),
+        previous_span: Span::default(),
+        struct_literals_allowed: true,
         sym,
     };
 
@@ -19,103 +24,860 @@ pub fn parse(tokens: Vec<Token>, sym: Sym) -> Result<ast::Root> {
 }
 
 pub struct Parser {
-    tokens: VecDeque<Token>,
+    tokens: VecDeque<SpannedToken>,
+    /// Span of the token that was eaten last
+    previous_span: Span,
+    /// Whether an identifier followed by a brace starts a struct literal. This is not the case in conditions like
+    /// `if done { ... }`, where the brace starts a compound instead.
+    struct_literals_allowed: bool,
     sym: Sym,
 }
 
 impl Parser {
     fn peek_token(&self) -> Option<&Token> {
-        self.tokens.iter().next()
+        self.tokens
+            .front()
+            .map(|spanned_token| &spanned_token.token)
+    }
+
+    fn peek_second_token(&self) -> Option<&Token> {
+        self.tokens.get(1).map(|spanned_token| &spanned_token.token)
+    }
+
+    /// Returns the span of the next token. At the end of the token stream an empty span after the last token is returned.
+    fn peek_span(&self) -> Span {
+        self.tokens.front().map_or(
+            Span::new(self.previous_span.end, self.previous_span.end),
+            |spanned_token| spanned_token.span,
+        )
     }
 
     fn eat_token(&mut self) -> Option<Token> {
-        self.tokens.pop_front()
+        let spanned_token = self.tokens.pop_front()?;
+        self.previous_span = spanned_token.span;
+
+        Some(spanned_token.token)
     }
 
     fn expect_token(&mut self, expected: Token) -> Result<()> {
         let token = self.eat_token().ok_or_else(|| {
-            anyhow!("Expected token `{expected:?}, reached end of token stream instead`")
+            anyhow!(
+                "{}: Expected token `{expected:?}`, reached end of token stream instead",
+                self.previous_span.end
+            )
         })?;
 
         if token != expected {
-            bail!("Expected token `{expected:?}`, got `{token:?}` instead")
+            bail!(
+                "{}: Expected token `{expected:?}`, got `{token:?}` instead",
+                self.previous_span
+            )
         }
 
         Ok(())
     }
 
+    /// Eats an identifier token and returns its name. `description` describes the expected identifier in error messages.
+    fn expect_identifier(&mut self, description: &str) -> Result<String> {
+        let span = self.peek_span();
+
+        match self.eat_token() {
+            Some(Token::Identifier(name)) => Ok(name),
+            Some(other) => bail!("{span}: Expected {description}, got {other:?} instead"),
+            None => bail!("{span}: Expected {description}, reached end of token stream instead"),
+        }
+    }
+
+    /// Expects the name of a field like `x` or the position of an element of a tuple like `0`
+    fn expect_field_name(&mut self, description: &str) -> Result<String> {
+        let span = self.peek_span();
+
+        match self.eat_token() {
+            Some(Token::Identifier(name)) => Ok(name),
+            Some(Token::Number(index)) => Ok(index.to_string()),
+            Some(other) => bail!("{span}: Expected {description}, got {other:?} instead"),
+            None => bail!("{span}: Expected {description}, reached end of token stream instead"),
+        }
+    }
+
     fn has_tokens(&self) -> bool {
         self.peek_token().is_some()
     }
 
     fn parse_compound(&mut self) -> Result<ast::CompoundExpr> {
+        let start = self.peek_span();
         self.expect_token(Token::LeftBrace)?;
 
         let mut expressions = Vec::new();
 
         loop {
-            if let Some(Token::LeftBrace) = self.peek_token() {
-                let sub_compound = self.parse_compound()?;
-                expressions.push(ast::Expr::Compound(Box::new(sub_compound)));
-            }
-
-            let expr = match self.eat_token() {
+            let expr_start = self.peek_span();
+            let kind = match self.peek_token() {
+                Some(Token::LeftBrace) => {
+                    let sub_compound = self.parse_compound()?;
+                    ExprKind::Compound(Box::new(sub_compound))
+                }
                 Some(Token::Keyword(Keyword::Exit)) => {
+                    let _ = self.eat_token();
                     if let Some(Token::Number(exit_code)) = self.eat_token() {
-                        ast::Expr::Exit(exit_code)
+                        ExprKind::Exit(exit_code)
                     } else {
-                        bail!("Expected numeric exit code after exit keyword");
+                        bail!(
+                            "{}: Expected numeric exit code after exit keyword",
+                            self.previous_span
+                        );
                     }
                 }
-                Some(Token::RightBrace) => break,
-                Some(Token::NewLine) => continue,
-                Some(Token::Identifier(fn_name)) => {
-                    self.expect_token(Token::LeftParentheses)?;
-                    self.expect_token(Token::RightParentheses)?;
-                    ast::Expr::FnCall(fn_name)
+                Some(Token::RightBrace) => {
+                    let _ = self.eat_token();
+                    break;
+                }
+                Some(Token::NewLine | Token::Semicolon) => {
+                    let _ = self.eat_token();
+                    continue;
                 }
                 Some(Token::Keyword(Keyword::Return)) => {
-                    let Some(Token::Number(num)) = self.eat_token() else {
-                        bail!("Expected number after return statement");
-                    };
-                    ast::Expr::Return(num)
+                    let _ = self.eat_token();
+                    ExprKind::Return(Box::new(self.parse_expr()?))
+                }
+                Some(Token::Keyword(Keyword::If)) => self.parse_if()?,
+                Some(Token::Keyword(Keyword::Let)) => self.parse_let()?,
+                Some(Token::Identifier(_)) if self.is_assignment() => self.parse_assignment()?,
+                Some(Token::Keyword(Keyword::While)) => {
+                    let _ = self.eat_token();
+                    let condition = self.parse_expr_before_compound()?;
+                    ExprKind::While(Box::new(condition), Box::new(self.parse_compound()?))
+                }
+                Some(Token::Keyword(Keyword::Loop)) => {
+                    let _ = self.eat_token();
+                    ExprKind::Loop(Box::new(self.parse_compound()?))
+                }
+                Some(Token::Keyword(Keyword::For)) => self.parse_for()?,
+                Some(Token::Keyword(Keyword::Match)) => {
+                    let _ = self.eat_token();
+                    self.parse_match(true)?
                 }
-                Some(other) => bail!("Got invalid token `{other:?}` in compound expression"),
-                None => bail!("Expected token, reached end of token stream instead"),
+                Some(Token::DocComment(_)) => bail!(
+                    "{}: Doc comments are only allowed before function definitions",
+                    self.peek_span()
+                ),
+                Some(_) => {
+                    expressions.push(self.parse_expr()?);
+                    continue;
+                }
+                None => bail!(
+                    "{}: Expected token, reached end of token stream instead",
+                    self.peek_span()
+                ),
             };
 
-            expressions.push(expr);
+            expressions.push(ast::Expr::new(kind, expr_start.to(self.previous_span)));
+        }
+
+        Ok(ast::CompoundExpr {
+            expressions,
+            span: start.to(self.previous_span),
+            scope: None,
+        })
+    }
+
+    /// Parses an `if` with its branches. The `else` has to be on the same line as the closing brace of the `then` branch.
+    fn parse_if(&mut self) -> Result<ExprKind> {
+        self.expect_token(Token::Keyword(Keyword::If))?;
+        let condition = self.parse_expr_before_compound()?;
+        let then_branch = self.parse_compound()?;
+
+        if self.peek_token() != Some(&Token::Keyword(Keyword::Else)) {
+            return Ok(ExprKind::If(
+                Box::new(condition),
+                Box::new(then_branch),
+                None,
+            ));
+        }
+        let _ = self.eat_token();
+
+        let else_branch = if self.peek_token() == Some(&Token::Keyword(Keyword::If)) {
+            let start = self.peek_span();
+            let else_if = self.parse_if()?;
+            let span = start.to(self.previous_span);
+
+            ast::CompoundExpr {
+                expressions: vec![ast::Expr::new(else_if, span)],
+                span,
+                scope: None,
+            }
+        } else {
+            self.parse_compound()?
+        };
+
+        Ok(ExprKind::If(
+            Box::new(condition),
+            Box::new(then_branch),
+            Some(Box::new(else_branch)),
+        ))
+    }
+
+    /// Parses a variable declaration like `let x: int = 1` or `let (a, b) = f()`, where the type is optional
+    fn parse_let(&mut self) -> Result<ExprKind> {
+        self.expect_token(Token::Keyword(Keyword::Let))?;
+
+        let pattern = self.parse_pattern()?;
+
+        let ty = if self.peek_token() == Some(&Token::Colon) {
+            let _ = self.eat_token();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+
+        self.expect_token(Token::Equals)?;
+        let value = self.parse_expr()?;
+
+        Ok(ExprKind::Let(ast::Let {
+            pattern,
+            ty,
+            value: Box::new(value),
+        }))
+    }
+
+    /// Parses a loop over the elements of an array or slice like `for x in values { ... }`
+    fn parse_for(&mut self) -> Result<ExprKind> {
+        self.expect_token(Token::Keyword(Keyword::For))?;
+
+        let span = self.peek_span();
+        let name = self.expect_identifier("loop variable name")?;
+        self.expect_token(Token::Keyword(Keyword::In))?;
+        let iterable = self.parse_expr_before_compound()?;
+        let body = self.parse_compound()?;
+
+        Ok(ExprKind::For(ast::For {
+            variable: self.sym.add_variable(Variable::new(name, None)),
+            span,
+            iterable: Box::new(iterable),
+            body: Box::new(body),
+        }))
+    }
+
+    /// Whether the next tokens start an assignment to a name or to a field of it like `origin.x = 1`
+    fn is_assignment(&self) -> bool {
+        let mut tokens = self.tokens.iter().map(|spanned_token| &spanned_token.token);
+        if !matches!(tokens.next(), Some(Token::Identifier(_))) {
+            return false;
+        }
+
+        loop {
+            match tokens.next() {
+                Some(Token::Equals) => return true,
+                Some(Token::Dot)
+                    if matches!(tokens.next(), Some(Token::Identifier(_) | Token::Number(_))) => {}
+                _ => return false,
+            }
+        }
+    }
+
+    /// Parses an assignment like `counter = counter + 1` or `ORIGIN.x = 1`
+    fn parse_assignment(&mut self) -> Result<ExprKind> {
+        let span = self.peek_span();
+        let name = self.expect_identifier("assignment target")?;
+
+        let mut fields = Vec::new();
+        while self.peek_token() == Some(&Token::Dot) {
+            let _ = self.eat_token();
+            let field_span = self.peek_span();
+            let field_name = self.expect_field_name("field name")?;
+            fields.push(ast::FieldRef::new(field_name, field_span));
+        }
+
+        self.expect_token(Token::Equals)?;
+        let value = self.parse_expr()?;
+
+        Ok(ExprKind::Assign(ast::Assignment {
+            name,
+            target: None,
+            fields,
+            span,
+            value: Box::new(value),
+        }))
+    }
+
+    /// Parses an expression that is followed by a compound, like the condition of an `if`. It cannot be a struct
+    /// literal unless it is inside of parentheses, because its brace would be mistaken for the compound.
+    fn parse_expr_before_compound(&mut self) -> Result<ast::Expr> {
+        let allowed = std::mem::replace(&mut self.struct_literals_allowed, false);
+        let expr = self.parse_expr();
+        self.struct_literals_allowed = allowed;
+
+        expr
+    }
+
+    /// Parses something enclosed in parentheses, brackets or braces using `parse`. Struct literals are allowed inside,
+    /// because the closing delimiter has to come before any compound.
+    fn parse_delimited<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let allowed = std::mem::replace(&mut self.struct_literals_allowed, true);
+        let result = parse(self);
+        self.struct_literals_allowed = allowed;
+
+        result
+    }
+
+    fn parse_expr(&mut self) -> Result<ast::Expr> {
+        let start = self.peek_span();
+        let lhs = self.parse_additive()?;
+
+        let Some(op) = self.peek_token().and_then(comparison_op) else {
+            return Ok(lhs);
+        };
+        let _ = self.eat_token();
+
+        let rhs = self.parse_additive()?;
+
+        if self.peek_token().and_then(comparison_op).is_some() {
+            bail!(
+                "{}: Comparison operators cannot be chained, use parentheses instead",
+                self.peek_span()
+            );
+        }
+
+        Ok(ast::Expr::new(
+            ExprKind::Binary(Box::new(lhs), op, Box::new(rhs)),
+            start.to(self.previous_span),
+        ))
+    }
+
+    fn parse_additive(&mut self) -> Result<ast::Expr> {
+        self.parse_left_associative(additive_op, Self::parse_multiplicative)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<ast::Expr> {
+        self.parse_left_associative(multiplicative_op, Self::parse_cast)
+    }
+
+    /// Parses a chain of binary operations of the same precedence like `a - b + c`, which is grouped as `(a - b) + c`
+    fn parse_left_associative(
+        &mut self,
+        op_from_token: fn(&Token) -> Option<ast::BinaryOp>,
+        parse_operand: fn(&mut Self) -> Result<ast::Expr>,
+    ) -> Result<ast::Expr> {
+        let start = self.peek_span();
+        let mut expr = parse_operand(self)?;
+
+        while let Some(op) = self.peek_token().and_then(op_from_token) {
+            let _ = self.eat_token();
+            let rhs = parse_operand(self)?;
+            expr = ast::Expr::new(
+                ExprKind::Binary(Box::new(expr), op, Box::new(rhs)),
+                start.to(self.previous_span),
+            );
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_cast(&mut self) -> Result<ast::Expr> {
+        let start = self.peek_span();
+        let mut expr = self.parse_postfix()?;
+
+        while let Some(Token::Keyword(Keyword::As)) = self.peek_token() {
+            let _ = self.eat_token();
+            let kind = ExprKind::Cast(Box::new(expr), self.parse_type()?);
+            expr = ast::Expr::new(kind, start.to(self.previous_span));
+        }
+
+        Ok(expr)
+    }
+
+    /// Parses an expression followed by any number of indices, ranges and `.len()` calls like `grid[y][1..].len()`
+    fn parse_postfix(&mut self) -> Result<ast::Expr> {
+        let start = self.peek_span();
+        let mut expr = self.parse_primary()?;
+
+        loop {
+            let kind = match self.peek_token() {
+                Some(Token::LeftSquareBracket) => {
+                    let _ = self.eat_token();
+                    self.parse_delimited(|parser| parser.parse_index_or_range(expr))?
+                }
+                Some(Token::Dot) => {
+                    let _ = self.eat_token();
+                    let span = self.peek_span();
+                    let name = self.expect_field_name("field or method name")?;
+
+                    if self.peek_token() != Some(&Token::LeftParentheses) {
+                        ExprKind::Field(Box::new(expr), ast::FieldRef::new(name, span))
+                    } else if name == "len" {
+                        self.expect_token(Token::LeftParentheses)?;
+                        self.expect_token(Token::RightParentheses)?;
+                        ExprKind::Len(Box::new(expr))
+                    } else {
+                        bail!("{span}: Unknown method `{name}`, only `len` is supported");
+                    }
+                }
+                _ => return Ok(expr),
+            };
+
+            expr = ast::Expr::new(kind, start.to(self.previous_span));
+        }
+    }
+
+    /// Parses an index like `[i]` or a range like `[1..3]` into `array`, whose opening bracket was already eaten.
+    /// Both bounds of a range are optional.
+    fn parse_index_or_range(&mut self, array: ast::Expr) -> Result<ExprKind> {
+        let start = match self.peek_token() {
+            Some(Token::DoubleDot) => None,
+            _ => Some(Box::new(self.parse_expr()?)),
+        };
+
+        if self.peek_token() != Some(&Token::DoubleDot) {
+            self.expect_token(Token::RightSquareBracket)?;
+            let index = start.expect("index to be parsed if there is no range");
+            return Ok(ExprKind::Index(Box::new(array), index));
+        }
+        let _ = self.eat_token();
+
+        let end = match self.peek_token() {
+            Some(Token::RightSquareBracket) => None,
+            _ => Some(Box::new(self.parse_expr()?)),
+        };
+        self.expect_token(Token::RightSquareBracket)?;
+
+        Ok(ExprKind::Slice(Box::new(array), start, end))
+    }
+
+    fn parse_primary(&mut self) -> Result<ast::Expr> {
+        let start = self.peek_span();
+
+        let kind = match self.eat_token() {
+            Some(Token::Number(num)) => ExprKind::Literal(ast::Literal::Int(num)),
+            Some(Token::StringLiteral(string)) => ExprKind::Literal(ast::Literal::Str(string)),
+            Some(Token::CharLiteral(c)) => ExprKind::Literal(ast::Literal::Char(c)),
+            Some(Token::Keyword(Keyword::True)) => ExprKind::Literal(ast::Literal::Bool(true)),
+            Some(Token::Keyword(Keyword::False)) => ExprKind::Literal(ast::Literal::Bool(false)),
+            Some(Token::Identifier(name))
+                if self.struct_literals_allowed && self.peek_token() == Some(&Token::LeftBrace) =>
+            {
+                self.parse_struct_literal(name)?
+            }
+            Some(Token::Identifier(enum_name))
+                if self.peek_token() == Some(&Token::DoubleColon) =>
+            {
+                let variant = self.parse_variant_ref(enum_name, start)?;
+                let args = if self.peek_token() == Some(&Token::LeftParentheses) {
+                    self.parse_delimited(Self::parse_call_args)?
+                } else {
+                    Vec::new()
+                };
+
+                ExprKind::Variant(ast::VariantLiteral { variant, args })
+            }
+            Some(Token::Identifier(name)) => {
+                if self.peek_token() != Some(&Token::LeftParentheses) {
+                    ExprKind::Variable(ast::VariableRef {
+                        name,
+                        variable: None,
+                    })
+                } else if let Some(builtin) = ast::Builtin::try_from_name(&name) {
+                    ExprKind::BuiltinCall(builtin, self.parse_delimited(Self::parse_call_args)?)
+                } else {
+                    ExprKind::FnCall(ast::FnCall {
+                        name,
+                        args: self.parse_delimited(Self::parse_call_args)?,
+                        function: None,
+                    })
+                }
+            }
+            Some(Token::LeftSquareBracket) => self.parse_delimited(Self::parse_array)?,
+            Some(Token::Keyword(Keyword::Match)) => self.parse_match(false)?,
+            Some(Token::LeftParentheses) => {
+                let (mut elements, is_tuple) =
+                    self.parse_delimited(|parser| parser.parse_parenthesized(Self::parse_expr))?;
+
+                match elements.pop() {
+                    Some(mut expr) if !is_tuple => {
+                        expr.span = start.to(self.previous_span);
+                        return Ok(expr);
+                    }
+                    Some(last) => {
+                        elements.push(last);
+                        ExprKind::Tuple(elements)
+                    }
+                    None => bail!("{start}: Expected expression in parentheses, got `()`"),
+                }
+            }
+            Some(other) => bail!("{start}: Got invalid token `{other:?}` in expression"),
+            None => bail!("{start}: Expected expression, reached end of token stream instead"),
+        };
+
+        Ok(ast::Expr::new(kind, start.to(self.previous_span)))
+    }
+
+    /// Parses the `::Circle` part of a variant like `Shape::Circle`, whose enum name starting at `start` was already
+    /// eaten
+    fn parse_variant_ref(&mut self, enum_name: String, start: Span) -> Result<ast::VariantRef> {
+        self.expect_token(Token::DoubleColon)?;
+        let name = self.expect_identifier("variant name")?;
+
+        Ok(ast::VariantRef {
+            enum_name,
+            name,
+            span: start.to(self.previous_span),
+            index: None,
+        })
+    }
+
+    /// Parses a `match` with its arms, whose `match` keyword was already eaten. The arms may only have blocks as their
+    /// bodies if the `match` is a statement, because blocks have no value.
+    fn parse_match(&mut self, is_statement: bool) -> Result<ExprKind> {
+        let value = self.parse_expr_before_compound()?;
+        self.expect_token(Token::LeftBrace)?;
+
+        let mut arms = Vec::new();
+        self.skip_newlines();
+        while self.peek_token() != Some(&Token::RightBrace) {
+            let pattern = self.parse_pattern()?;
+            self.expect_token(Token::FatArrow)?;
+
+            let body_start = self.peek_span();
+            let body = if self.peek_token() == Some(&Token::LeftBrace) {
+                if !is_statement {
+                    bail!("{body_start}: Blocks are only allowed in the arms of a `match` that is used as a statement");
+                }
+
+                let compound = self.parse_compound()?;
+                ast::Expr::new(
+                    ExprKind::Compound(Box::new(compound)),
+                    body_start.to(self.previous_span),
+                )
+            } else {
+                self.parse_delimited(Self::parse_expr)?
+            };
+            arms.push(ast::MatchArm { pattern, body });
+
+            // Arms are separated by commas or new lines
+            let mut is_separated = false;
+            if self.peek_token() == Some(&Token::Comma) {
+                let _ = self.eat_token();
+                is_separated = true;
+            }
+            while let Some(Token::NewLine | Token::Semicolon) = self.peek_token() {
+                let _ = self.eat_token();
+                is_separated = true;
+            }
+            if !is_separated {
+                break;
+            }
+        }
+        self.expect_token(Token::RightBrace)?;
+
+        Ok(ExprKind::Match(ast::Match {
+            value: Box::new(value),
+            arms,
+        }))
+    }
+
+    /// Parses a pattern of a match arm like `_`, `x`, `1`, `-1`, `'a'`, `true` or `Shape::Rect(w, _)`
+    fn parse_pattern(&mut self) -> Result<ast::Pattern> {
+        let start = self.peek_span();
+
+        let kind = match self.eat_token() {
+            Some(Token::Identifier(name)) if name == "_" => ast::PatternKind::Wildcard,
+            Some(Token::Identifier(enum_name)) if self.peek_token() == Some(&Token::DoubleColon) => {
+                let variant = self.parse_variant_ref(enum_name, start)?;
+
+                let mut fields = Vec::new();
+                if self.peek_token() == Some(&Token::LeftParentheses) {
+                    let _ = self.eat_token();
+                    fields = self.parse_parenthesized(Self::parse_pattern)?.0;
+                }
+
+                ast::PatternKind::Variant(variant, fields)
+            }
+            Some(Token::LeftParentheses) => {
+                let (mut elements, is_tuple) = self.parse_parenthesized(Self::parse_pattern)?;
+                match elements.pop() {
+                    Some(pattern) if !is_tuple => return Ok(pattern),
+                    Some(last) => {
+                        elements.push(last);
+                        ast::PatternKind::Tuple(elements)
+                    }
+                    None => bail!("{start}: Expected pattern in parentheses, got `()`"),
+                }
+            }
+            Some(Token::Identifier(name)) => {
+                ast::PatternKind::Binding(self.sym.add_variable(Variable::new(name, None)))
+            }
+            Some(Token::Number(value)) => ast::PatternKind::Literal(int_pattern(value.into(), start)?),
+            Some(Token::Minus) => match self.eat_token() {
+                Some(Token::Number(value)) => ast::PatternKind::Literal(int_pattern(
+                    -i64::from(value),
+                    start.to(self.previous_span),
+                )?),
+                _ => bail!("{start}: Expected number after `-` in pattern"),
+            },
+            Some(Token::CharLiteral(value)) => ast::PatternKind::Literal(ConstValue::Char(value)),
+            Some(Token::Keyword(Keyword::True)) => ast::PatternKind::Literal(ConstValue::Bool(true)),
+            Some(Token::Keyword(Keyword::False)) => {
+                ast::PatternKind::Literal(ConstValue::Bool(false))
+            }
+            Some(Token::StringLiteral(_)) => bail!(
+                "{start}: String literals are not supported in patterns, because strings cannot be compared"
+            ),
+            Some(other) => bail!("{start}: Expected pattern, got {other:?} instead"),
+            None => bail!("{start}: Expected pattern, reached end of token stream instead"),
+        };
+
+        Ok(ast::Pattern {
+            kind,
+            span: start.to(self.previous_span),
+        })
+    }
+
+    /// Parses a struct literal like `Point { x: 1, y: 2 }`, whose name was already eaten
+    fn parse_struct_literal(&mut self, name: String) -> Result<ExprKind> {
+        self.expect_token(Token::LeftBrace)?;
+
+        let fields = self.parse_delimited(|parser| {
+            parser.parse_fields(|parser, field| {
+                Ok(ast::FieldInit {
+                    field,
+                    value: parser.parse_expr()?,
+                })
+            })
+        })?;
+
+        Ok(ExprKind::Struct(ast::StructLiteral { name, fields }))
+    }
+
+    /// Parses a list of fields like `x: 1, y: 2` up to and including the closing brace. `parse_field` parses whatever
+    /// follows the colon after the name of a field. The fields may be spread over multiple lines.
+    fn parse_fields<T>(
+        &mut self,
+        mut parse_field: impl FnMut(&mut Self, ast::FieldRef) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let mut fields = Vec::new();
+
+        loop {
+            self.skip_newlines();
+            if self.peek_token() == Some(&Token::RightBrace) {
+                break;
+            }
+
+            let span = self.peek_span();
+            let name = self.expect_identifier("field name")?;
+            self.expect_token(Token::Colon)?;
+            fields.push(parse_field(self, ast::FieldRef::new(name, span))?);
+
+            self.skip_newlines();
+            if self.peek_token() == Some(&Token::Comma) {
+                let _ = self.eat_token();
+            } else {
+                break;
+            }
+        }
+        self.skip_newlines();
+        self.expect_token(Token::RightBrace)?;
+
+        Ok(fields)
+    }
+
+    /// Parses an array literal like `[1, 2, 3]` or `[0; 16]`, whose opening bracket was already eaten
+    fn parse_array(&mut self) -> Result<ExprKind> {
+        let mut elements = Vec::new();
+
+        while self.peek_token() != Some(&Token::RightSquareBracket) {
+            elements.push(self.parse_expr()?);
+
+            if elements.len() == 1 && self.peek_token() == Some(&Token::Semicolon) {
+                let _ = self.eat_token();
+                let len = self.expect_array_len()?;
+                self.expect_token(Token::RightSquareBracket)?;
+
+                let element = elements.pop().expect("element to be parsed before");
+                return Ok(ExprKind::ArrayRepeat(Box::new(element), len));
+            }
+
+            if self.peek_token() == Some(&Token::Comma) {
+                let _ = self.eat_token();
+            } else {
+                break;
+            }
+        }
+        self.expect_token(Token::RightSquareBracket)?;
+
+        Ok(ExprKind::Array(elements))
+    }
+
+    /// Eats the length of an array type or repeat literal, which has to be a number literal
+    fn expect_array_len(&mut self) -> Result<u32> {
+        let span = self.peek_span();
+
+        match self.eat_token() {
+            Some(Token::Number(len)) => Ok(len),
+            Some(other) => bail!("{span}: Expected array length, got {other:?} instead"),
+            None => bail!("{span}: Expected array length, reached end of token stream instead"),
+        }
+    }
+
+    /// Parses a parenthesized, comma separated list of arguments
+    fn parse_call_args(&mut self) -> Result<Vec<ast::Expr>> {
+        self.expect_token(Token::LeftParentheses)?;
+
+        let mut args = Vec::new();
+        while self.peek_token() != Some(&Token::RightParentheses) {
+            args.push(self.parse_expr()?);
+
+            if self.peek_token() == Some(&Token::Comma) {
+                let _ = self.eat_token();
+            } else {
+                break;
+            }
         }
+        self.expect_token(Token::RightParentheses)?;
 
-        Ok(ast::CompoundExpr { expressions })
+        Ok(args)
     }
 
     fn parse_type(&mut self) -> Result<ast::Type> {
         let Some(tok) = self.eat_token() else {
-            bail!("Expected token for type definition");
+            bail!("{}: Expected token for type definition", self.peek_span());
         };
 
         Ok(match tok {
             Token::Keyword(Keyword::Int) => ast::Type::Int,
+            Token::Keyword(Keyword::Str) => ast::Type::Str,
+            Token::Keyword(Keyword::Char) => ast::Type::Char,
+            Token::Keyword(Keyword::Bool) => ast::Type::Bool,
+            Token::ExclamationMark => ast::Type::Never,
+            Token::Identifier(name) => ast::Type::Struct(name),
+            Token::LeftSquareBracket => {
+                let element_ty = self.parse_type()?;
+                if self.peek_token() == Some(&Token::RightSquareBracket) {
+                    let _ = self.eat_token();
+                    return Ok(ast::Type::Slice(Box::new(element_ty)));
+                }
+
+                self.expect_token(Token::Semicolon)?;
+                let len = self.expect_array_len()?;
+                self.expect_token(Token::RightSquareBracket)?;
+                ast::Type::Array(Box::new(element_ty), len)
+            }
             Token::LeftParentheses => {
-                self.expect_token(Token::RightParentheses)?;
-                ast::Type::Unit
+                let start = self.previous_span;
+                let (mut element_tys, is_tuple) = self.parse_parenthesized(Self::parse_type)?;
+
+                match element_tys.pop() {
+                    None => ast::Type::Unit,
+                    Some(ty) if !is_tuple => ty,
+                    Some(last) => {
+                        element_tys.push(last);
+                        if let Some(ty) = element_tys
+                            .iter()
+                            .find(|ty| matches!(ty, ast::Type::Unit | ast::Type::Never))
+                        {
+                            bail!(
+                                "{}: Tuples with elements of type `{ty}` are not supported",
+                                start.to(self.previous_span)
+                            );
+                        }
+                        ast::Type::Tuple(element_tys)
+                    }
+                }
             }
-            other => bail!("Expected type token, got {other:?}"),
+            other => bail!("{}: Expected type token, got {other:?}", self.previous_span),
         })
     }
 
-    fn parse_function_def(&mut self) -> Result<ast::FunctionDefinition> {
+    /// Parses comma separated elements using `parse` up to and including the closing parenthesis, whose opening
+    /// parenthesis was already eaten. Returns the elements and whether any comma was found, which distinguishes a tuple
+    /// like `(1,)` from a parenthesized `(1)`.
+    fn parse_parenthesized<T>(
+        &mut self,
+        mut parse: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<(Vec<T>, bool)> {
+        let mut elements = Vec::new();
+        let mut has_comma = false;
+        while self.peek_token() != Some(&Token::RightParentheses) {
+            elements.push(parse(self)?);
+
+            if self.peek_token() == Some(&Token::Comma) {
+                let _ = self.eat_token();
+                has_comma = true;
+            } else {
+                break;
+            }
+        }
+        self.expect_token(Token::RightParentheses)?;
+
+        Ok((elements, has_comma))
+    }
+
+    /// Parses consecutive doc comment lines. Returns `None` if there are none.
+    fn parse_doc_comments(&mut self) -> Option<String> {
+        let mut lines = Vec::new();
+
+        while let Some(Token::DocComment(_)) = self.peek_token() {
+            let Some(Token::DocComment(line)) = self.eat_token() else {
+                unreachable!("token was peeked before");
+            };
+            lines.push(line);
+            self.skip_newlines();
+        }
+
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Parses consecutive attributes like `#[allow(unused_functions)]`
+    fn parse_attributes(&mut self) -> Result<Vec<ast::Attribute>> {
+        let mut attributes = Vec::new();
+
+        while let Some(Token::Hash) = self.peek_token() {
+            let start = self.peek_span();
+            let _ = self.eat_token();
+            self.expect_token(Token::LeftSquareBracket)?;
+
+            let name = self.expect_identifier("attribute name")?;
+
+            let mut args = Vec::new();
+            if self.peek_token() == Some(&Token::LeftParentheses) {
+                let _ = self.eat_token();
+
+                while self.peek_token() != Some(&Token::RightParentheses) {
+                    args.push(self.expect_identifier("attribute argument")?);
+
+                    if self.peek_token() == Some(&Token::Comma) {
+                        let _ = self.eat_token();
+                    } else {
+                        break;
+                    }
+                }
+                self.expect_token(Token::RightParentheses)?;
+            }
+
+            self.expect_token(Token::RightSquareBracket)?;
+            attributes.push(ast::Attribute {
+                name,
+                args,
+                span: start.to(self.previous_span),
+            });
+            self.skip_newlines();
+        }
+
+        Ok(attributes)
+    }
+
+    /// Parses a function definition. A leading `const` must already be consumed and is passed as `is_const`.
+    fn parse_function_def(
+        &mut self,
+        doc: Option<String>,
+        attributes: Vec<ast::Attribute>,
+        is_const: bool,
+    ) -> Result<ast::FunctionDefinition> {
         self.expect_token(Token::Keyword(Keyword::Fun))?;
 
-        let name = match self.eat_token() {
-            Some(Token::Identifier(name)) => name,
-            Some(other) => bail!("Expected function identifier, got {other:?} instead"),
-            None => bail!("Expected function identifier, reached end of token stream instead"),
-        };
+        let span = self.peek_span();
+        let name = self.expect_identifier("function identifier")?;
 
         self.expect_token(Token::LeftParentheses)?;
-        self.expect_token(Token::RightParentheses)?;
+        let (params, _) = self.parse_parenthesized(Self::parse_param)?;
 
         let return_ty = match self.peek_token() {
             Some(Token::RightArrow) => {
@@ -123,37 +885,283 @@ impl Parser {
                 self.parse_type()?
             }
             Some(Token::LeftBrace) => Type::Unit,
-            Some(other) => bail!("Expected function return type, got {other:?} instead"),
-            None => bail!("Expected function return type, reached end of token stream instead"),
+            Some(other) => bail!(
+                "{}: Expected function return type, got {other:?} instead",
+                self.peek_span()
+            ),
+            None => bail!(
+                "{}: Expected function return type, reached end of token stream instead",
+                self.peek_span()
+            ),
         };
 
-        let sym_ref = self.sym.add_function(Function::new(name, return_ty))?;
+        let sym_ref = self
+            .sym
+            .add_function(Function::new(name, params, return_ty, is_const));
 
         let compound = self.parse_compound()?;
 
         Ok(ast::FunctionDefinition {
             compound,
             sym: sym_ref,
+            span,
+            doc,
+            attributes,
+        })
+    }
+
+    /// Parses a parameter of a function like `values: [int]`
+    fn parse_param(&mut self) -> Result<Parameter> {
+        let span = self.peek_span();
+        let name = self.expect_identifier("parameter name")?;
+        self.expect_token(Token::Colon)?;
+        let ty = self.parse_type()?;
+
+        Ok(Parameter {
+            variable: self.sym.add_variable(Variable::new(name, Some(ty))),
+            span,
+        })
+    }
+
+    /// Parses a constant like `const ANSWER: int = 42`, the leading `const` must already be consumed
+    fn parse_constant_def(&mut self, doc: Option<String>) -> Result<ast::ConstantDefinition> {
+        let (span, name, ty, value) = self.parse_global_binding("constant")?;
+        let sym = self.sym.add_constant(Constant::new(name, ty));
+
+        Ok(ast::ConstantDefinition {
+            sym,
+            span,
+            doc,
+            value,
+        })
+    }
+
+    /// Parses a struct type like `struct Point { x: int, y: int }`, the leading `struct` must already be consumed
+    fn parse_struct_def(&mut self, doc: Option<String>) -> Result<ast::StructDefinition> {
+        let span = self.peek_span();
+        let name = self.expect_identifier("struct name")?;
+        self.expect_token(Token::LeftBrace)?;
+
+        let fields = self.parse_fields(|parser, field| {
+            Ok(Field {
+                name: field.name,
+                ty: parser.parse_type()?,
+                span: field.span,
+            })
+        })?;
+
+        Ok(ast::StructDefinition {
+            sym: self.sym.add_struct(Struct::new(name, fields)),
+            span,
+            doc,
         })
     }
 
+    /// Parses an enum type like `enum Shape { Circle(int), Rect(int, int), Empty }`, the leading `enum` must already be
+    /// consumed. The variants may be spread over multiple lines.
+    fn parse_enum_def(&mut self, doc: Option<String>) -> Result<ast::EnumDefinition> {
+        let span = self.peek_span();
+        let name = self.expect_identifier("enum name")?;
+        self.expect_token(Token::LeftBrace)?;
+
+        let mut variants = Vec::new();
+        loop {
+            self.skip_newlines();
+            if self.peek_token() == Some(&Token::RightBrace) {
+                break;
+            }
+
+            let variant_span = self.peek_span();
+            let variant_name = self.expect_identifier("variant name")?;
+
+            let mut fields = Vec::new();
+            if self.peek_token() == Some(&Token::LeftParentheses) {
+                let _ = self.eat_token();
+                while self.peek_token() != Some(&Token::RightParentheses) {
+                    fields.push(self.parse_type()?);
+
+                    if self.peek_token() == Some(&Token::Comma) {
+                        let _ = self.eat_token();
+                    } else {
+                        break;
+                    }
+                }
+                self.expect_token(Token::RightParentheses)?;
+            }
+
+            variants.push(Variant {
+                name: variant_name,
+                fields,
+                span: variant_span,
+            });
+
+            self.skip_newlines();
+            if self.peek_token() == Some(&Token::Comma) {
+                let _ = self.eat_token();
+            } else {
+                break;
+            }
+        }
+        self.skip_newlines();
+        self.expect_token(Token::RightBrace)?;
+
+        Ok(ast::EnumDefinition {
+            sym: self.sym.add_enum(Enum::new(name, variants)),
+            span,
+            doc,
+        })
+    }
+
+    /// Parses a global variable like `static mut COUNTER: int = 0`, the leading `static` must already be consumed
+    fn parse_static_def(&mut self, doc: Option<String>) -> Result<ast::StaticDefinition> {
+        let is_mutable = self.peek_token() == Some(&Token::Keyword(Keyword::Mut));
+        if is_mutable {
+            let _ = self.eat_token();
+        }
+
+        let (span, name, ty, value) = self.parse_global_binding("static")?;
+        let sym = self.sym.add_static(Static::new(name, ty, is_mutable));
+
+        Ok(ast::StaticDefinition {
+            sym,
+            span,
+            doc,
+            value,
+        })
+    }
+
+    /// Parses the `NAME: type = value` part of a constant or static, which has to end with the line.
+    /// Returns the span of the name, the name, the type and the value.
+    fn parse_global_binding(
+        &mut self,
+        description: &str,
+    ) -> Result<(Span, String, ast::Type, ast::Expr)> {
+        let span = self.peek_span();
+        let name = self.expect_identifier(&format!("{description} name"))?;
+
+        self.expect_token(Token::Colon)?;
+        let ty = self.parse_type()?;
+        self.expect_token(Token::Equals)?;
+        let value = self.parse_expr()?;
+
+        match self.peek_token() {
+            Some(Token::NewLine | Token::Semicolon) | None => {}
+            Some(other) => bail!(
+                "{}: Expected end of line after {description}, got {other:?} instead",
+                self.peek_span()
+            ),
+        }
+
+        Ok((span, name, ty, value))
+    }
+
+    /// Skips the ends of lines, including statements that are ended by a semicolon instead
     fn skip_newlines(&mut self) {
-        while let Some(&Token::NewLine) = self.peek_token() {
+        while let Some(Token::NewLine | Token::Semicolon) = self.peek_token() {
             self.eat_token();
         }
     }
 
     fn parse_root(mut self) -> Result<ast::Root> {
         let mut functions = Vec::new();
+        let mut constants = Vec::new();
+        let mut statics = Vec::new();
+        let mut structs = Vec::new();
+        let mut enums = Vec::new();
         loop {
             self.skip_newlines();
             if !self.has_tokens() {
                 break;
             }
 
-            functions.push(self.parse_function_def()?);
+            // Doc comments have to be placed before the attributes of a function
+            let doc = self.parse_doc_comments();
+            let attributes = self.parse_attributes()?;
+            if (doc.is_some() || !attributes.is_empty()) && !self.has_tokens() {
+                bail!(
+                    "{}: Expected function definition after doc comment or attribute, reached end of token stream instead",
+                    self.peek_span()
+                );
+            }
+
+            let is_const = self.peek_token() == Some(&Token::Keyword(Keyword::Const));
+            let is_static = self.peek_token() == Some(&Token::Keyword(Keyword::Static));
+            let is_struct = self.peek_token() == Some(&Token::Keyword(Keyword::Struct));
+            let is_enum = self.peek_token() == Some(&Token::Keyword(Keyword::Enum));
+            if is_const && self.peek_second_token() == Some(&Token::Keyword(Keyword::Fun)) {
+                let _ = self.eat_token();
+                functions.push(self.parse_function_def(doc, attributes, true)?);
+                continue;
+            }
+            if !is_const && !is_static && !is_struct && !is_enum {
+                functions.push(self.parse_function_def(doc, attributes, false)?);
+                continue;
+            }
+
+            if let Some(attribute) = attributes.first() {
+                bail!(
+                    "{}: Attributes are only allowed before function definitions",
+                    attribute.span
+                );
+            }
+            let _ = self.eat_token();
+
+            if is_const {
+                constants.push(self.parse_constant_def(doc)?);
+            } else if is_struct {
+                structs.push(self.parse_struct_def(doc)?);
+            } else if is_enum {
+                enums.push(self.parse_enum_def(doc)?);
+            } else {
+                statics.push(self.parse_static_def(doc)?);
+            }
         }
 
-        Ok(ast::Root { functions })
+        Ok(ast::Root {
+            functions,
+            constants,
+            statics,
+            structs,
+            enums,
+        })
+    }
+}
+
+/// Converts the number of an integer literal pattern at `span`, which may be negated, into a value of type `int`
+fn int_pattern(value: i64, span: Span) -> Result<ConstValue> {
+    i32::try_from(value).map(ConstValue::Int).map_err(|_| {
+        anyhow!(
+            "{span}: Integer literal `{value}` is too large for `int`, the maximum value is {}",
+            i32::MAX
+        )
+    })
+}
+
+fn comparison_op(token: &Token) -> Option<ast::BinaryOp> {
+    Some(match token {
+        Token::DoubleEquals => ast::BinaryOp::Equals,
+        Token::NotEquals => ast::BinaryOp::NotEquals,
+        Token::LessThan => ast::BinaryOp::LessThan,
+        Token::LessThanOrEquals => ast::BinaryOp::LessThanOrEquals,
+        Token::GreaterThan => ast::BinaryOp::GreaterThan,
+        Token::GreaterThanOrEquals => ast::BinaryOp::GreaterThanOrEquals,
+        _ => return None,
+    })
+}
+
+fn additive_op(token: &Token) -> Option<ast::BinaryOp> {
+    match token {
+        Token::Plus => Some(ast::BinaryOp::Add),
+        Token::Minus => Some(ast::BinaryOp::Subtract),
+        _ => None,
+    }
+}
+
+fn multiplicative_op(token: &Token) -> Option<ast::BinaryOp> {
+    match token {
+        Token::Star => Some(ast::BinaryOp::Multiply),
+        Token::Slash => Some(ast::BinaryOp::Divide),
+        Token::Percent => Some(ast::BinaryOp::Remainder),
+        _ => None,
     }
 }
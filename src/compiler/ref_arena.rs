@@ -1,4 +1,5 @@
 use std::cell::{Ref, RefCell, RefMut};
+use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
 use std::rc::Rc;
 
@@ -30,6 +31,21 @@ impl<T> Clone for ArenaRef<T> {
     }
 }
 
+/// Two references are equal if they point to the same element of the same arena
+impl<T> PartialEq for ArenaRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.idx == other.idx && Rc::ptr_eq(&self.arena, &other.arena)
+    }
+}
+
+impl<T> Eq for ArenaRef<T> {}
+
+impl<T> Hash for ArenaRef<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.idx.hash(state);
+    }
+}
+
 impl<T> Arena<T> {
     pub fn new() -> Self {
         Self {
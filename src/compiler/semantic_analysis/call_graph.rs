@@ -0,0 +1,108 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::compiler::parser::ast::{ExprKind, Root};
+use crate::compiler::ref_arena::ArenaRef;
+use crate::compiler::symbol_table::Function;
+
+/// Which functions are called by which other functions
+#[derive(Debug)]
+pub struct CallGraph {
+    /// All functions in the order of their definition
+    functions: Vec<ArenaRef<Function>>,
+    /// The functions called by each function, in the order of their first call
+    callees: HashMap<ArenaRef<Function>, Vec<ArenaRef<Function>>>,
+}
+
+impl CallGraph {
+    pub fn build(root: &Root) -> Self {
+        let functions = root.functions.iter().map(|f| f.sym.clone()).collect();
+
+        let callees = root
+            .functions
+            .iter()
+            .map(|function| {
+                let mut callees = Vec::new();
+                function.compound.visit(&mut |expr| {
+                    if let ExprKind::FnCall(call) = &expr.kind {
+                        if !callees.contains(call.function()) {
+                            callees.push(call.function().clone());
+                        }
+                    }
+                });
+
+                (function.sym.clone(), callees)
+            })
+            .collect();
+
+        Self { functions, callees }
+    }
+
+    pub fn callees(&self, function: &ArenaRef<Function>) -> &[ArenaRef<Function>] {
+        self.callees
+            .get(function)
+            .map_or(&[], |callees| callees.as_slice())
+    }
+
+    /// Returns all functions that can be called from `start` through one or more calls
+    pub fn reachable_from(&self, start: &ArenaRef<Function>) -> HashSet<ArenaRef<Function>> {
+        let mut reachable = HashSet::new();
+        let mut unvisited = self.callees(start).to_vec();
+
+        while let Some(function) = unvisited.pop() {
+            if reachable.insert(function.clone()) {
+                unvisited.extend_from_slice(self.callees(&function));
+            }
+        }
+
+        reachable
+    }
+
+    /// Returns all groups of functions that call each other recursively, in the order of their definition.
+    /// A function that calls itself forms a group on its own.
+    pub fn recursive_components(&self) -> Vec<Vec<ArenaRef<Function>>> {
+        let reachable: HashMap<_, _> = self
+            .functions
+            .iter()
+            .map(|function| (function, self.reachable_from(function)))
+            .collect();
+
+        let mut components: Vec<Vec<ArenaRef<Function>>> = Vec::new();
+        for function in &self.functions {
+            let is_recursive = reachable[function].contains(function);
+            let is_grouped = components.iter().flatten().any(|f| f == function);
+            if !is_recursive || is_grouped {
+                continue;
+            }
+
+            let component = self
+                .functions
+                .iter()
+                .filter(|other| {
+                    reachable[function].contains(other) && reachable[other].contains(function)
+                })
+                .cloned()
+                .collect();
+            components.push(component);
+        }
+
+        components
+    }
+
+    /// Renders the call graph in the DOT format of Graphviz
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph callgraph {\n");
+
+        for function in &self.functions {
+            let name = &function.get().name;
+            let _ = writeln!(dot, "    \"{name}\";");
+
+            for callee in self.callees(function) {
+                let _ = writeln!(dot, "    \"{name}\" -> \"{}\";", callee.get().name);
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -0,0 +1,792 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+
+use anyhow::{anyhow, bail, Error, Result};
+
+use crate::compiler::parser::ast::{
+    BinaryOp, Builtin, CompoundExpr, Expr, ExprKind, FnCall, Literal, Match, MatchArm, Pattern,
+    PatternKind, Root, Type,
+};
+use crate::compiler::ref_arena::ArenaRef;
+use crate::compiler::span::Span;
+use crate::compiler::symbol_table::{ConstValue, Constant, Function, Variable};
+
+/// Maximum number of loop iterations during each evaluation at compile time, so that endless loops are detected
+const MAX_ITERATIONS: u32 = 1_000_000;
+/// Maximum number of nested calls of const functions during an evaluation at compile time, so that endless recursions
+/// are detected before the compiler runs out of stack
+const MAX_CALL_DEPTH: u32 = 64;
+
+/// Error of an evaluation that exceeded `MAX_ITERATIONS` or `MAX_CALL_DEPTH`. Constants and initial values of statics
+/// fail to compile with it, while calls of const functions in function bodies are made at runtime instead.
+#[derive(Debug)]
+struct LimitExceeded(String);
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// Evaluates all constants and initial values of statics and folds every expression in a function body whose value is known at compile time, so
+/// that codegen can emit its value directly. Overflows and divisions by zero in these expressions are reported as errors.
+/// Calls of const functions that exceed a limit of the evaluation are left to runtime, except in constants and statics.
+pub fn evaluate(root: &mut Root) -> Result<()> {
+    check_side_effects(root)?;
+
+    let mut evaluator = Evaluator {
+        functions: root
+            .functions
+            .iter()
+            .map(|function| (function.sym.clone(), &function.compound))
+            .collect(),
+        constants: root
+            .constants
+            .iter()
+            .map(|constant| (constant.sym.clone(), &constant.value))
+            .collect(),
+        ..Evaluator::default()
+    };
+
+    for constant in &root.constants {
+        evaluator.iterations = 0;
+        evaluator.eval_constant(&constant.sym, constant.span)?;
+    }
+
+    for static_def in &root.statics {
+        evaluator.iterations = 0;
+        let initial_value = evaluator.eval_expr(&static_def.value)?;
+        static_def.sym.get_mut().initial_value = Some(initial_value);
+    }
+
+    // Calls of const functions are evaluated up front, because the function bodies cannot be borrowed while
+    // expressions are folded
+    for function in &root.functions {
+        let mut result = Ok(());
+        function.compound.visit(&mut |expr| {
+            if result.is_ok() && matches!(expr.kind, ExprKind::FnCall(_)) && is_constant(expr) {
+                evaluator.iterations = 0;
+                result = match evaluator.eval_expr(expr) {
+                    Err(err) if !err.is::<LimitExceeded>() => Err(err),
+                    _ => Ok(()),
+                };
+            }
+        });
+        result?;
+    }
+
+    let mut folder = Evaluator {
+        function_values: evaluator.function_values,
+        runtime_calls: evaluator.runtime_calls,
+        ..Evaluator::default()
+    };
+    root.functions
+        .iter_mut()
+        .try_for_each(|function| folder.fold_compound(&mut function.compound))
+}
+
+/// Makes sure that constants, initial values of statics and const functions only call other const functions, neither
+/// print nor exit and do not access statics
+fn check_side_effects(root: &Root) -> Result<()> {
+    for constant in &root.constants {
+        let context = format!("constant `{}`", constant.sym.get().name);
+        check_side_effects_in(&context, |f| constant.value.visit(&mut |expr| f(expr)))?;
+    }
+
+    for static_def in &root.statics {
+        let context = format!("initial value of static `{}`", static_def.sym.get().name);
+        check_side_effects_in(&context, |f| static_def.value.visit(&mut |expr| f(expr)))?;
+    }
+
+    for function in &root.functions {
+        if !function.sym.get().is_const {
+            continue;
+        }
+
+        let context = format!("const function `{}`", function.sym.get().name);
+        check_side_effects_in(&context, |f| function.compound.visit(&mut |expr| f(expr)))?;
+    }
+
+    Ok(())
+}
+
+fn check_side_effects_in(context: &str, visit: impl FnOnce(&mut dyn FnMut(&Expr))) -> Result<()> {
+    let mut error: Option<Error> = None;
+
+    visit(&mut |expr| {
+        if error.is_some() {
+            return;
+        }
+
+        error = match &expr.kind {
+            ExprKind::BuiltinCall(builtin, _) if builtin.has_side_effects() => Some(anyhow!(
+                "{}: Cannot call `{}` in {context}, because it is evaluated at compile time",
+                expr.span,
+                builtin.name()
+            )),
+            ExprKind::Exit(_) => Some(anyhow!(
+                "{}: Cannot exit in {context}, because it is evaluated at compile time",
+                expr.span
+            )),
+            ExprKind::FnCall(call) if !call.function().get().is_const => Some(anyhow!(
+                "{}: Cannot call non-const function `{}` in {context}",
+                expr.span,
+                call.name
+            )),
+            ExprKind::Static(static_ref) => Some(anyhow!(
+                "{}: Cannot use static `{}` in {context}, because its value is not known at compile time",
+                expr.span,
+                static_ref.get().name
+            )),
+            ExprKind::Assign(assignment) => Some(anyhow!(
+                "{}: Cannot assign to static `{}` in {context}, because it is evaluated at compile time",
+                assignment.span,
+                assignment.name
+            )),
+            _ => None,
+        };
+    });
+
+    error.map_or(Ok(()), Err)
+}
+
+/// Whether the value of an expression is known at compile time
+fn is_constant(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Constant(_) | ExprKind::Literal(_) => true,
+        ExprKind::Binary(lhs, _, rhs) => is_constant(lhs) && is_constant(rhs),
+        ExprKind::Cast(value, _) | ExprKind::Field(value, _) | ExprKind::Len(value) => {
+            is_constant(value)
+        }
+        // Array, struct and tuple literals are not folded, because large repeat literals are generated more efficiently
+        // at runtime and the fields of a struct or tuple literal are folded individually
+        ExprKind::Index(array, index) => is_constant(array) && is_constant(index),
+        // Assertions have no value, so they are only evaluated inside of constants and const functions
+        ExprKind::BuiltinCall(builtin, args) => {
+            !builtin.has_side_effects()
+                && !matches!(expr.ty(), Type::Unit | Type::Never)
+                && args.iter().all(is_constant)
+        }
+        ExprKind::FnCall(call) => {
+            let function = call.function().get();
+            function.is_const
+                && function.return_ty != Type::Never
+                && call.args.iter().all(is_constant)
+        }
+        _ => false,
+    }
+}
+
+#[derive(Default)]
+struct Evaluator<'a> {
+    /// Bodies of all functions
+    functions: HashMap<ArenaRef<Function>, &'a CompoundExpr>,
+    /// Values of all constants
+    constants: HashMap<ArenaRef<Constant>, &'a Expr>,
+    /// Results of const functions that were already called by their arguments. Const functions have no side effects,
+    /// so every call with the same arguments returns the same value.
+    function_values: HashMap<(ArenaRef<Function>, Vec<ConstValue>), ConstValue>,
+    /// Calls of const functions that exceeded a limit of the evaluation, so they are made at runtime
+    runtime_calls: HashSet<(ArenaRef<Function>, Vec<ConstValue>)>,
+    /// Constants and calls that are currently evaluated, to detect cycles
+    evaluating_constants: HashSet<ArenaRef<Constant>>,
+    evaluating_calls: HashSet<(ArenaRef<Function>, Vec<ConstValue>)>,
+    /// Values of the local variables of the function that is currently evaluated
+    variables: HashMap<ArenaRef<Variable>, ConstValue>,
+    iterations: u32,
+}
+
+impl Evaluator<'_> {
+    /// Annotates all maximal constant expressions inside of the compound with their value
+    fn fold_compound(&mut self, compound: &mut CompoundExpr) -> Result<()> {
+        compound
+            .expressions
+            .iter_mut()
+            .try_for_each(|expr| self.fold_expr(expr))
+    }
+
+    fn fold_expr(&mut self, expr: &mut Expr) -> Result<()> {
+        if is_constant(expr) {
+            // Literals are emitted directly anyway
+            if matches!(expr.kind, ExprKind::Literal(_)) {
+                return Ok(());
+            }
+
+            // Values of enums are always built at runtime, so only the parts of such an expression are folded.
+            // The same goes for expressions with calls that are too expensive to evaluate at compile time.
+            match self.eval_expr(expr) {
+                Ok(value) if !value.contains_variant() => {
+                    expr.value = Some(value);
+                    return Ok(());
+                }
+                Err(err) if !err.is::<LimitExceeded>() => return Err(err),
+                _ => {}
+            }
+        }
+
+        match &mut expr.kind {
+            ExprKind::Assign(assignment) => self.fold_expr(&mut assignment.value),
+            ExprKind::Array(elements) | ExprKind::Tuple(elements) => elements
+                .iter_mut()
+                .try_for_each(|element| self.fold_expr(element)),
+            ExprKind::ArrayRepeat(element, _) => self.fold_expr(element),
+            ExprKind::Binary(lhs, _, rhs) | ExprKind::Index(lhs, rhs) => {
+                self.fold_expr(lhs)?;
+                self.fold_expr(rhs)
+            }
+            ExprKind::BuiltinCall(_, args) | ExprKind::FnCall(FnCall { args, .. }) => {
+                args.iter_mut().try_for_each(|arg| self.fold_expr(arg))
+            }
+            ExprKind::Cast(value, _)
+            | ExprKind::Field(value, _)
+            | ExprKind::Len(value)
+            | ExprKind::Return(value) => self.fold_expr(value),
+            ExprKind::Struct(literal) => literal
+                .fields
+                .iter_mut()
+                .try_for_each(|field_init| self.fold_expr(&mut field_init.value)),
+            ExprKind::Variant(literal) => literal
+                .args
+                .iter_mut()
+                .try_for_each(|arg| self.fold_expr(arg)),
+            ExprKind::Match(match_expr) => {
+                self.fold_expr(&mut match_expr.value)?;
+                match_expr
+                    .arms
+                    .iter_mut()
+                    .try_for_each(|arm| self.fold_expr(&mut arm.body))
+            }
+            ExprKind::Slice(array, start, end) => {
+                self.fold_expr(array)?;
+                start
+                    .iter_mut()
+                    .chain(end)
+                    .try_for_each(|bound| self.fold_expr(bound))
+            }
+            ExprKind::For(for_loop) => {
+                self.fold_expr(&mut for_loop.iterable)?;
+                self.fold_compound(&mut for_loop.body)
+            }
+            ExprKind::Compound(compound) | ExprKind::Loop(compound) => self.fold_compound(compound),
+            ExprKind::If(condition, then_branch, else_branch) => {
+                self.fold_expr(condition)?;
+                self.fold_compound(then_branch)?;
+                else_branch
+                    .iter_mut()
+                    .try_for_each(|else_branch| self.fold_compound(else_branch))
+            }
+            ExprKind::Let(let_expr) => self.fold_expr(&mut let_expr.value),
+            ExprKind::While(condition, body) => {
+                self.fold_expr(condition)?;
+                self.fold_compound(body)
+            }
+            ExprKind::Constant(_)
+            | ExprKind::Exit(_)
+            | ExprKind::Literal(_)
+            | ExprKind::Static(_)
+            | ExprKind::Variable(_) => Ok(()),
+        }
+    }
+
+    fn eval_constant(&mut self, constant: &ArenaRef<Constant>, span: Span) -> Result<ConstValue> {
+        if let Some(value) = &constant.get().value {
+            return Ok(value.clone());
+        }
+
+        if !self.evaluating_constants.insert(constant.clone()) {
+            bail!(
+                "{span}: Constant `{}` depends on its own value",
+                constant.get().name
+            );
+        }
+
+        let value_expr = *self
+            .constants
+            .get(constant)
+            .expect("constants to be evaluated before expressions are folded");
+        let value = self.eval_expr(value_expr)?;
+
+        self.evaluating_constants.remove(constant);
+        constant.get_mut().value = Some(value.clone());
+
+        Ok(value)
+    }
+
+    fn eval_call(
+        &mut self,
+        function: &ArenaRef<Function>,
+        args: Vec<ConstValue>,
+        span: Span,
+    ) -> Result<ConstValue> {
+        let call = (function.clone(), args);
+        if let Some(value) = self.function_values.get(&call) {
+            return Ok(value.clone());
+        }
+        if self.runtime_calls.contains(&call) {
+            bail!(LimitExceeded(format!(
+                "{span}: Evaluation of this call at compile time already exceeded a limit"
+            )));
+        }
+
+        // A call with the same arguments as a surrounding one would repeat the same steps, so it can never end
+        if self.evaluating_calls.contains(&call) {
+            bail!(
+                "{span}: Const function `{}` calls itself with the same arguments, so it cannot be evaluated at compile time",
+                function.get().name
+            );
+        }
+        if self.evaluating_calls.len() as u32 >= MAX_CALL_DEPTH {
+            bail!(LimitExceeded(format!(
+                "{span}: Evaluation at compile time exceeded the limit of {MAX_CALL_DEPTH} nested calls"
+            )));
+        }
+
+        let body = *self
+            .functions
+            .get(function)
+            .expect("const functions to be evaluated before expressions are folded");
+
+        // The called function only sees its parameters, which are bound to the arguments
+        let params: HashMap<_, _> = function
+            .get()
+            .params
+            .iter()
+            .map(|param| param.variable.clone())
+            .zip(call.1.iter().cloned())
+            .collect();
+        let caller_variables = std::mem::replace(&mut self.variables, params);
+        self.evaluating_calls.insert(call.clone());
+        let returned = self.eval_compound(body);
+        self.evaluating_calls.remove(&call);
+        self.variables = caller_variables;
+
+        // The call would exceed the limit again, so it is not evaluated a second time
+        let returned = match returned {
+            Err(err) if err.is::<LimitExceeded>() => {
+                self.runtime_calls.insert(call);
+                return Err(err);
+            }
+            returned => returned?,
+        };
+
+        // Only functions returning `()` can reach the end of their body
+        let value = returned.unwrap_or(ConstValue::Unit);
+        self.function_values.insert(call, value.clone());
+
+        Ok(value)
+    }
+
+    /// Evaluates all statements of a compound. Returns the returned value if a `return` is reached.
+    fn eval_compound(&mut self, compound: &CompoundExpr) -> Result<Option<ConstValue>> {
+        for expr in &compound.expressions {
+            if let Some(returned) = self.eval_statement(expr)? {
+                return Ok(Some(returned));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Evaluates an expression directly inside of a compound. Returns the returned value if a `return` is reached.
+    fn eval_statement(&mut self, expr: &Expr) -> Result<Option<ConstValue>> {
+        match &expr.kind {
+            ExprKind::Return(value) => Ok(Some(self.eval_expr(value)?)),
+            ExprKind::Compound(compound) => self.eval_compound(compound),
+            ExprKind::If(condition, then_branch, else_branch) => {
+                if self.eval_condition(condition)? {
+                    self.eval_compound(then_branch)
+                } else if let Some(else_branch) = else_branch {
+                    self.eval_compound(else_branch)
+                } else {
+                    Ok(None)
+                }
+            }
+            ExprKind::Let(let_expr) => {
+                let value = self.eval_expr(&let_expr.value)?;
+                let matched = match_pattern(&let_expr.pattern, &value, &mut self.variables);
+                assert!(
+                    matched,
+                    "type checking to only allow irrefutable patterns in `let`"
+                );
+                Ok(None)
+            }
+            ExprKind::Loop(body) => loop {
+                self.count_iteration(expr.span)?;
+                if let Some(returned) = self.eval_compound(body)? {
+                    return Ok(Some(returned));
+                }
+            },
+            ExprKind::While(condition, body) => {
+                while self.eval_condition(condition)? {
+                    self.count_iteration(expr.span)?;
+                    if let Some(returned) = self.eval_compound(body)? {
+                        return Ok(Some(returned));
+                    }
+                }
+                Ok(None)
+            }
+            ExprKind::For(for_loop) => {
+                for element in self.eval_elements(&for_loop.iterable)? {
+                    self.count_iteration(expr.span)?;
+                    self.variables.insert(for_loop.variable.clone(), element);
+                    if let Some(returned) = self.eval_compound(&for_loop.body)? {
+                        return Ok(Some(returned));
+                    }
+                }
+                Ok(None)
+            }
+            ExprKind::Match(match_expr) => {
+                let arm = self.select_arm(match_expr)?;
+                match &arm.body.kind {
+                    ExprKind::Compound(body) => self.eval_compound(body),
+                    _ => {
+                        let _ = self.eval_expr(&arm.body)?;
+                        Ok(None)
+                    }
+                }
+            }
+            _ => {
+                let _ = self.eval_expr(expr)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Evaluates the value of a `match` and returns the first arm whose pattern matches it, after binding the variables
+    /// of that pattern
+    fn select_arm<'m>(&mut self, match_expr: &'m Match) -> Result<&'m MatchArm> {
+        let value = self.eval_expr(&match_expr.value)?;
+
+        let arm = match_expr
+            .arms
+            .iter()
+            .find(|arm| match_pattern(&arm.pattern, &value, &mut self.variables))
+            .expect("type checking to only allow exhaustive matches");
+        Ok(arm)
+    }
+
+    fn eval_condition(&mut self, condition: &Expr) -> Result<bool> {
+        match self.eval_expr(condition)? {
+            ConstValue::Bool(value) => Ok(value),
+            other => {
+                unreachable!("type checking to only allow conditions of type `bool`, got {other}")
+            }
+        }
+    }
+
+    /// Evaluates an array or slice, both are represented by an array of their elements at compile time
+    fn eval_elements(&mut self, array: &Expr) -> Result<Vec<ConstValue>> {
+        match self.eval_expr(array)? {
+            ConstValue::Array(_, elements) => Ok(elements),
+            other => unreachable!("type checking to only allow arrays and slices, got {other}"),
+        }
+    }
+
+    fn eval_int(&mut self, expr: &Expr) -> Result<i32> {
+        match self.eval_expr(expr)? {
+            ConstValue::Int(value) => Ok(value),
+            other => unreachable!("type checking to only allow values of type `int`, got {other}"),
+        }
+    }
+
+    fn count_iteration(&mut self, span: Span) -> Result<()> {
+        self.iterations += 1;
+
+        if self.iterations > MAX_ITERATIONS {
+            bail!(LimitExceeded(format!(
+                "{span}: Evaluation at compile time exceeded the limit of {MAX_ITERATIONS} loop iterations"
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn eval_expr(&mut self, expr: &Expr) -> Result<ConstValue> {
+        let span = expr.span;
+
+        Ok(match &expr.kind {
+            ExprKind::Array(elements) => {
+                let values = elements
+                    .iter()
+                    .map(|element| self.eval_expr(element))
+                    .collect::<Result<Vec<_>>>()?;
+                ConstValue::Array(element_ty(expr), values)
+            }
+            ExprKind::ArrayRepeat(element, len) => {
+                if *len > MAX_ITERATIONS {
+                    bail!("{span}: Array of length {len} is too large to be evaluated at compile time, the limit is {MAX_ITERATIONS}");
+                }
+
+                let value = self.eval_expr(element)?;
+                ConstValue::Array(element_ty(expr), vec![value; *len as usize])
+            }
+            ExprKind::Index(array, index) => {
+                let elements = self.eval_elements(array)?;
+                let index = self.eval_int(index)?;
+
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|index| elements.get(index).cloned())
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "{span}: Index out of bounds while evaluating at compile time: the length is {} but the index is {index}",
+                            elements.len()
+                        )
+                    })?
+            }
+            ExprKind::Slice(array, start, end) => {
+                let elements = self.eval_elements(array)?;
+                let len = elements.len() as i32;
+                let start = start.as_ref().map_or(Ok(0), |start| self.eval_int(start))?;
+                let end = end.as_ref().map_or(Ok(len), |end| self.eval_int(end))?;
+
+                if start < 0 || start > end || end > len {
+                    bail!("{span}: Range out of bounds while evaluating at compile time: the length is {len} but the range is {start}..{end}");
+                }
+
+                ConstValue::Array(element_ty(expr), elements[start as usize..end as usize].to_vec())
+            }
+            ExprKind::Len(array) => ConstValue::Int(self.eval_elements(array)?.len() as i32),
+            ExprKind::Struct(literal) => {
+                // Field values are evaluated in source order, but stored in the order of their declaration
+                let mut fields = vec![None; literal.fields.len()];
+                for field_init in &literal.fields {
+                    let value = self.eval_expr(&field_init.value)?;
+                    fields[field_init.field.index() as usize] =
+                        Some((field_init.field.name.clone(), value));
+                }
+
+                let fields = fields
+                    .into_iter()
+                    .map(|field| field.expect("type checking to require every field to be initialized"))
+                    .collect();
+                ConstValue::Struct(literal.name.clone(), fields)
+            }
+            ExprKind::Tuple(elements) => ConstValue::Tuple(
+                elements
+                    .iter()
+                    .map(|element| self.eval_expr(element))
+                    .collect::<Result<_>>()?,
+            ),
+            ExprKind::Variant(literal) => {
+                let fields = literal
+                    .args
+                    .iter()
+                    .map(|arg| self.eval_expr(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                ConstValue::Variant {
+                    enum_name: literal.variant.enum_name.clone(),
+                    name: literal.variant.name.clone(),
+                    index: literal.variant.index(),
+                    fields,
+                }
+            }
+            ExprKind::Match(match_expr) => {
+                let arm = self.select_arm(match_expr)?;
+                self.eval_expr(&arm.body)?
+            }
+            ExprKind::Field(value, field_ref) => match self.eval_expr(value)? {
+                ConstValue::Struct(_, mut fields) => {
+                    fields.swap_remove(field_ref.index() as usize).1
+                }
+                ConstValue::Tuple(mut elements) => elements.swap_remove(field_ref.index() as usize),
+                other => unreachable!(
+                    "type checking to only allow fields of structs and tuples, got {other}"
+                ),
+            },
+            ExprKind::Binary(lhs, op, rhs) => {
+                let lhs = self.eval_expr(lhs)?;
+                let rhs = self.eval_expr(rhs)?;
+                eval_binary(lhs, *op, rhs, span)?
+            }
+            ExprKind::Cast(value, target_ty) => match (self.eval_expr(value)?, target_ty) {
+                (ConstValue::Int(value), Type::Int) => ConstValue::Int(value),
+                (ConstValue::Char(value), Type::Int) => ConstValue::Int(value as i32),
+                (ConstValue::Bool(value), Type::Int) => ConstValue::Int(value.into()),
+                (ConstValue::Char(value), Type::Char) => ConstValue::Char(value),
+                (ConstValue::Int(value), Type::Char) => u32::try_from(value)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map(ConstValue::Char)
+                    .ok_or_else(|| anyhow!("{span}: `{value}` is not a valid `char`"))?,
+                (value, target_ty) => {
+                    unreachable!("type checking to reject the cast of `{value}` to `{target_ty}`")
+                }
+            },
+            ExprKind::BuiltinCall(builtin, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval_expr(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                eval_builtin(*builtin, &args, span)?
+            }
+            ExprKind::Constant(constant) => self.eval_constant(constant, span)?,
+            ExprKind::FnCall(call) => {
+                let args = call
+                    .args
+                    .iter()
+                    .map(|arg| self.eval_expr(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                self.eval_call(call.function(), args, span)?
+            }
+            ExprKind::Literal(literal) => match literal {
+                Literal::Int(value) => i32::try_from(*value).map(ConstValue::Int).map_err(|_| {
+                    anyhow!(
+                        "{span}: Integer literal `{value}` is too large for `int`, the maximum value is {}",
+                        i32::MAX
+                    )
+                })?,
+                Literal::Str(value) => ConstValue::Str(value.clone()),
+                Literal::Char(value) => ConstValue::Char(*value),
+                Literal::Bool(value) => ConstValue::Bool(*value),
+            },
+            ExprKind::Variable(variable_ref) => self
+                .variables
+                .get(variable_ref.variable())
+                .cloned()
+                .expect("variable to be declared before it is used"),
+            ExprKind::Assign(_) | ExprKind::Exit(_) | ExprKind::Static(_) => {
+                unreachable!("side effects and statics to be rejected before evaluation")
+            }
+            ExprKind::Compound(_)
+            | ExprKind::If(..)
+            | ExprKind::Let(_)
+            | ExprKind::Loop(_)
+            | ExprKind::Return(_)
+            | ExprKind::For(_)
+            | ExprKind::While(..) => {
+                unreachable!("statements to be evaluated by `eval_statement`")
+            }
+        })
+    }
+}
+
+/// Whether `value` matches `pattern`. The variables bound by the pattern are added to `variables`.
+fn match_pattern(
+    pattern: &Pattern,
+    value: &ConstValue,
+    variables: &mut HashMap<ArenaRef<Variable>, ConstValue>,
+) -> bool {
+    match (&pattern.kind, value) {
+        (PatternKind::Wildcard, _) => true,
+        (PatternKind::Binding(variable), _) => {
+            variables.insert(variable.clone(), value.clone());
+            true
+        }
+        (PatternKind::Literal(literal), _) => literal == value,
+        (PatternKind::Variant(variant, patterns), ConstValue::Variant { index, fields, .. }) => {
+            variant.index() == *index
+                && patterns
+                    .iter()
+                    .zip(fields)
+                    .all(|(pattern, field)| match_pattern(pattern, field, variables))
+        }
+        (PatternKind::Variant(variant, _), other) => {
+            unreachable!("type checking to only allow pattern `{variant}` for enums, got {other}")
+        }
+        (PatternKind::Tuple(patterns), ConstValue::Tuple(elements)) => patterns
+            .iter()
+            .zip(elements)
+            .all(|(pattern, element)| match_pattern(pattern, element, variables)),
+        (PatternKind::Tuple(_), other) => {
+            unreachable!("type checking to only allow tuple patterns for tuples, got {other}")
+        }
+    }
+}
+
+/// Returns the element type of an array or slice
+fn element_ty(array: &Expr) -> Type {
+    let ty = array.ty();
+    match ty.element_ty() {
+        Some(element_ty) => element_ty.clone(),
+        None => unreachable!("arrays and slices to be of an array or slice type, got `{ty}`"),
+    }
+}
+
+fn eval_builtin(builtin: Builtin, args: &[ConstValue], span: Span) -> Result<ConstValue> {
+    match (builtin, args) {
+        (Builtin::Panic, [ConstValue::Str(message)]) => {
+            bail!("{span}: Panicked at compile time: {message}")
+        }
+        (Builtin::Assert, [ConstValue::Bool(condition)]) => {
+            if !condition {
+                bail!("{span}: Assertion failed at compile time");
+            }
+            return Ok(ConstValue::Unit);
+        }
+        (Builtin::AssertEq, [lhs, rhs]) => {
+            if lhs != rhs {
+                bail!(
+                    "{span}: Assertion failed at compile time, left is `{lhs}`, right is `{rhs}`"
+                );
+            }
+            return Ok(ConstValue::Unit);
+        }
+        _ => {}
+    }
+
+    let [ConstValue::Int(a), ConstValue::Int(b)] = args else {
+        unreachable!(
+            "type checking to only allow two ints as arguments of `{}`",
+            builtin.name()
+        );
+    };
+
+    Ok(match builtin {
+        Builtin::WrappingAdd => ConstValue::Int(a.wrapping_add(*b)),
+        Builtin::CheckedAdd => eval_arithmetic(*a, BinaryOp::Add, *b, span)?,
+        Builtin::SaturatingAdd => ConstValue::Int(a.saturating_add(*b)),
+        Builtin::Print | Builtin::Println => {
+            unreachable!("side effects to be rejected before evaluation")
+        }
+        Builtin::Panic | Builtin::Assert | Builtin::AssertEq => {
+            unreachable!("`{}` to be evaluated separately", builtin.name())
+        }
+    })
+}
+
+fn eval_binary(lhs: ConstValue, op: BinaryOp, rhs: ConstValue, span: Span) -> Result<ConstValue> {
+    let ordering = match (&lhs, &rhs) {
+        (ConstValue::Int(a), ConstValue::Int(b)) if !op.is_comparison() => {
+            return eval_arithmetic(*a, op, *b, span);
+        }
+        (ConstValue::Int(a), ConstValue::Int(b)) => a.cmp(b),
+        (ConstValue::Char(a), ConstValue::Char(b)) => a.cmp(b),
+        (ConstValue::Bool(a), ConstValue::Bool(b)) => a.cmp(b),
+        _ => unreachable!("type checking to reject `{lhs} {op} {rhs}`"),
+    };
+
+    let result = match op {
+        BinaryOp::Equals => ordering == Ordering::Equal,
+        BinaryOp::NotEquals => ordering != Ordering::Equal,
+        BinaryOp::LessThan => ordering == Ordering::Less,
+        BinaryOp::LessThanOrEquals => ordering != Ordering::Greater,
+        BinaryOp::GreaterThan => ordering == Ordering::Greater,
+        BinaryOp::GreaterThanOrEquals => ordering != Ordering::Less,
+        _ => unreachable!("arithmetic to be evaluated by `eval_arithmetic`"),
+    };
+
+    Ok(ConstValue::Bool(result))
+}
+
+fn eval_arithmetic(a: i32, op: BinaryOp, b: i32, span: Span) -> Result<ConstValue> {
+    if matches!(op, BinaryOp::Divide | BinaryOp::Remainder) && b == 0 {
+        bail!("{span}: Division by zero while evaluating `{a} {op} {b}` at compile time");
+    }
+
+    let result = match op {
+        BinaryOp::Add => a.checked_add(b),
+        BinaryOp::Subtract => a.checked_sub(b),
+        BinaryOp::Multiply => a.checked_mul(b),
+        BinaryOp::Divide => a.checked_div(b),
+        BinaryOp::Remainder => a.checked_rem(b),
+        _ => unreachable!("comparisons to be evaluated by `eval_binary`"),
+    };
+
+    result.map(ConstValue::Int).ok_or_else(|| {
+        anyhow!("{span}: Overflow while evaluating `{a} {op} {b}` at compile time, the result does not fit into `int`")
+    })
+}
@@ -0,0 +1,307 @@
+use std::collections::HashSet;
+use std::iter;
+
+use anyhow::{bail, Result};
+
+use crate::compiler::diagnostic::Warning;
+use crate::compiler::parser::ast::{
+    Builtin, CompoundExpr, Expr, ExprKind, FunctionDefinition, Root, Type,
+};
+use crate::compiler::ref_arena::ArenaRef;
+use crate::compiler::semantic_analysis::lints::Lint;
+use crate::compiler::symbol_table::Function;
+
+/// What happens after an expression was evaluated
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Flow {
+    /// Execution may continue with the next expression
+    Continues,
+    /// Execution never continues with the next expression, but the function may return
+    Returns,
+    /// The program never continues, e.g. because it exits
+    Diverges,
+}
+
+impl Flow {
+    /// Flow of evaluating `self` and then, if execution continues, `next`
+    fn then(self, next: impl FnOnce() -> Flow) -> Flow {
+        match self {
+            Flow::Continues => next(),
+            other => other,
+        }
+    }
+
+    /// Flow of taking either the branch with flow `self` or the one with flow `other`
+    fn join(self, other: Flow) -> Flow {
+        match (self, other) {
+            (Flow::Continues, _) | (_, Flow::Continues) => Flow::Continues,
+            (Flow::Returns, _) | (_, Flow::Returns) => Flow::Returns,
+            (Flow::Diverges, Flow::Diverges) => Flow::Diverges,
+        }
+    }
+}
+
+/// Control flow information of a whole program
+pub struct ControlFlow {
+    /// Functions that never return to their caller
+    diverging_functions: HashSet<ArenaRef<Function>>,
+}
+
+impl ControlFlow {
+    pub fn analyse(root: &Root) -> Self {
+        // Start by assuming that all functions diverge and remove functions that are found to return, until nothing changes.
+        // Functions that only call each other in an endless recursion are correctly considered diverging this way.
+        let mut control_flow = Self {
+            diverging_functions: root.functions.iter().map(|f| f.sym.clone()).collect(),
+        };
+
+        loop {
+            let diverging_functions: HashSet<_> = root
+                .functions
+                .iter()
+                .filter(|f| control_flow.body_diverges(&f.compound))
+                .map(|f| f.sym.clone())
+                .collect();
+
+            if diverging_functions.len() == control_flow.diverging_functions.len() {
+                return control_flow;
+            }
+            control_flow.diverging_functions = diverging_functions;
+        }
+    }
+
+    pub fn diverges(&self, function: &ArenaRef<Function>) -> bool {
+        self.diverging_functions.contains(function)
+    }
+
+    /// Whether a function with this body never returns. The body may still end in a diverging expression like a
+    /// recursive call after an earlier `return`, like the base case of a recursion.
+    fn body_diverges(&self, body: &CompoundExpr) -> bool {
+        self.compound_flow(body) == Flow::Diverges && !self.compound_may_return(body)
+    }
+
+    pub fn compound_flow(&self, compound: &CompoundExpr) -> Flow {
+        self.sequence_flow(&compound.expressions)
+    }
+
+    fn sequence_flow<'a>(&self, exprs: impl IntoIterator<Item = &'a Expr>) -> Flow {
+        exprs.into_iter().fold(Flow::Continues, |flow, expr| {
+            flow.then(|| self.expr_flow(expr))
+        })
+    }
+
+    pub fn expr_flow(&self, expr: &Expr) -> Flow {
+        match &expr.kind {
+            ExprKind::Array(elements) | ExprKind::Tuple(elements) => self.sequence_flow(elements),
+            ExprKind::ArrayRepeat(element, _) => self.expr_flow(element),
+            ExprKind::Assign(assignment) => self.expr_flow(&assignment.value),
+            ExprKind::Binary(lhs, _, rhs) | ExprKind::Index(lhs, rhs) => {
+                self.expr_flow(lhs).then(|| self.expr_flow(rhs))
+            }
+            ExprKind::BuiltinCall(Builtin::Panic, args) => {
+                self.sequence_flow(args).then(|| Flow::Diverges)
+            }
+            ExprKind::BuiltinCall(_, args) => self.sequence_flow(args),
+            ExprKind::Cast(value, _) | ExprKind::Field(value, _) | ExprKind::Len(value) => {
+                self.expr_flow(value)
+            }
+            ExprKind::Slice(array, start, end) => self.sequence_flow(
+                iter::once(&**array)
+                    .chain(start.as_deref())
+                    .chain(end.as_deref()),
+            ),
+            ExprKind::Struct(literal) => {
+                self.sequence_flow(literal.fields.iter().map(|field_init| &field_init.value))
+            }
+            ExprKind::Variant(literal) => self.sequence_flow(&literal.args),
+            ExprKind::Compound(compound) => self.compound_flow(compound),
+            ExprKind::Exit(_) => Flow::Diverges,
+            ExprKind::FnCall(call) => self.sequence_flow(&call.args).then(|| {
+                if self.diverges(call.function()) {
+                    Flow::Diverges
+                } else {
+                    Flow::Continues
+                }
+            }),
+            ExprKind::If(condition, then_branch, else_branch) => {
+                self.expr_flow(condition).then(|| {
+                    let else_flow = else_branch.as_ref().map_or(Flow::Continues, |else_branch| {
+                        self.compound_flow(else_branch)
+                    });
+                    self.compound_flow(then_branch).join(else_flow)
+                })
+            }
+            ExprKind::Let(let_expr) => self.expr_flow(&let_expr.value),
+            // Matches are exhaustive, so one of the arms is always taken
+            ExprKind::Match(match_expr) => self.expr_flow(&match_expr.value).then(|| {
+                match_expr
+                    .arms
+                    .iter()
+                    .map(|arm| self.expr_flow(&arm.body))
+                    .reduce(Flow::join)
+                    .unwrap_or(Flow::Diverges)
+            }),
+            ExprKind::Constant(_)
+            | ExprKind::Literal(_)
+            | ExprKind::Static(_)
+            | ExprKind::Variable(_) => Flow::Continues,
+            // A loop can only be left by returning
+            ExprKind::Loop(body) => {
+                if self.compound_may_return(body) {
+                    Flow::Returns
+                } else {
+                    Flow::Diverges
+                }
+            }
+            ExprKind::Return(value) => self.expr_flow(value).then(|| Flow::Returns),
+            // The condition may be false right away, so the body is not considered
+            ExprKind::While(condition, _) => self.expr_flow(condition),
+            // There may be no elements, so the body is not considered either
+            ExprKind::For(for_loop) => self.expr_flow(&for_loop.iterable),
+        }
+    }
+
+    /// Whether a `return` inside of the compound may be reached
+    fn compound_may_return(&self, compound: &CompoundExpr) -> bool {
+        for expr in &compound.expressions {
+            if self.expr_may_return(expr) {
+                return true;
+            }
+            if self.expr_flow(expr) != Flow::Continues {
+                return false;
+            }
+        }
+
+        false
+    }
+
+    fn expr_may_return(&self, expr: &Expr) -> bool {
+        match &expr.kind {
+            ExprKind::Return(value) => self.expr_flow(value) == Flow::Continues,
+            ExprKind::Compound(compound) | ExprKind::Loop(compound) => {
+                self.compound_may_return(compound)
+            }
+            ExprKind::If(condition, then_branch, else_branch) => {
+                self.expr_flow(condition) == Flow::Continues
+                    && (self.compound_may_return(then_branch)
+                        || else_branch
+                            .as_deref()
+                            .is_some_and(|else_branch| self.compound_may_return(else_branch)))
+            }
+            ExprKind::While(condition, body) => {
+                self.expr_flow(condition) == Flow::Continues && self.compound_may_return(body)
+            }
+            ExprKind::For(for_loop) => {
+                self.expr_flow(&for_loop.iterable) == Flow::Continues
+                    && self.compound_may_return(&for_loop.body)
+            }
+            ExprKind::Match(match_expr) => {
+                self.expr_flow(&match_expr.value) == Flow::Continues
+                    && match_expr
+                        .arms
+                        .iter()
+                        .any(|arm| self.expr_may_return(&arm.body))
+            }
+            // `return` can only be used as a statement, so it cannot occur inside of any other expression
+            _ => false,
+        }
+    }
+
+    /// Checks that all paths through a function end in a value of its return type being returned
+    pub fn check_returns(&self, root: &Root) -> Result<()> {
+        root.functions
+            .iter()
+            .try_for_each(|function| self.check_function_returns(function))
+    }
+
+    fn check_function_returns(&self, function: &FunctionDefinition) -> Result<()> {
+        let fn_sym = function.sym.get();
+        let flow = self.compound_flow(&function.compound);
+
+        match (&fn_sym.return_ty, flow) {
+            (Type::Never, Flow::Continues | Flow::Returns) => bail!(
+                "{}: Function `{}` is declared to never return (`-> !`), but it may return",
+                function.span,
+                fn_sym.name
+            ),
+            (Type::Unit | Type::Never, _) | (_, Flow::Returns | Flow::Diverges) => Ok(()),
+            (return_ty, Flow::Continues) => bail!(
+                "{}: Function `{}` declared `-> {return_ty}` may fall off the end",
+                function.compound.closing_brace_span(),
+                fn_sym.name
+            ),
+        }
+    }
+
+    /// Reports all expressions that can never be executed, because they follow an expression that does not continue
+    pub fn find_unreachable_code(&self, root: &Root) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+
+        for function in &root.functions {
+            self.find_unreachable_in_compound(&function.compound, &mut warnings);
+        }
+
+        warnings
+    }
+
+    fn find_unreachable_in_compound(&self, compound: &CompoundExpr, warnings: &mut Vec<Warning>) {
+        for (i, expr) in compound.expressions.iter().enumerate() {
+            match &expr.kind {
+                ExprKind::Compound(inner) | ExprKind::Loop(inner) | ExprKind::While(_, inner) => {
+                    self.find_unreachable_in_compound(inner, warnings);
+                }
+                ExprKind::For(for_loop) => {
+                    self.find_unreachable_in_compound(&for_loop.body, warnings);
+                }
+                ExprKind::If(_, then_branch, else_branch) => {
+                    self.find_unreachable_in_compound(then_branch, warnings);
+                    if let Some(else_branch) = else_branch {
+                        self.find_unreachable_in_compound(else_branch, warnings);
+                    }
+                }
+                ExprKind::Match(match_expr) => {
+                    for arm in &match_expr.arms {
+                        if let ExprKind::Compound(body) = &arm.body.kind {
+                            self.find_unreachable_in_compound(body, warnings);
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            if self.expr_flow(expr) == Flow::Continues {
+                continue;
+            }
+
+            let unreachable = &compound.expressions[i + 1..];
+            if let (Some(first), Some(last)) = (unreachable.first(), unreachable.last()) {
+                warnings.push(Warning::new(
+                    Lint::UnreachableCode,
+                    first.span.to(last.span),
+                    format!("Unreachable code after {}", describe_end_of_flow(expr)),
+                ));
+            }
+
+            break;
+        }
+    }
+}
+
+/// Describes an expression that does not continue for use in diagnostics
+fn describe_end_of_flow(expr: &Expr) -> String {
+    let span = expr.span;
+
+    match &expr.kind {
+        ExprKind::Return(_) => format!("the `return` at {span}"),
+        ExprKind::Exit(_) => format!("the `exit` at {span}"),
+        ExprKind::BuiltinCall(Builtin::Panic, _) => format!("the `panic` at {span}"),
+        ExprKind::FnCall(call) => {
+            format!("the call of `{}` at {span}, which never returns", call.name)
+        }
+        ExprKind::Compound(_) => format!("the block at {span}, which never completes"),
+        ExprKind::If(..) => format!("the `if` at {span}, whose branches never complete"),
+        ExprKind::Loop(_) => format!("the `loop` at {span}, which is never left"),
+        ExprKind::Match(_) => format!("the `match` at {span}, whose arms never complete"),
+        _ => format!("the expression at {span}, which never completes"),
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use crate::compiler::parser::ast::{Builtin, Root};
+use crate::compiler::span::Span;
+use crate::compiler::symbol_table::Sym;
+
+/// Declares the names of all functions, constants, statics, structs and enums in the symbol table before any of them is
+/// used, so they can be referenced independently of the order of their definitions.
+/// Fails if a name, the parameter of a function, the field of a struct or the variant of an enum is defined multiple
+/// times, the error points at both definitions.
+pub fn collect(root: &Root, sym: &Sym) -> Result<()> {
+    let mut functions = Declarations::default();
+    for function in &root.functions {
+        let name = &function.sym.get().name;
+
+        if Builtin::try_from_name(name).is_some() {
+            bail!(
+                "{}: Cannot define function `{name}`, because a builtin function with the same name exists",
+                function.span
+            );
+        }
+
+        functions.declare(name, function.span)?;
+
+        let mut params = Declarations::default();
+        for param in &function.sym.get().params {
+            params.declare(&param.variable.get().name, param.span)?;
+        }
+
+        sym.declare_function(&function.sym);
+    }
+
+    // Constants and statics are both referenced like variables, so they share their names
+    let mut values = Declarations::default();
+    for constant in &root.constants {
+        values.declare(&constant.sym.get().name, constant.span)?;
+        sym.declare_constant(&constant.sym);
+    }
+    for static_def in &root.statics {
+        values.declare(&static_def.sym.get().name, static_def.span)?;
+        sym.declare_static(&static_def.sym);
+    }
+
+    // Structs and enums are both referenced as types, so they share their names
+    let mut types = Declarations::default();
+    for struct_def in &root.structs {
+        let struct_sym = struct_def.sym.get();
+        types.declare(&struct_sym.name, struct_def.span)?;
+
+        let mut fields = Declarations::default();
+        for field in &struct_sym.fields {
+            fields.declare(&field.name, field.span)?;
+        }
+
+        sym.declare_struct(&struct_def.sym);
+    }
+    for enum_def in &root.enums {
+        let enum_sym = enum_def.sym.get();
+        types.declare(&enum_sym.name, enum_def.span)?;
+
+        let mut variants = Declarations::default();
+        for variant in &enum_sym.variants {
+            variants.declare(&variant.name, variant.span)?;
+        }
+
+        sym.declare_enum(&enum_def.sym);
+    }
+
+    Ok(())
+}
+
+/// Spans of the names that are already declared
+#[derive(Default)]
+struct Declarations {
+    spans: HashMap<String, Span>,
+}
+
+impl Declarations {
+    fn declare(&mut self, name: &str, span: Span) -> Result<()> {
+        if let Some(first_span) = self.spans.get(name) {
+            bail!("{span}: Redefinition of `{name}`, which is already defined at {first_span}");
+        }
+
+        self.spans.insert(name.to_owned(), span);
+        Ok(())
+    }
+}
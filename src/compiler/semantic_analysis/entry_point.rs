@@ -0,0 +1,29 @@
+use anyhow::{bail, Result};
+
+use crate::compiler::parser::ast::{Root, Type};
+
+/// Checks that an executable has a `main` function without parameters, which is called when the program starts.
+/// Redefinitions of `main` are already rejected by `declarations::collect`, which runs before this check.
+pub fn check(root: &Root) -> Result<()> {
+    let Some(main) = root.main_function() else {
+        bail!("Missing function `main`, which is required as the entry point of an executable (use `--lib` to compile a library)");
+    };
+
+    if let Some(param) = main.sym.get().params.first() {
+        bail!(
+            "{}: Function `main` cannot have parameters, because it is called without arguments when the program starts",
+            param.span
+        );
+    }
+
+    // A `main` that never returns is fine as well, because `!` can be used in place of any type
+    let return_ty = main.sym.get().return_ty.clone();
+    if !matches!(return_ty, Type::Unit | Type::Int | Type::Never) {
+        bail!(
+            "{}: Function `main` must return `()`, `int` or `!`, found `{return_ty}`",
+            main.span
+        );
+    }
+
+    Ok(())
+}
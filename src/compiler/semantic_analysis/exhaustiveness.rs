@@ -0,0 +1,245 @@
+use crate::compiler::parser::ast::{self, Match, Pattern, PatternKind, Type};
+use crate::compiler::span::Span;
+use crate::compiler::symbol_table::{ConstValue, Sym};
+
+/// Returns a pattern for values of type `ty` that are not matched by any of `patterns`, like the arms of a `match`, or
+/// `None` if the patterns cover every value. Must only be called after the patterns were type checked.
+pub fn find_missing_pattern<'a>(
+    patterns: impl IntoIterator<Item = &'a Pattern>,
+    ty: &Type,
+    sym: &Sym,
+) -> Option<String> {
+    let matrix: Vec<_> = patterns
+        .into_iter()
+        .map(|pattern| vec![Pat::from_pattern(pattern)])
+        .collect();
+
+    let witness = find_useful(&matrix, &[Pat::Wild], std::slice::from_ref(ty), sym)?;
+    Some(witness[0].describe(ty, sym))
+}
+
+/// Returns the spans of the patterns of `match_expr` that can only match values that are already matched by an earlier
+/// arm
+pub fn find_unreachable_arms(match_expr: &Match, sym: &Sym) -> Vec<Span> {
+    // Any pattern is accepted for a value that diverges, none of them is ever reached
+    let ty = match_expr.value.ty();
+    if ty == Type::Never {
+        return Vec::new();
+    }
+
+    let mut matrix = Vec::new();
+    let mut unreachable = Vec::new();
+
+    for arm in &match_expr.arms {
+        let row = vec![Pat::from_pattern(&arm.pattern)];
+        if find_useful(&matrix, &row, std::slice::from_ref(&ty), sym).is_none() {
+            unreachable.push(arm.pattern.span);
+        }
+        matrix.push(row);
+    }
+
+    unreachable
+}
+
+/// A constructor of values, whose fields are matched by nested patterns
+#[derive(Debug, Clone, PartialEq)]
+enum Ctor {
+    /// Variant of an enum by its position, the enum is given by the type of the matched value
+    Variant(u32),
+    /// A literal without fields
+    Literal(ConstValue),
+    /// The only constructor of a tuple, whose fields are its elements
+    Tuple,
+}
+
+/// A pattern reduced to what matters for the values it matches
+#[derive(Debug, Clone)]
+enum Pat {
+    /// Matches any value, like a wildcard or a binding
+    Wild,
+    Ctor(Ctor, Vec<Pat>),
+}
+
+impl Pat {
+    fn from_pattern(pattern: &Pattern) -> Self {
+        match &pattern.kind {
+            PatternKind::Wildcard | PatternKind::Binding(_) => Pat::Wild,
+            PatternKind::Literal(value) => Pat::Ctor(Ctor::Literal(value.clone()), Vec::new()),
+            PatternKind::Variant(variant, fields) => Pat::Ctor(
+                Ctor::Variant(variant.index()),
+                fields.iter().map(Pat::from_pattern).collect(),
+            ),
+            PatternKind::Tuple(elements) => Pat::Ctor(
+                Ctor::Tuple,
+                elements.iter().map(Pat::from_pattern).collect(),
+            ),
+        }
+    }
+
+    /// Formats this pattern for values of type `ty` like it would be written in the source
+    fn describe(&self, ty: &Type, sym: &Sym) -> String {
+        match self {
+            Pat::Wild => "_".to_string(),
+            Pat::Ctor(Ctor::Literal(value), _) => value.to_string(),
+            Pat::Ctor(Ctor::Variant(index), fields) => {
+                let Type::Enum(enum_name) = ty else {
+                    unreachable!("variant patterns to match values of an enum");
+                };
+
+                let enum_ref = sym.get_enum(enum_name);
+                let variant = &enum_ref.get().variants[*index as usize];
+                if fields.is_empty() {
+                    return format!("{enum_name}::{}", variant.name);
+                }
+
+                let fields: Vec<_> = fields
+                    .iter()
+                    .zip(&variant.fields)
+                    .map(|(field, field_ty)| field.describe(field_ty, sym))
+                    .collect();
+                format!("{enum_name}::{}({})", variant.name, fields.join(", "))
+            }
+            Pat::Ctor(Ctor::Tuple, elements) => {
+                let Type::Tuple(element_tys) = ty else {
+                    unreachable!("tuple patterns to match values of a tuple");
+                };
+
+                let elements: Vec<_> = elements
+                    .iter()
+                    .zip(element_tys)
+                    .map(|(element, element_ty)| element.describe(element_ty, sym))
+                    .collect();
+                format!("({})", ast::format_tuple(&elements))
+            }
+        }
+    }
+}
+
+/// Returns all constructors of values of type `ty` together with the types of their fields, or `None` if there are too
+/// many to list them
+fn all_ctors(ty: &Type, sym: &Sym) -> Option<Vec<(Ctor, Vec<Type>)>> {
+    match ty {
+        Type::Bool => Some(vec![
+            (Ctor::Literal(ConstValue::Bool(false)), Vec::new()),
+            (Ctor::Literal(ConstValue::Bool(true)), Vec::new()),
+        ]),
+        Type::Enum(name) => {
+            let enum_ref = sym.get_enum(name);
+            let enum_sym = enum_ref.get();
+            let ctors = enum_sym
+                .variants
+                .iter()
+                .enumerate()
+                .map(|(index, variant)| (Ctor::Variant(index as u32), variant.fields.clone()))
+                .collect();
+            Some(ctors)
+        }
+        Type::Tuple(element_tys) => Some(vec![(Ctor::Tuple, element_tys.clone())]),
+        _ => None,
+    }
+}
+
+/// Returns the types of the fields of `ctor` for values of type `ty`
+fn field_tys(ctor: &Ctor, ty: &Type, sym: &Sym) -> Vec<Type> {
+    match (ctor, ty) {
+        (Ctor::Variant(index), Type::Enum(name)) => {
+            let enum_ref = sym.get_enum(name);
+            let fields = enum_ref.get().variants[*index as usize].fields.clone();
+            fields
+        }
+        (Ctor::Tuple, Type::Tuple(element_tys)) => element_tys.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// Keeps the rows of `matrix` that match values built by `ctor` and replaces their first pattern by the patterns for
+/// the `arity` fields of `ctor`
+fn specialize(matrix: &[Vec<Pat>], ctor: &Ctor, arity: usize) -> Vec<Vec<Pat>> {
+    matrix
+        .iter()
+        .filter_map(|row| {
+            let (first, rest) = row
+                .split_first()
+                .expect("rows to have a pattern per column");
+            let mut specialized = match first {
+                Pat::Ctor(other, fields) if other == ctor => fields.clone(),
+                Pat::Ctor(..) => return None,
+                Pat::Wild => vec![Pat::Wild; arity],
+            };
+            specialized.extend_from_slice(rest);
+            Some(specialized)
+        })
+        .collect()
+}
+
+/// Builds the pattern for `ctor` from the first `arity` patterns of `witness`, which is the reverse of `specialize`
+fn wrap(ctor: Ctor, mut witness: Vec<Pat>, arity: usize) -> Vec<Pat> {
+    let rest = witness.split_off(arity);
+    let mut wrapped = vec![Pat::Ctor(ctor, witness)];
+    wrapped.extend(rest);
+    wrapped
+}
+
+/// Checks whether `row` matches a value that is not matched by any row of `matrix`, where column `i` matches values of
+/// type `tys[i]`. Returns the patterns for such a value if there is one.
+///
+/// This is the usefulness algorithm from "Warnings for pattern matching" by Luc Maranget.
+fn find_useful(matrix: &[Vec<Pat>], row: &[Pat], tys: &[Type], sym: &Sym) -> Option<Vec<Pat>> {
+    let Some((first, rest)) = row.split_first() else {
+        // Without any columns, a row matches everything and is only useful if no earlier row exists
+        return matrix.is_empty().then(Vec::new);
+    };
+    let (ty, rest_tys) = tys.split_first().expect("every column to have a type");
+
+    let specialize_row = |ctor: &Ctor, fields: Vec<Pat>| {
+        let field_tys = field_tys(ctor, ty, sym);
+        let arity = field_tys.len();
+        let specialized = specialize(matrix, ctor, arity);
+
+        let mut specialized_row = fields;
+        specialized_row.extend_from_slice(rest);
+        let specialized_tys = [field_tys, rest_tys.to_vec()].concat();
+
+        find_useful(&specialized, &specialized_row, &specialized_tys, sym)
+            .map(|witness| wrap(ctor.clone(), witness, arity))
+    };
+
+    if let Pat::Ctor(ctor, fields) = first {
+        return specialize_row(ctor, fields.clone());
+    }
+
+    let used: Vec<_> = matrix
+        .iter()
+        .filter_map(|row| match &row[0] {
+            Pat::Ctor(ctor, _) => Some(ctor),
+            Pat::Wild => None,
+        })
+        .collect();
+    let ctors = all_ctors(ty, sym);
+
+    match ctors {
+        // Every constructor is matched by some row, so a value can only be missing inside of one of them
+        Some(ctors) if ctors.iter().all(|(ctor, _)| used.contains(&ctor)) => ctors
+            .iter()
+            .find_map(|(ctor, field_tys)| specialize_row(ctor, vec![Pat::Wild; field_tys.len()])),
+        // Some constructor is not matched by any row, so only the rows starting with a wildcard are relevant
+        ctors => {
+            let default: Vec<_> = matrix
+                .iter()
+                .filter(|row| matches!(row[0], Pat::Wild))
+                .map(|row| row[1..].to_vec())
+                .collect();
+
+            let witness = find_useful(&default, rest, rest_tys, sym)?;
+            let missing = ctors
+                .into_iter()
+                .flatten()
+                .find(|(ctor, _)| !used.contains(&ctor))
+                .map_or(Pat::Wild, |(ctor, field_tys)| {
+                    Pat::Ctor(ctor, vec![Pat::Wild; field_tys.len()])
+                });
+
+            Some([vec![missing], witness].concat())
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use anyhow::{anyhow, bail, Result};
+use itertools::Itertools;
+
+use crate::compiler::diagnostic::Warning;
+use crate::compiler::parser::ast::{Attribute, Root};
+use crate::compiler::semantic_analysis::call_graph::CallGraph;
+use crate::compiler::semantic_analysis::control_flow::ControlFlow;
+use crate::compiler::symbol_table::Sym;
+
+mod naming;
+mod recursion;
+mod redundant_blocks;
+mod unreachable_patterns;
+mod unused_functions;
+mod variables;
+
+/// A kind of warning, which can be allowed, warned about or denied
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnreachableCode,
+    UnusedFunctions,
+    UnusedVariables,
+    Shadowing,
+    RedundantBlocks,
+    NonSnakeCase,
+    NonCamelCaseTypes,
+    NonUpperCaseGlobals,
+    UnconditionalRecursion,
+    UnreachablePatterns,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 10] = [
+        Lint::UnreachableCode,
+        Lint::UnusedFunctions,
+        Lint::UnusedVariables,
+        Lint::Shadowing,
+        Lint::RedundantBlocks,
+        Lint::NonSnakeCase,
+        Lint::NonCamelCaseTypes,
+        Lint::NonUpperCaseGlobals,
+        Lint::UnconditionalRecursion,
+        Lint::UnreachablePatterns,
+    ];
+
+    pub fn try_from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+
+    /// The ID of this lint, which is used to configure its level
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::UnusedFunctions => "unused_functions",
+            Lint::UnusedVariables => "unused_variables",
+            Lint::Shadowing => "shadowing",
+            Lint::RedundantBlocks => "redundant_blocks",
+            Lint::NonSnakeCase => "non_snake_case",
+            Lint::NonCamelCaseTypes => "non_camel_case_types",
+            Lint::NonUpperCaseGlobals => "non_upper_case_globals",
+            Lint::UnconditionalRecursion => "unconditional_recursion",
+            Lint::UnreachablePatterns => "unreachable_patterns",
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LintLevel {
+    /// The lint is not reported
+    Allow,
+    /// The lint is reported as a warning
+    Warn,
+    /// The lint is reported as an error and compilation fails
+    Deny,
+}
+
+impl LintLevel {
+    /// Parses the name of a level, as used in attributes and command line flags
+    pub fn try_from_name(name: &str) -> Option<Self> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// Levels of all lints for a whole program. Lints that were not configured are warned about.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintConfig {
+    pub fn set_level(&mut self, lint: Lint, level: LintLevel) {
+        self.levels.insert(lint, level);
+    }
+
+    pub fn level(&self, lint: Lint) -> LintLevel {
+        self.levels.get(&lint).copied().unwrap_or(LintLevel::Warn)
+    }
+}
+
+/// Runs all lints and returns the warnings that are not allowed.
+/// Attributes of a function override the levels of `config` for all warnings inside of that function.
+/// Fails if a denied lint is reported.
+pub fn check(
+    root: &Root,
+    sym: &Sym,
+    call_graph: &CallGraph,
+    control_flow: &ControlFlow,
+    config: &LintConfig,
+) -> Result<Vec<Warning>> {
+    let function_levels = root
+        .functions
+        .iter()
+        .map(|function| {
+            let span = function.span.to(function.compound.span);
+            Ok((span, attribute_levels(&function.attributes)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let warnings = [
+        control_flow.find_unreachable_code(root),
+        unused_functions::check(root, call_graph),
+        variables::check(root),
+        redundant_blocks::check(root),
+        naming::check(root),
+        recursion::check(root, call_graph, control_flow),
+        unreachable_patterns::check(root, sym),
+    ]
+    .into_iter()
+    .flatten()
+    .sorted_by_key(|warning| warning.span.start);
+
+    let mut reported = Vec::new();
+    let mut denied = Vec::new();
+
+    for warning in warnings {
+        // Later attributes take precedence over earlier ones
+        let attribute_level = function_levels
+            .iter()
+            .find(|(span, _)| span.contains(warning.span))
+            .and_then(|(_, levels)| levels.iter().rev().find(|(lint, _)| *lint == warning.lint));
+
+        match attribute_level.map_or(config.level(warning.lint), |(_, level)| *level) {
+            LintLevel::Allow => {}
+            LintLevel::Warn => reported.push(warning),
+            LintLevel::Deny => denied.push(warning),
+        }
+    }
+
+    if !denied.is_empty() {
+        bail!(
+            "{}",
+            denied
+                .iter()
+                .map(|warning| format!(
+                    "{}: {} (lint `{}` is denied)",
+                    warning.span, warning.message, warning.lint
+                ))
+                .join("\n")
+        );
+    }
+
+    Ok(reported)
+}
+
+/// Parses lint attributes like `#[allow(unused_functions, shadowing)]`
+fn attribute_levels(attributes: &[Attribute]) -> Result<Vec<(Lint, LintLevel)>> {
+    let mut levels = Vec::new();
+
+    for attribute in attributes {
+        let Some(level) = LintLevel::try_from_name(&attribute.name) else {
+            bail!("{}: Unknown attribute `{}`", attribute.span, attribute.name);
+        };
+
+        if attribute.args.is_empty() {
+            bail!(
+                "{}: Attribute `{}` expects at least one lint",
+                attribute.span,
+                attribute.name
+            );
+        }
+
+        for arg in &attribute.args {
+            let lint = Lint::try_from_name(arg)
+                .ok_or_else(|| anyhow!("{}: Unknown lint `{arg}`", attribute.span))?;
+            levels.push((lint, level));
+        }
+    }
+
+    Ok(levels)
+}
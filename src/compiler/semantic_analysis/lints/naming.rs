@@ -0,0 +1,164 @@
+use crate::compiler::diagnostic::Warning;
+use crate::compiler::parser::ast::Root;
+use crate::compiler::semantic_analysis::lints::Lint;
+use crate::compiler::span::Span;
+
+/// Reports functions, parameters and variables whose names are not in snake case, structs, enums and variants whose
+/// names are not in upper camel case and constants and statics whose names are not in upper case
+pub fn check(root: &Root) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    for function in &root.functions {
+        check_snake_case(
+            "Function",
+            &function.sym.get().name,
+            function.span,
+            &mut warnings,
+        );
+
+        for param in &function.sym.get().params {
+            check_snake_case(
+                "Parameter",
+                &param.variable.get().name,
+                param.span,
+                &mut warnings,
+            );
+        }
+        function.compound.visit(&mut |expr| {
+            for (variable, span) in expr.kind.declared_variables() {
+                check_snake_case("Variable", &variable.get().name, span, &mut warnings);
+            }
+        });
+    }
+
+    for struct_def in &root.structs {
+        check_camel_case(
+            "Struct",
+            &struct_def.sym.get().name,
+            struct_def.span,
+            &mut warnings,
+        );
+    }
+
+    for enum_def in &root.enums {
+        let enum_sym = enum_def.sym.get();
+        check_camel_case("Enum", &enum_sym.name, enum_def.span, &mut warnings);
+
+        for variant in &enum_sym.variants {
+            check_camel_case("Variant", &variant.name, variant.span, &mut warnings);
+        }
+    }
+
+    for constant in &root.constants {
+        check_upper_case(
+            "Constant",
+            &constant.sym.get().name,
+            constant.span,
+            &mut warnings,
+        );
+    }
+
+    for static_def in &root.statics {
+        check_upper_case(
+            "Static",
+            &static_def.sym.get().name,
+            static_def.span,
+            &mut warnings,
+        );
+    }
+
+    warnings
+}
+
+fn check_snake_case(kind: &str, name: &str, span: Span, warnings: &mut Vec<Warning>) {
+    if name.chars().any(char::is_uppercase) {
+        warnings.push(Warning::new(
+            Lint::NonSnakeCase,
+            span,
+            format!(
+                "{kind} `{name}` should have a snake case name such as `{}`",
+                to_snake_case(name)
+            ),
+        ));
+    }
+}
+
+fn check_camel_case(kind: &str, name: &str, span: Span, warnings: &mut Vec<Warning>) {
+    if name.starts_with(char::is_lowercase) || name.contains('_') {
+        warnings.push(Warning::new(
+            Lint::NonCamelCaseTypes,
+            span,
+            format!(
+                "{kind} `{name}` should have an upper camel case name such as `{}`",
+                to_camel_case(name)
+            ),
+        ));
+    }
+}
+
+fn check_upper_case(kind: &str, name: &str, span: Span, warnings: &mut Vec<Warning>) {
+    if name.chars().any(char::is_lowercase) {
+        warnings.push(Warning::new(
+            Lint::NonUpperCaseGlobals,
+            span,
+            format!(
+                "{kind} `{name}` should have an upper case name such as `{}`",
+                to_snake_case(name).to_uppercase()
+            ),
+        ));
+    }
+}
+
+/// Converts a name in camel or pascal case to snake case, e.g. `printAll` to `print_all`
+fn to_snake_case(name: &str) -> String {
+    let mut snake_case = String::new();
+    let mut previous: Option<char> = None;
+
+    for c in name.chars() {
+        if c.is_uppercase() {
+            if previous.is_some_and(|previous| previous.is_lowercase() || previous.is_ascii_digit())
+            {
+                snake_case.push('_');
+            }
+            snake_case.extend(c.to_lowercase());
+        } else {
+            snake_case.push(c);
+        }
+
+        previous = Some(c);
+    }
+
+    snake_case
+}
+
+/// Converts a name in snake case to upper camel case, e.g. `text_box` to `TextBox`
+fn to_camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::semantic_analysis::lints::naming::{to_camel_case, to_snake_case};
+
+    #[test]
+    pub fn snake_case_conversion() {
+        assert_eq!(to_snake_case("printAll"), "print_all");
+        assert_eq!(to_snake_case("PrintAll"), "print_all");
+        assert_eq!(to_snake_case("value2Int"), "value2_int");
+        assert_eq!(to_snake_case("already_snake"), "already_snake");
+    }
+
+    #[test]
+    pub fn camel_case_conversion() {
+        assert_eq!(to_camel_case("text_box"), "TextBox");
+        assert_eq!(to_camel_case("point"), "Point");
+        assert_eq!(to_camel_case("Already_Camel"), "AlreadyCamel");
+    }
+}
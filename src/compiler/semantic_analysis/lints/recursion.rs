@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+use std::iter;
+
+use itertools::Itertools;
+
+use crate::compiler::diagnostic::Warning;
+use crate::compiler::parser::ast::{Builtin, CompoundExpr, Expr, ExprKind, Root};
+use crate::compiler::ref_arena::ArenaRef;
+use crate::compiler::semantic_analysis::call_graph::CallGraph;
+use crate::compiler::semantic_analysis::control_flow::ControlFlow;
+use crate::compiler::semantic_analysis::lints::Lint;
+use crate::compiler::symbol_table::Function;
+
+/// The paths through an expression that do not make a recursive call. If there are none, every path recurses.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Recursion {
+    /// Execution may continue with the next expression without a recursive call
+    may_continue: bool,
+    /// Execution may leave the function without a recursive call, by returning, exiting or looping forever
+    may_leave: bool,
+}
+
+impl Recursion {
+    const CONTINUES: Recursion = Recursion {
+        may_continue: true,
+        may_leave: false,
+    };
+    const LEAVES: Recursion = Recursion {
+        may_continue: false,
+        may_leave: true,
+    };
+    const RECURSES: Recursion = Recursion {
+        may_continue: false,
+        may_leave: false,
+    };
+
+    fn then(self, next: impl FnOnce() -> Recursion) -> Recursion {
+        if !self.may_continue {
+            return self;
+        }
+
+        let next = next();
+        Recursion {
+            may_continue: next.may_continue,
+            may_leave: self.may_leave || next.may_leave,
+        }
+    }
+
+    fn join(self, other: Recursion) -> Recursion {
+        Recursion {
+            may_continue: self.may_continue || other.may_continue,
+            may_leave: self.may_leave || other.may_leave,
+        }
+    }
+}
+
+/// Reports groups of recursive functions without a base case. Every call of one of them leads to an endless recursion.
+pub fn check(root: &Root, call_graph: &CallGraph, control_flow: &ControlFlow) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    for component in call_graph.recursive_components() {
+        let checker = RecursionChecker {
+            component: component.iter().cloned().collect(),
+            control_flow,
+        };
+
+        let definitions = root
+            .functions
+            .iter()
+            .filter(|function| checker.component.contains(&function.sym))
+            .collect_vec();
+
+        let has_base_case = definitions
+            .iter()
+            .any(|function| checker.compound_recursion(&function.compound) != Recursion::RECURSES);
+        if has_base_case {
+            continue;
+        }
+
+        let names = component
+            .iter()
+            .map(|function| format!("`{}`", function.get().name))
+            .collect_vec();
+        let message = match names.as_slice() {
+            [name] => format!("Function {name} recurses endlessly, because it always calls itself"),
+            [names @ .., last] => format!(
+                "Functions {} and {last} recurse endlessly, because they always call each other",
+                names.join(", ")
+            ),
+            [] => unreachable!("recursive components to contain at least one function"),
+        };
+
+        warnings.push(Warning::new(
+            Lint::UnconditionalRecursion,
+            definitions[0].span,
+            message,
+        ));
+    }
+
+    warnings
+}
+
+struct RecursionChecker<'a> {
+    /// Functions calling each other recursively
+    component: HashSet<ArenaRef<Function>>,
+    control_flow: &'a ControlFlow,
+}
+
+impl RecursionChecker<'_> {
+    fn compound_recursion(&self, compound: &CompoundExpr) -> Recursion {
+        self.sequence_recursion(&compound.expressions)
+    }
+
+    fn sequence_recursion<'a>(&self, exprs: impl IntoIterator<Item = &'a Expr>) -> Recursion {
+        exprs
+            .into_iter()
+            .fold(Recursion::CONTINUES, |recursion, expr| {
+                recursion.then(|| self.expr_recursion(expr))
+            })
+    }
+
+    fn expr_recursion(&self, expr: &Expr) -> Recursion {
+        match &expr.kind {
+            ExprKind::Array(elements) | ExprKind::Tuple(elements) => {
+                self.sequence_recursion(elements)
+            }
+            ExprKind::ArrayRepeat(element, _) => self.expr_recursion(element),
+            ExprKind::Assign(assignment) => self.expr_recursion(&assignment.value),
+            ExprKind::Binary(lhs, _, rhs) | ExprKind::Index(lhs, rhs) => {
+                self.expr_recursion(lhs).then(|| self.expr_recursion(rhs))
+            }
+            ExprKind::BuiltinCall(Builtin::Panic, args) => {
+                self.sequence_recursion(args).then(|| Recursion::LEAVES)
+            }
+            ExprKind::BuiltinCall(_, args) => self.sequence_recursion(args),
+            ExprKind::Cast(value, _) | ExprKind::Field(value, _) | ExprKind::Len(value) => {
+                self.expr_recursion(value)
+            }
+            ExprKind::Slice(array, start, end) => self.sequence_recursion(
+                iter::once(&**array)
+                    .chain(start.as_deref())
+                    .chain(end.as_deref()),
+            ),
+            ExprKind::Struct(literal) => {
+                self.sequence_recursion(literal.fields.iter().map(|field_init| &field_init.value))
+            }
+            ExprKind::Variant(literal) => self.sequence_recursion(&literal.args),
+            ExprKind::Compound(compound) => self.compound_recursion(compound),
+            ExprKind::Exit(_) => Recursion::LEAVES,
+            ExprKind::FnCall(call) => self.sequence_recursion(&call.args).then(|| {
+                if self.component.contains(call.function()) {
+                    Recursion::RECURSES
+                } else if self.control_flow.diverges(call.function()) {
+                    Recursion::LEAVES
+                } else {
+                    Recursion::CONTINUES
+                }
+            }),
+            ExprKind::If(condition, then_branch, else_branch) => {
+                self.expr_recursion(condition).then(|| {
+                    let else_recursion = else_branch
+                        .as_ref()
+                        .map_or(Recursion::CONTINUES, |else_branch| {
+                            self.compound_recursion(else_branch)
+                        });
+                    self.compound_recursion(then_branch).join(else_recursion)
+                })
+            }
+            ExprKind::Let(let_expr) => self.expr_recursion(&let_expr.value),
+            ExprKind::Match(match_expr) => self.expr_recursion(&match_expr.value).then(|| {
+                match_expr
+                    .arms
+                    .iter()
+                    .map(|arm| self.expr_recursion(&arm.body))
+                    .reduce(Recursion::join)
+                    .unwrap_or(Recursion::LEAVES)
+            }),
+            ExprKind::Constant(_)
+            | ExprKind::Literal(_)
+            | ExprKind::Static(_)
+            | ExprKind::Variable(_) => Recursion::CONTINUES,
+            // A loop without a recursive call loops forever, which is not an endless recursion
+            ExprKind::Loop(body) => match self.compound_recursion(body) {
+                Recursion::RECURSES => Recursion::RECURSES,
+                _ => Recursion::LEAVES,
+            },
+            ExprKind::Return(value) => self.expr_recursion(value).then(|| Recursion::LEAVES),
+            // The condition may be false right away, so the body is not considered
+            ExprKind::While(condition, _) => self.expr_recursion(condition),
+            ExprKind::For(for_loop) => self.expr_recursion(&for_loop.iterable),
+        }
+    }
+}
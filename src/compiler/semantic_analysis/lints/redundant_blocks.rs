@@ -0,0 +1,32 @@
+use crate::compiler::diagnostic::Warning;
+use crate::compiler::parser::ast::{ExprKind, Root};
+use crate::compiler::semantic_analysis::lints::Lint;
+
+/// Reports blocks nested inside of another block that do not declare any variables.
+/// Their contents could be moved into the surrounding block without changing the program.
+pub fn check(root: &Root) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    for function in &root.functions {
+        function.compound.visit(&mut |expr| {
+            let ExprKind::Compound(inner) = &expr.kind else {
+                return;
+            };
+
+            let declares_variables = inner
+                .expressions
+                .iter()
+                .any(|expr| matches!(expr.kind, ExprKind::Let(_)));
+
+            if !declares_variables {
+                warnings.push(Warning::new(
+                    Lint::RedundantBlocks,
+                    expr.span,
+                    "Redundant block, its contents can be moved into the surrounding block",
+                ));
+            }
+        });
+    }
+
+    warnings
+}
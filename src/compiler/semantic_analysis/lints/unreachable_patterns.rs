@@ -0,0 +1,29 @@
+use crate::compiler::diagnostic::Warning;
+use crate::compiler::parser::ast::{ExprKind, Root};
+use crate::compiler::semantic_analysis::exhaustiveness;
+use crate::compiler::semantic_analysis::lints::Lint;
+use crate::compiler::symbol_table::Sym;
+
+/// Reports arms of a `match` that are never taken, because every value matching their pattern is already matched by an
+/// earlier arm
+pub fn check(root: &Root, sym: &Sym) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    for function in &root.functions {
+        function.compound.visit(&mut |expr| {
+            let ExprKind::Match(match_expr) = &expr.kind else {
+                return;
+            };
+
+            for span in exhaustiveness::find_unreachable_arms(match_expr, sym) {
+                warnings.push(Warning::new(
+                    Lint::UnreachablePatterns,
+                    span,
+                    "Unreachable pattern, all values it matches are matched by earlier arms",
+                ));
+            }
+        });
+    }
+
+    warnings
+}
@@ -0,0 +1,34 @@
+use crate::compiler::diagnostic::Warning;
+use crate::compiler::parser::ast::{ExprKind, Root};
+use crate::compiler::semantic_analysis::call_graph::CallGraph;
+use crate::compiler::semantic_analysis::lints::Lint;
+
+/// Reports functions that are never called from `main` or a constant, neither directly nor through other functions.
+/// Programs without a `main` function are not checked.
+pub fn check(root: &Root, call_graph: &CallGraph) -> Vec<Warning> {
+    let Some(main) = root.main_function() else {
+        return Vec::new();
+    };
+    let mut used = call_graph.reachable_from(&main.sym);
+
+    for constant in &root.constants {
+        constant.value.visit(&mut |expr| {
+            if let ExprKind::FnCall(call) = &expr.kind {
+                used.insert(call.function().clone());
+                used.extend(call_graph.reachable_from(call.function()));
+            }
+        });
+    }
+
+    root.functions
+        .iter()
+        .filter(|function| function.sym != main.sym && !used.contains(&function.sym))
+        .map(|function| {
+            Warning::new(
+                Lint::UnusedFunctions,
+                function.span,
+                format!("Function `{}` is never used", function.sym.get().name),
+            )
+        })
+        .collect()
+}
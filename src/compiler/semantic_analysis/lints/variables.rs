@@ -0,0 +1,135 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::compiler::diagnostic::Warning;
+use crate::compiler::parser::ast::{CompoundExpr, ExprKind, Root};
+use crate::compiler::semantic_analysis::lints::Lint;
+use crate::compiler::span::Span;
+
+/// Reports variables and parameters that are never used and variables that shadow another variable or a parameter
+pub fn check(root: &Root) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    for function in &root.functions {
+        let fn_sym = function.sym.get();
+        let mut used = HashSet::new();
+        function.compound.visit(&mut |expr| {
+            if let ExprKind::Variable(variable_ref) = &expr.kind {
+                used.insert(variable_ref.variable().clone());
+            }
+        });
+
+        // Variables starting with an underscore are intentionally unused
+        for param in &fn_sym.params {
+            let name = &param.variable.get().name;
+            if !used.contains(&param.variable) && !name.starts_with('_') {
+                warnings.push(Warning::new(
+                    Lint::UnusedVariables,
+                    param.span,
+                    format!("Parameter `{name}` is never used"),
+                ));
+            }
+        }
+        function.compound.visit(&mut |expr| {
+            for (variable, span) in expr.kind.declared_variables() {
+                let name = &variable.get().name;
+                if !used.contains(variable) && !name.starts_with('_') {
+                    warnings.push(Warning::new(
+                        Lint::UnusedVariables,
+                        span,
+                        format!("Variable `{name}` is never used"),
+                    ));
+                }
+            }
+        });
+
+        // The parameters are declared in a scope around the body
+        let params = fn_sym
+            .params
+            .iter()
+            .map(|param| (param.variable.get().name.clone(), param.span))
+            .collect();
+        find_shadowing(&function.compound, &mut vec![params], &mut warnings);
+    }
+
+    warnings
+}
+
+/// `scopes` contains the spans of the variables declared in every compound surrounding `compound` by their name
+fn find_shadowing(
+    compound: &CompoundExpr,
+    scopes: &mut Vec<HashMap<String, Span>>,
+    warnings: &mut Vec<Warning>,
+) {
+    scopes.push(HashMap::new());
+
+    // Only statements can contain declarations or compounds
+    for expr in &compound.expressions {
+        match &expr.kind {
+            ExprKind::Let(let_expr) => {
+                for (variable, span) in let_expr.pattern.bindings() {
+                    let name = variable.get().name.clone();
+                    check_shadowing(&name, span, scopes, warnings);
+
+                    scopes
+                        .last_mut()
+                        .expect("scope of the current compound to exist")
+                        .insert(name, span);
+                }
+            }
+            // The loop variable is declared in a scope around the body
+            ExprKind::For(for_loop) => {
+                let name = for_loop.variable.get().name.clone();
+                check_shadowing(&name, for_loop.span, scopes, warnings);
+
+                scopes.push(HashMap::from([(name, for_loop.span)]));
+                find_shadowing(&for_loop.body, scopes, warnings);
+                scopes.pop();
+            }
+            ExprKind::Compound(inner) | ExprKind::Loop(inner) | ExprKind::While(_, inner) => {
+                find_shadowing(inner, scopes, warnings);
+            }
+            ExprKind::If(_, then_branch, else_branch) => {
+                find_shadowing(then_branch, scopes, warnings);
+                if let Some(else_branch) = else_branch {
+                    find_shadowing(else_branch, scopes, warnings);
+                }
+            }
+            // The variables bound by a pattern are declared in a scope around the body of its arm
+            ExprKind::Match(match_expr) => {
+                for arm in &match_expr.arms {
+                    let mut arm_scope = HashMap::new();
+                    for (variable, span) in arm.pattern.bindings() {
+                        let name = variable.get().name.clone();
+                        check_shadowing(&name, span, scopes, warnings);
+                        arm_scope.insert(name, span);
+                    }
+
+                    if let ExprKind::Compound(body) = &arm.body.kind {
+                        scopes.push(arm_scope);
+                        find_shadowing(body, scopes, warnings);
+                        scopes.pop();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    scopes.pop();
+}
+
+/// Reports the variable `name` declared at `span` if it shadows a variable in one of the surrounding `scopes`
+fn check_shadowing(
+    name: &str,
+    span: Span,
+    scopes: &[HashMap<String, Span>],
+    warnings: &mut Vec<Warning>,
+) {
+    if let Some(shadowed) = scopes.iter().rev().find_map(|scope| scope.get(name)) {
+        warnings.push(Warning::new(
+            Lint::Shadowing,
+            span,
+            format!("Variable `{name}` shadows the variable declared at {shadowed}"),
+        ));
+    }
+}
@@ -1,13 +1,930 @@
 use anyhow::Result;
 
+use crate::compiler::diagnostic::Warning;
+use crate::compiler::options::Options;
 use crate::compiler::parser::ast;
+use crate::compiler::semantic_analysis::call_graph::CallGraph;
+use crate::compiler::semantic_analysis::control_flow::ControlFlow;
 use crate::compiler::symbol_table::Sym;
 
-pub fn analyse(ast: ast::Root, _sym: Sym) -> Result<AnalyzedAST> {
-    // TODO type checking
-    Ok(AnalyzedAST { ast })
+pub mod call_graph;
+mod const_eval;
+mod control_flow;
+mod declarations;
+mod entry_point;
+mod exhaustiveness;
+pub mod lints;
+mod name_resolution;
+mod type_check;
+
+pub fn analyse(mut ast: ast::Root, sym: Sym, options: &Options) -> Result<AnalyzedAST> {
+    declarations::collect(&ast, &sym)?;
+
+    if !options.lib {
+        entry_point::check(&ast)?;
+    }
+
+    name_resolution::resolve(&mut ast, &sym)?;
+
+    type_check::check(&mut ast, &sym)?;
+
+    let control_flow = ControlFlow::analyse(&ast);
+    control_flow.check_returns(&ast)?;
+    const_eval::evaluate(&mut ast)?;
+    let call_graph = CallGraph::build(&ast);
+    let warnings = lints::check(&ast, &sym, &call_graph, &control_flow, &options.lints)?;
+
+    // Functions that can never be called do not have to be generated
+    if let Some(main) = ast.main_function().filter(|_| !options.lib) {
+        let mut live_functions = call_graph.reachable_from(&main.sym);
+        live_functions.insert(main.sym.clone());
+
+        ast.functions
+            .retain(|function| live_functions.contains(&function.sym));
+    }
+
+    Ok(AnalyzedAST {
+        ast,
+        warnings,
+        call_graph,
+    })
 }
 
 pub struct AnalyzedAST {
     pub ast: ast::Root,
+    pub warnings: Vec<Warning>,
+    /// Call graph of all functions, including the ones that were removed from `ast` because they are never called
+    pub call_graph: CallGraph,
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::compiler::lexer::tokenize_with_spans;
+    use crate::compiler::options::Options;
+    use crate::compiler::parser::{ast, parse};
+    use crate::compiler::semantic_analysis::lints::{Lint, LintLevel};
+    use crate::compiler::semantic_analysis::{analyse, AnalyzedAST};
+    use crate::compiler::span::{Location, Span};
+    use crate::compiler::symbol_table::{ConstValue, Sym, Symbol};
+
+    fn analyse_src(src: &str) -> Result<AnalyzedAST> {
+        analyse_src_with_options(src, &Options::default())
+    }
+
+    fn analyse_src_with_options(src: &str, options: &Options) -> Result<AnalyzedAST> {
+        let sym = Sym::new();
+        let ast = parse(tokenize_with_spans(src)?, sym.clone())?;
+        analyse(ast, sym, options)
+    }
+
+    /// Analyses `src`, which must be rejected, and returns the error message
+    fn analyse_error(src: &str) -> String {
+        match analyse_src(src) {
+            Ok(_) => panic!("expected semantic analysis to fail"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    pub fn annotates_types() -> Result<()> {
+        let analyzed = analyse_src("fun check() -> bool {\n return 'a' as int < answer()\n}\nfun answer() -> int { return 42 }\nfun main() { print(check()) }")?;
+
+        let return_expr = &analyzed.ast.functions[0].compound.expressions[0];
+        let ast::ExprKind::Return(value) = &return_expr.kind else {
+            panic!("expected return expression");
+        };
+        assert_eq!(return_expr.ty(), ast::Type::Never);
+        assert_eq!(value.ty(), ast::Type::Bool);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn type_mismatches() {
+        assert!(analyse_src("fun main() -> int {}").is_err());
+        assert!(analyse_src("fun main() -> int { return 'a' }").is_err());
+        assert!(analyse_src("fun main() { print(1 == true) }").is_err());
+        assert!(analyse_src("fun main() { print(\"a\" as int) }").is_err());
+        assert!(analyse_src("fun main() { print(main()) }").is_err());
+        assert!(analyse_src("fun main() { println(1, 2) }").is_err());
+    }
+
+    #[test]
+    pub fn unknown_function_suggestion() {
+        let Err(err) = analyse_src("fun main() {\n helo()\n}\nfun hello() {}") else {
+            panic!("expected call of unknown function to fail");
+        };
+        assert!(err.to_string().contains("did you mean `hello`?"));
+
+        let Err(err) = analyse_src("fun main() { prntln() }") else {
+            panic!("expected call of unknown function to fail");
+        };
+        assert!(err.to_string().contains("did you mean `println`?"));
+    }
+
+    #[test]
+    pub fn never_type() -> Result<()> {
+        analyse_src("fun main() -> int {\n fail()\n}\nfun fail() -> ! {\n exit 1\n}")?;
+        analyse_src("fun main() -> int {\n return fail()\n}\nfun fail() -> ! {\n exit 1\n}")?;
+        analyse_src("fun main() -> int {\n world()\n}\nfun world() {\n exit 1\n}")?;
+        analyse_src(
+            "fun main() -> int {\n return (fail() == 1) as int\n}\nfun fail() -> ! {\n exit 1\n}",
+        )?;
+
+        assert!(analyse_src("fun main() -> ! {}").is_err());
+        assert!(analyse_src("fun main() -> ! { return main() }").is_ok());
+        assert!(analyse_src("fun main() -> ! { return 1 }").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn missing_return() -> Result<()> {
+        let Err(err) = analyse_src("fun main() -> int {\n if true {\n return 1\n }\n}") else {
+            panic!("expected function that may fall off the end to fail");
+        };
+        assert_eq!(
+            err.to_string(),
+            "5:1: Function `main` declared `-> int` may fall off the end"
+        );
+
+        analyse_src("fun main() -> int {\n if true {\n return 1\n } else if false {\n exit 1\n } else {\n return 2\n }\n}")?;
+        analyse_src("fun main() -> int {\n loop {\n if true {\n return 1\n }\n }\n}")?;
+        analyse_src("fun main() -> int {\n fail()\n}\nfun fail() {\n loop {}\n}")?;
+
+        assert!(analyse_src("fun main() -> int {\n while true {\n return 1\n }\n}").is_err());
+        assert!(analyse_src("fun main() -> int {\n if true {\n return 1\n } else {}\n}").is_err());
+        assert!(analyse_src(
+            "fun main() -> ! {\n loop {\n return fail()\n }\n}\nfun fail() -> ! { exit 1 }"
+        )
+        .is_ok());
+        assert!(analyse_src("fun main() { if 1 {} }").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn unreachable_code() -> Result<()> {
+        let analyzed = analyse_src(
+            "fun main() -> int {\n {\n return 1\n return 2\n }\n return 3\n}\nfun a() {\n b()\n println()\n}\nfun b() {\n exit 1\n}",
+        )?;
+
+        let unreachable_lines: Vec<_> = analyzed
+            .warnings
+            .iter()
+            .filter(|warning| warning.lint == Lint::UnreachableCode)
+            .map(|warning| warning.span.start.line)
+            .collect();
+        assert_eq!(unreachable_lines, &[4, 6, 10]);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn variables() -> Result<()> {
+        analyse_src("fun main() -> int {\n let x = 1\n let y: bool = x < 2\n {\n let x = y\n print(x)\n }\n return x\n}")?;
+
+        let Err(err) = analyse_src("fun main() {\n let value = 1\n print(valu)\n}") else {
+            panic!("expected reference to unknown variable to fail");
+        };
+        assert!(err.to_string().contains("did you mean `value`?"));
+
+        assert!(analyse_src("fun main() { let x: int = 'a' }").is_err());
+        assert!(analyse_src("fun main() { let x = x }").is_err());
+        assert!(analyse_src("fun main() {\n { let x = 1 }\n print(x)\n}").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn lints() -> Result<()> {
+        let src = "fun main() {\n let unused = 1\n let x = 1\n {\n let x = 2\n print(x)\n }\n {\n print(x)\n }\n}\nfun helperFn() {}";
+
+        let lints: Vec<_> = analyse_src(src)?
+            .warnings
+            .iter()
+            .map(|warning| (warning.span.start.line, warning.lint))
+            .collect();
+        assert_eq!(
+            lints,
+            &[
+                (2, Lint::UnusedVariables),
+                (5, Lint::Shadowing),
+                (8, Lint::RedundantBlocks),
+                (12, Lint::UnusedFunctions),
+                (12, Lint::NonSnakeCase),
+            ]
+        );
+
+        let mut options = Options::default();
+        options.lints.set_level(Lint::Shadowing, LintLevel::Allow);
+        options
+            .lints
+            .set_level(Lint::UnusedFunctions, LintLevel::Deny);
+        assert!(analyse_src_with_options(src, &options).is_err());
+
+        let src = src.replace(
+            "fun helperFn",
+            "#[allow(unused_functions)]\n#[warn(shadowing)]\nfun helperFn",
+        );
+        let analyzed = analyse_src_with_options(&src, &options)?;
+        assert_eq!(analyzed.warnings.len(), 3);
+
+        assert!(analyse_src("#[allow(unknown_lint)]\nfun main() {}").is_err());
+        assert!(analyse_src("#[inline]\nfun main() {}").is_err());
+
+        let analyzed = analyse_src(
+            "struct text_box { width: int }\nenum Shape { circle }\nconst max: int = 1\nstatic mut Count: int = 0\nfun main() {}",
+        )?;
+        let lints: Vec<_> = analyzed
+            .warnings
+            .iter()
+            .map(|warning| (warning.span.start.line, warning.lint))
+            .collect();
+        assert_eq!(
+            lints,
+            &[
+                (1, Lint::NonCamelCaseTypes),
+                (2, Lint::NonCamelCaseTypes),
+                (3, Lint::NonUpperCaseGlobals),
+                (4, Lint::NonUpperCaseGlobals),
+            ]
+        );
+        assert_eq!(
+            analyzed.warnings[0].message,
+            "Struct `text_box` should have an upper camel case name such as `TextBox`"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn call_graph() -> Result<()> {
+        let analyzed = analyse_src(
+            "fun main() {\n a()\n b()\n a()\n}\nfun a() { b() }\nfun b() {}\nfun unused() { a() }",
+        )?;
+
+        assert_eq!(
+            analyzed.call_graph.to_dot(),
+            "digraph callgraph {\n    \"main\";\n    \"main\" -> \"a\";\n    \"main\" -> \"b\";\n    \"a\";\n    \"a\" -> \"b\";\n    \"b\";\n    \"unused\";\n    \"unused\" -> \"a\";\n}\n"
+        );
+
+        // Functions that are never called are not generated
+        let live_functions: Vec<_> = analyzed
+            .ast
+            .functions
+            .iter()
+            .map(|function| function.sym.get().name.clone())
+            .collect();
+        assert_eq!(live_functions, &["main", "a", "b"]);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn unconditional_recursion() -> Result<()> {
+        let src = "fun main() {\n a()\n b()\n print(c())\n}\nfun a() {\n b()\n}\nfun b() {\n if true {\n a()\n } else {\n a()\n }\n}\nfun c() -> int {\n if false {\n return 1\n }\n return c()\n}";
+        let recursion_lines: Vec<_> = analyse_src(src)?
+            .warnings
+            .iter()
+            .filter(|warning| warning.lint == Lint::UnconditionalRecursion)
+            .map(|warning| warning.span.start.line)
+            .collect();
+        assert_eq!(recursion_lines, &[6]);
+
+        let src = "fun main() -> int {\n return main()\n}\nfun exits() {\n if true {\n exit 1\n }\n exits()\n}";
+        let recursion_lines: Vec<_> = analyse_src(src)?
+            .warnings
+            .iter()
+            .filter(|warning| warning.lint == Lint::UnconditionalRecursion)
+            .map(|warning| warning.span.start.line)
+            .collect();
+        assert_eq!(recursion_lines, &[1]);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn entry_point() -> Result<()> {
+        analyse_src("fun main() {}")?;
+        analyse_src("fun main() -> int { return 0 }")?;
+        analyse_src("fun main() -> ! {\n exit 1\n}")?;
+
+        assert!(analyse_src("fun start() {}").is_err());
+        assert_eq!(
+            analyse_error("fun main() -> bool { return true }"),
+            "1:5: Function `main` must return `()`, `int` or `!`, found `bool`"
+        );
+        assert!(analyse_src("fun main() {}\nfun main() {}").is_err());
+
+        let options = Options {
+            lib: true,
+            ..Options::default()
+        };
+        analyse_src_with_options("fun start() {}", &options)?;
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn declarations() -> Result<()> {
+        analyse_src("static S: int = B\nfun main() { print(a()) }\nconst B: int = A + 1\nfun a() -> int { return S }\nconst A: int = 1")?;
+
+        assert_eq!(
+            analyse_error("fun main() {}\nfun a() {}\nfun main() {}"),
+            "3:5: Redefinition of `main`, which is already defined at 1:5"
+        );
+        assert_eq!(
+            analyse_error("const A: int = 1\nstatic A: int = 2\nfun main() {}"),
+            "2:8: Redefinition of `A`, which is already defined at 1:7"
+        );
+        assert!(analyse_error("fun print() {}\nfun main() {}").contains("builtin function"));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn scopes() -> Result<()> {
+        let sym = Sym::new();
+        let src = "fun main() {\n let a = 1\n let b = 2\n {\n  let a = 'x'\n  print(a)\n }\n print(b)\n}\nconst LIMIT: int = 3\nstruct Point { x: int }\nenum Axis { X, Y }";
+        let ast = parse(tokenize_with_spans(src)?, sym.clone())?;
+        analyse(ast, sym.clone(), &Options::default())?;
+
+        let visible_at = |line, column| {
+            let location = Location::new(line, column);
+            sym.lookup_at(Span::new(location, location))
+                .iter()
+                .map(|symbol| match symbol {
+                    Symbol::Variable(variable) => {
+                        format!("{}: {}", variable.get().name, variable.get().ty())
+                    }
+                    other => other.name(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            visible_at(3, 2),
+            &["a: int", "LIMIT", "Axis", "Point", "main"]
+        );
+        assert_eq!(
+            visible_at(6, 3),
+            &["a: char", "b: int", "LIMIT", "Axis", "Point", "main"]
+        );
+        assert_eq!(
+            visible_at(8, 2),
+            &["a: int", "b: int", "LIMIT", "Axis", "Point", "main"]
+        );
+        assert_eq!(visible_at(10, 1), &["LIMIT", "Axis", "Point", "main"]);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn constant_evaluation() -> Result<()> {
+        let analyzed = analyse_src(
+            "const ANSWER: int = 6 * (3 + 4)\nconst TOO_BIG: bool = square() > ANSWER\nconst fun square() -> int {\n let x = 5\n return x * x\n}\nconst fun sum() -> int {\n let total = 0\n while total < 10 {\n let total = 10\n return total\n }\n return 0\n}\nfun main() {\n print(ANSWER - 1)\n print(sum())\n}",
+        )?;
+
+        let values: Vec<_> = analyzed
+            .ast
+            .constants
+            .iter()
+            .map(|constant| constant.sym.get().value().clone())
+            .collect();
+        assert_eq!(values, &[ConstValue::Int(42), ConstValue::Bool(false)]);
+
+        let main = analyzed.ast.main_function().expect("main to exist");
+        let print_values: Vec<_> = main
+            .compound
+            .expressions
+            .iter()
+            .map(|expr| {
+                let ast::ExprKind::BuiltinCall(_, args) = &expr.kind else {
+                    panic!("expected call of `print`");
+                };
+                args[0].value.clone()
+            })
+            .collect();
+        assert_eq!(
+            print_values,
+            &[Some(ConstValue::Int(41)), Some(ConstValue::Int(10))]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn statics() -> Result<()> {
+        let analyzed = analyse_src(
+            "static mut COUNTER: int = 10 * 10\nstatic NAME: str = \"yyn\"\nfun main() {\n COUNTER = COUNTER + 1\n println(NAME)\n}",
+        )?;
+        assert_eq!(
+            analyzed.ast.statics[0].sym.get().initial_value(),
+            &ConstValue::Int(100)
+        );
+
+        assert!(analyse_error("static A: int = 1\nfun main() { A = 2 }")
+            .contains("immutable static `A`"));
+        assert!(
+            analyse_error("static mut A: int = 1\nfun main() { A = true }")
+                .contains("Mismatched types")
+        );
+        assert!(analyse_error("fun main() {\n let a = 1\n a = 2\n}")
+            .contains("Cannot assign to variable `a`"));
+        assert!(
+            analyse_error("static mut A: int = 1\nconst B: int = A\nfun main() {}")
+                .contains("Cannot use static `A`")
+        );
+        assert!(
+            analyse_error("static A: int = f()\nfun f() -> int { return 1 }\nfun main() {}")
+                .contains("non-const function")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn constant_evaluation_errors() {
+        assert!(analyse_error("const A: int = 2147483647 + 1\nfun main() {}").contains("Overflow"));
+        assert!(analyse_error("fun main() { print(7 / (2 - 2)) }").contains("Division by zero"));
+        assert!(
+            analyse_error("const A: int = B\nconst B: int = A\nfun main() {}")
+                .contains("depends on its own value")
+        );
+        assert!(
+            analyse_error("const A: int = f()\nfun f() -> int { return 1 }\nfun main() {}")
+                .contains("non-const function `f`")
+        );
+        assert!(analyse_error("const fun f() { print(1) }\nfun main() {}")
+            .contains("Cannot call `print`"));
+        assert!(analyse_error(
+            "const fun f() -> int {\n loop {}\n}\nconst A: int = f()\nfun main() {}"
+        )
+        .contains("loop iterations"));
+        assert!(analyse_error("const A: str = 1\nfun main() {}").contains("Mismatched types"));
+        assert!(
+            analyse_error("const A: char = 55296 as char\nfun main() {}")
+                .contains("`55296` is not a valid `char`")
+        );
+        assert!(analyse_error("fun main() { print((0 - 1) as char) }")
+            .contains("`-1` is not a valid `char`"));
+    }
+
+    #[test]
+    pub fn evaluation_limits() -> Result<()> {
+        let sum = "const fun sum(n: int) -> int {\n if n == 0 {\n  return 0\n }\n return n + sum(n - 1)\n}";
+
+        // Calls in function bodies that exceed a limit are made at runtime instead
+        let analyzed = analyse_src(&format!(
+            "{sum}\nconst fun spin() -> int {{\n loop {{}}\n}}\nfun main() {{\n println(sum(100))\n println(sum(100) + 1)\n println(spin())\n println(sum(10))\n}}"
+        ))?;
+        let main = analyzed.ast.main_function().expect("main to exist");
+        let values: Vec<_> = main
+            .compound
+            .expressions
+            .iter()
+            .map(|expr| {
+                let ast::ExprKind::BuiltinCall(_, args) = &expr.kind else {
+                    panic!("expected call of `println`");
+                };
+                args[0].value.clone()
+            })
+            .collect();
+        assert_eq!(values, &[None, None, None, Some(ConstValue::Int(55))]);
+
+        // Constants and statics must be known at compile time
+        assert!(
+            analyse_error(&format!("{sum}\nconst A: int = sum(100)\nfun main() {{}}"))
+                .contains("limit of 64 nested calls")
+        );
+        assert!(analyse_error(&format!(
+            "{sum}\nstatic mut A: int = sum(100)\nfun main() {{}}"
+        ))
+        .contains("limit of 64 nested calls"));
+
+        // Every evaluation has its own budget of loop iterations
+        let analyzed = analyse_src(
+            "const fun count(n: int) -> int {\n for value in [0; 600000] {}\n return n\n}\nconst A: int = count(1)\nconst B: int = count(2)\nfun main() {}",
+        )?;
+        assert_eq!(
+            analyzed.ast.constants[1].sym.get().value(),
+            &ConstValue::Int(2)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn arithmetic_builtins() -> Result<()> {
+        let analyzed = analyse_src(
+            "const A: int = wrapping_add(2147483647, 1)\nconst B: int = saturating_add(2147483647, 1)\nconst C: int = checked_add(40, 2)\nfun main() {}",
+        )?;
+
+        let values: Vec<_> = analyzed
+            .ast
+            .constants
+            .iter()
+            .map(|constant| constant.sym.get().value().clone())
+            .collect();
+        assert_eq!(
+            values,
+            &[
+                ConstValue::Int(i32::MIN),
+                ConstValue::Int(i32::MAX),
+                ConstValue::Int(42)
+            ]
+        );
+
+        assert!(
+            analyse_error("const A: int = checked_add(2147483647, 1)\nfun main() {}")
+                .contains("Overflow")
+        );
+        assert!(analyse_error("fun main() { print(wrapping_add(1)) }")
+            .contains("exactly two arguments"));
+        assert!(
+            analyse_error("fun main() { print(saturating_add(1, true)) }")
+                .contains("Mismatched types")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn panics() -> Result<()> {
+        let analyzed = analyse_src(
+            "fun main() -> int {\n if false {\n return 1\n }\n panic(\"unexpected\")\n println()\n}",
+        )?;
+        let messages: Vec<_> = analyzed
+            .warnings
+            .iter()
+            .filter(|warning| warning.lint == Lint::UnreachableCode)
+            .map(|warning| warning.message.clone())
+            .collect();
+        assert_eq!(messages, &["Unreachable code after the `panic` at 5:2"]);
+
+        assert_eq!(
+            analyse_error(
+                "const fun f() -> int {\n panic(\"not yet\")\n}\nconst A: int = f()\nfun main() {}"
+            ),
+            "2:2: Panicked at compile time: not yet"
+        );
+        assert!(analyse_error("fun main() { panic(1) }").contains("expected `str`, found `int`"));
+        assert!(analyse_error("fun main() { panic() }").contains("exactly one argument"));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn assertions() -> Result<()> {
+        analyse_src(
+            "const fun checked() -> int {\n assert(1 < 2)\n assert_eq('a', 'a')\n return 1\n}\nconst A: int = checked()\nfun main() {\n assert_eq(A, 1)\n}",
+        )?;
+
+        assert_eq!(
+            analyse_error("const fun f() -> int {\n assert_eq(1 + 1, 3)\n return 1\n}\nconst A: int = f()\nfun main() {}"),
+            "2:2: Assertion failed at compile time, left is `2`, right is `3`"
+        );
+        assert!(analyse_error("fun main() { assert(1) }").contains("expected `bool`, found `int`"));
+        assert!(analyse_error("fun main() { assert_eq(1, true) }").contains("Mismatched types"));
+        assert!(
+            analyse_error("fun main() { assert_eq(\"a\", \"a\") }").contains("cannot be compared")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn arrays() -> Result<()> {
+        analyse_src(
+            "const PRIMES: [int; 4] = [2, 3, 5, 7]\nconst THIRD: int = PRIMES[2]\nstatic mut GRID: [[bool; 2]; 3] = [[false; 2]; 3]\nfun main() {\n let zeros = [0; 16]; let i = 3\n println(PRIMES[i] + zeros[15] + THIRD)\n}",
+        )?;
+
+        assert_eq!(
+            analyse_error("const A: int = [1, 2][2]\nfun main() {}"),
+            "1:16: Index out of bounds while evaluating at compile time: the length is 2 but the index is 2"
+        );
+        assert_eq!(
+            analyse_error("fun main() { let a = [1, true] }"),
+            "1:26: Mismatched types in array element: expected `int`, found `bool`"
+        );
+        assert!(analyse_error("fun main() { let a = [] }")
+            .contains("Empty array literals are not supported"));
+        assert!(
+            analyse_error("fun main() { let a = [1][true] }").contains("Mismatched types in index")
+        );
+        assert!(analyse_error("fun main() { let a = 1[0] }")
+            .contains("Cannot index into a value of type `int`"));
+        assert!(analyse_error("fun main() { println([1]) }")
+            .contains("Values of type `[int; 1]` cannot be printed"));
+        assert!(analyse_error("fun main() { let a: [int; 2] = [1, 2, 3] }")
+            .contains("expected `[int; 2]`, found `[int; 3]`"));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn slices() -> Result<()> {
+        let analyzed = analyse_src(
+            "const PRIMES: [int; 5] = [2, 3, 5, 7, 11]\nconst fun sum_middle() -> int {\n let middle = PRIMES[1..4]\n for prime in middle {\n  assert(prime > 2)\n }\n return middle[0] + middle[middle.len() - 1]\n}\nconst SUM: int = sum_middle()\nfun main() {\n let values: [int] = PRIMES[..]\n for value in values[2..] {\n  println(value)\n }\n}",
+        )?;
+        assert_eq!(
+            analyzed.ast.constants[1].sym.get().value(),
+            &ConstValue::Int(10)
+        );
+
+        let main = analyzed.ast.main_function().expect("main to exist");
+        let ast::ExprKind::For(for_loop) = &main.compound.expressions[1].kind else {
+            panic!("expected `for` loop");
+        };
+        let slice_ty = ast::Type::Slice(Box::new(ast::Type::Int));
+        assert_eq!(for_loop.iterable.ty(), slice_ty);
+        assert_eq!(for_loop.variable.get().ty(), ast::Type::Int);
+
+        assert_eq!(
+            analyse_error("const A: int = [1, 2][1..3].len()\nfun main() {}"),
+            "1:16: Range out of bounds while evaluating at compile time: the length is 2 but the range is 1..3"
+        );
+        assert!(
+            analyse_error("fun f() -> [int] { return [1][..] }\nfun main() {}")
+                .contains("Function `f` cannot return `[int]`")
+        );
+        assert!(analyse_error("const A: [int] = [1][..]\nfun main() {}")
+            .contains("Type `[int]` is not supported for constant `A`"));
+        assert!(analyse_error("fun main() { for x in 3 {} }")
+            .contains("Cannot iterate over a value of type `int`"));
+        assert!(analyse_error("fun main() { let a = [1][true..] }")
+            .contains("Mismatched types in range bound"));
+        assert!(analyse_error("fun main() { let a = [1].size() }")
+            .contains("Unknown method `size`, only `len` is supported"));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn parameters() -> Result<()> {
+        let analyzed = analyse_src(
+            "const fun sum(values: [int]) -> int {\n let total = 0\n for value in values {\n  assert(value > 0)\n }\n return values[0] + values[values.len() - 1]\n}\nconst fun fib(n: int) -> int {\n if n < 2 {\n  return n\n }\n return fib(n - 1) + fib(n - 2)\n}\nconst SUM: int = sum([1, 2, 3][1..])\nconst FIB: int = fib(20)\nfun main() {\n println(fib(10))\n println(sum([4, 5][..]))\n}",
+        )?;
+        assert_eq!(
+            analyzed.ast.constants[0].sym.get().value(),
+            &ConstValue::Int(5)
+        );
+        assert_eq!(
+            analyzed.ast.constants[1].sym.get().value(),
+            &ConstValue::Int(6765)
+        );
+
+        // The recursion of `fib` has a base case, so the call does not diverge
+        let main = analyzed.ast.main_function().expect("main to exist");
+        let ast::ExprKind::BuiltinCall(_, args) = &main.compound.expressions[0].kind else {
+            panic!("expected call of `println`");
+        };
+        assert_eq!(args[0].ty(), ast::Type::Int);
+        assert_eq!(args[0].value, Some(ConstValue::Int(55)));
+
+        let messages: Vec<_> = analyzed
+            .warnings
+            .iter()
+            .map(|warning| warning.message.clone())
+            .collect();
+        assert_eq!(messages, &["Variable `total` is never used"]);
+
+        assert_eq!(
+            analyse_error("fun f(a: int, b: bool) {}\nfun main() { f(1) }"),
+            "2:14: Function `f` expects 2 arguments, got 1"
+        );
+        assert_eq!(
+            analyse_error("fun f(a: int) {}\nfun main() { f('a') }"),
+            "2:16: Mismatched types in argument of `f`: expected `int`, found `char`"
+        );
+        assert_eq!(
+            analyse_error("fun f(a: int, a: int) {}\nfun main() {}"),
+            "1:15: Redefinition of `a`, which is already defined at 1:7"
+        );
+        assert!(analyse_error("fun f(a: ()) {}\nfun main() {}")
+            .contains("Parameters of type `()` are not supported"));
+        assert!(analyse_error("fun f(a: Point) {}\nfun main() {}").contains("Unknown type `Point`"));
+        assert!(
+            analyse_error("fun main(a: int) {}").contains("Function `main` cannot have parameters")
+        );
+        assert_eq!(
+            analyse_error("const fun f(n: int) -> int {\n return f(n)\n}\nconst A: int = f(1)\nfun main() {}"),
+            "2:9: Const function `f` calls itself with the same arguments, so it cannot be evaluated at compile time"
+        );
+        assert!(analyse_error(
+            "const fun f(n: int) -> int {\n return f(n + 1)\n}\nconst A: int = f(1)\nfun main() {}"
+        )
+        .contains("exceeded the limit of 64 nested calls"));
+
+        let warnings = analyse_src("fun f(used: int, unused: int, _ignored: int) {\n print(used)\n}\nfun main() { f(1, 2, 3) }")?.warnings;
+        let messages: Vec<_> = warnings
+            .iter()
+            .map(|warning| (warning.span.start.column, warning.message.clone()))
+            .collect();
+        assert_eq!(
+            messages,
+            &[(18, "Parameter `unused` is never used".to_string())]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn structs() -> Result<()> {
+        let analyzed = analyse_src(
+            "struct Point { x: int, y: int }\nstruct Line {\n start: Point,\n end: Point,\n}\nconst ORIGIN: Point = Point { y: 0, x: 0 }\nconst LINE: Line = Line { start: ORIGIN, end: Point { x: 3, y: 4 } }\nconst END_Y: int = LINE.end.y\nstatic mut CURSOR: Point = Point { x: 1, y: 2 }\nfun main() {\n CURSOR.x = LINE.end.x\n if CURSOR.x == 3 {}\n for p in [Point { x: 1, y: 1 }] { assert(p.x == (Point { x: 1, y: 0 }).x) }\n}",
+        )?;
+        assert_eq!(
+            analyzed.ast.constants[1].sym.get().value().to_string(),
+            "Line { start: Point { x: 0, y: 0 }, end: Point { x: 3, y: 4 } }"
+        );
+        assert_eq!(
+            analyzed.ast.constants[2].sym.get().value(),
+            &ConstValue::Int(4)
+        );
+
+        let main = analyzed.ast.main_function().expect("main to exist");
+        let ast::ExprKind::Assign(assignment) = &main.compound.expressions[0].kind else {
+            panic!("expected assignment");
+        };
+        assert_eq!(assignment.fields[0].index(), 0);
+        assert_eq!(assignment.value.value, Some(ConstValue::Int(3)));
+
+        assert_eq!(
+            analyse_error("struct Point { x: int }\nfun main() { let p: Pont = Point { x: 1 } }"),
+            "2:18: Unknown type `Pont`, did you mean `Point`?"
+        );
+        assert!(analyse_error("struct P { x: int, x: int }\nfun main() {}")
+            .contains("Redefinition of `x`"));
+        assert!(
+            analyse_error("struct A { b: B }\nstruct B { a: [A; 2] }\nfun main() {}")
+                .contains("Struct `A` contains itself through field `b`")
+        );
+        assert!(
+            analyse_error("struct P { x: int }\nfun main() { let p = P { x: 1, y: 2 } }")
+                .contains("Struct `P` has no field `y`")
+        );
+        assert!(
+            analyse_error("struct P { x: int, y: int }\nfun main() { let p = P { x: 1 } }")
+                .contains("Missing field `y` in literal of struct `P`")
+        );
+        assert!(
+            analyse_error("struct P { x: int }\nfun main() { let p = P { x: 1, x: 2 } }")
+                .contains("Field `x` is initialized more than once")
+        );
+        assert!(
+            analyse_error("struct P { x: int }\nfun main() { let p = P { x: true } }")
+                .contains("Mismatched types in field `x`: expected `int`, found `bool`")
+        );
+        assert!(analyse_error("fun main() { let a = 1.x }")
+            .contains("Values of type `int` have no fields"));
+        assert!(
+            analyse_error("struct P { x: int }\nfun main() { println(P { x: 1 }) }")
+                .contains("Values of type `P` cannot be printed")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn enums() -> Result<()> {
+        let analyzed = analyse_src(
+            "enum Shape { Circle(int), Rect(int, int), Empty }
+const fun area() -> int {
+ return match Shape::Rect(2, 3) {
+ Shape::Circle(r) => 3 * r * r,
+ Shape::Rect(w, h) => w * h,
+ Shape::Empty => 0,
+ }
+}
+const AREA: int = area()
+fun main() {
+ match Shape::Circle(1) {
+ Shape::Circle(1) => println(1),
+ Shape::Circle(_) => {}
+ _ => println(0),
+ Shape::Empty => {}
+ }
+}",
+        )?;
+        assert_eq!(
+            analyzed.ast.constants[0].sym.get().value(),
+            &ConstValue::Int(6)
+        );
+        let lints: Vec<_> = analyzed
+            .warnings
+            .iter()
+            .map(|warning| (warning.span.start.line, warning.lint))
+            .collect();
+        assert_eq!(lints, vec![(15, Lint::UnreachablePatterns)]);
+
+        let shape = "enum Shape { Circle(int), Rect(int, int), Empty }
+enum Wrapper { Some(Shape), None }
+";
+        assert_eq!(
+            analyse_error(&format!(
+                "{shape}fun main() {{
+ let a = match Shape::Empty {{ Shape::Circle(_) => 1, Shape::Empty => 0 }}
+}}"
+            )),
+            "4:10: Non-exhaustive patterns in `match`: `Shape::Rect(_, _)` is not covered"
+        );
+        assert!(analyse_error(&format!(
+            "{shape}fun main() {{
+ let a = match Wrapper::None {{ Wrapper::Some(Shape::Circle(_)) => 1, Wrapper::None => 0 }}
+}}"
+        ))
+        .contains("`Wrapper::Some(Shape::Rect(_, _))` is not covered"));
+        assert!(
+            analyse_error("fun main() { let a = match true { true => 1 } }")
+                .contains("`false` is not covered")
+        );
+        assert!(analyse_error("fun main() { let a = match 1 { 0 => 1 } }")
+            .contains("`_` is not covered"));
+        assert!(
+            analyse_error(&format!("{shape}fun main() {{ let a = Shape::Circl(1) }}"))
+                .contains("Enum `Shape` has no variant `Circl`, did you mean `Circle`?")
+        );
+        assert!(
+            analyse_error(&format!("{shape}fun main() {{ let a = Shape::Rect(1) }}"))
+                .contains("Variant `Shape::Rect` expects 2 fields, got 1")
+        );
+        assert!(analyse_error(&format!(
+            "{shape}fun main() {{ let a = match Shape::Empty {{ Shape::Rect(x, x) => x, _ => 0 }} }}"
+        ))
+        .contains("Variable `x` is bound more than once in the same pattern"));
+        assert!(analyse_error(&format!(
+            "{shape}fun main() {{ let a = match Shape::Empty {{ Shape::Empty => 1, _ => true }} }}"
+        ))
+        .contains("Mismatched types in match arm: expected `int`, found `bool`"));
+        assert!(analyse_error(
+            "enum List { Node(int, List), End }
+fun main() {}"
+        )
+        .contains("Enum `List` contains itself through variant `Node`"));
+        assert!(analyse_error(
+            "enum E { A }
+const X: E = E::A
+fun main() {}"
+        )
+        .contains("Type `E` is not supported for constant `X`"));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn tuples() -> Result<()> {
+        let analyzed = analyse_src(
+            "const fun divide() -> (int, int) {
+ let (q, _) = (17 / 5, 0)
+ return (q, 17 % 5)
+}
+const REMAINDER: int = divide().1
+const NESTED: (int, (bool,)) = (1, (true,))
+fun main() {
+ let (a, (b,)): (int, (bool,)) = NESTED
+ match divide() {
+ (_, 0) => println(a),
+ (q, _) => println(q),
+ }
+ if b {}
+}",
+        )?;
+        assert_eq!(
+            analyzed.ast.constants[0].sym.get().value(),
+            &ConstValue::Int(2)
+        );
+        assert_eq!(
+            analyzed.ast.constants[1].sym.get().value().to_string(),
+            "(1, (true,))"
+        );
+
+        assert_eq!(
+            analyse_error("fun main() { let (a, true) = (1, false) }"),
+            "1:18: Refutable pattern in `let`: `(_, false)` is not covered"
+        );
+        assert!(
+            analyse_error("fun main() { let t = (1, true) let c = t.2 }")
+                .contains("Tuple `(int, bool)` has no field `2`")
+        );
+        assert!(analyse_error("fun main() { let (a, b) = (1, 2, 3) }").contains(
+            "Mismatched types in pattern: expected `(int, int, int)`, found a tuple of 2 elements"
+        ));
+        assert!(analyse_error("fun main() { let (a, a) = (1, 2) }")
+            .contains("Variable `a` is bound more than once in the same pattern"));
+        assert!(analyse_error("fun main() { let t: (int, bool) = (1, 2) }").contains(
+            "Mismatched types in declaration of `t`: expected `(int, bool)`, found `(int, int)`"
+        ));
+        assert!(analyse_error("fun main() { let t = (1, println()) }")
+            .contains("Tuples with elements of type `()` are not supported"));
+        assert!(
+            analyse_error("fun main() { match (1, true) { (_, true) => {} } }")
+                .contains("Non-exhaustive patterns in `match`: `(_, false)` is not covered")
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,504 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, bail, Error, Result};
+
+use crate::compiler::parser::ast::{
+    Builtin, CompoundExpr, Expr, ExprKind, Pattern, PatternKind, Root, Type, VariantRef,
+};
+use crate::compiler::ref_arena::ArenaRef;
+use crate::compiler::span::{Location, Span};
+use crate::compiler::symbol_table::{Scope, Sym, Variable};
+
+/// Binds every function call to the called function from the symbol table and every variable reference to the
+/// declaration of the referenced variable. While doing so, the scope tree of every function is built in the symbol
+/// table, the parameters of a function are visible in its whole body. References to names that are not a variable in
+/// scope are bound to constants or statics. All names used in types must refer to a declared struct or enum, names of
+/// enums are resolved to enum types.
+pub fn resolve(root: &mut Root, sym: &Sym) -> Result<()> {
+    for struct_def in &root.structs {
+        for field in &mut struct_def.sym.get_mut().fields {
+            resolve_type(sym, &mut field.ty, field.span)?;
+        }
+    }
+    for enum_def in &root.enums {
+        for variant in &mut enum_def.sym.get_mut().variants {
+            for ty in &mut variant.fields {
+                resolve_type(sym, ty, variant.span)?;
+            }
+        }
+    }
+    for function in &root.functions {
+        let mut fn_sym = function.sym.get_mut();
+        for param in &fn_sym.params {
+            let mut variable = param.variable.get_mut();
+            let ty = variable
+                .ty
+                .as_mut()
+                .expect("parameters to have a declared type");
+            resolve_type(sym, ty, param.span)?;
+        }
+        resolve_type(sym, &mut fn_sym.return_ty, function.span)?;
+    }
+    for constant in &root.constants {
+        resolve_type(sym, &mut constant.sym.get_mut().ty, constant.span)?;
+    }
+    for static_def in &root.statics {
+        resolve_type(sym, &mut static_def.sym.get_mut().ty, static_def.span)?;
+    }
+
+    let global_values = root
+        .constants
+        .iter_mut()
+        .map(|constant| &mut constant.value)
+        .chain(
+            root.statics
+                .iter_mut()
+                .map(|static_def| &mut static_def.value),
+        );
+    for value in global_values {
+        let mut resolver = Resolver { sym, scope: None };
+        resolver.resolve_expr(value)?;
+    }
+
+    root.functions.iter_mut().try_for_each(|function| {
+        let params: Vec<_> = function
+            .sym
+            .get()
+            .params
+            .iter()
+            .map(|param| param.variable.clone())
+            .collect();
+
+        let mut resolver = Resolver { sym, scope: None };
+        resolver.resolve_compound_declaring(&mut function.compound, &params)
+    })
+}
+
+struct Resolver<'a> {
+    sym: &'a Sym,
+    /// Scope of the innermost compound surrounding the current expression, it is `None` outside of function bodies
+    scope: Option<ArenaRef<Scope>>,
+}
+
+impl Resolver<'_> {
+    fn resolve_compound(&mut self, compound: &mut CompoundExpr) -> Result<()> {
+        self.resolve_compound_declaring(compound, &[])
+    }
+
+    /// Resolves a compound, in which `variables` are visible from its start, like the variable of a `for` or the
+    /// parameters of a function
+    fn resolve_compound_declaring(
+        &mut self,
+        compound: &mut CompoundExpr,
+        variables: &[ArenaRef<Variable>],
+    ) -> Result<()> {
+        let expressions = &mut compound.expressions;
+        let scope = self.resolve_in_scope(compound.span, variables, |resolver| {
+            expressions
+                .iter_mut()
+                .try_for_each(|expr| resolver.resolve_expr(expr))
+        })?;
+        compound.scope = Some(scope);
+
+        Ok(())
+    }
+
+    /// Opens a new scope covering `span`, in which `variables` are visible from its start, and resolves its contents
+    /// with `resolve`. Returns the new scope.
+    fn resolve_in_scope(
+        &mut self,
+        span: Span,
+        variables: &[ArenaRef<Variable>],
+        resolve: impl FnOnce(&mut Self) -> Result<()>,
+    ) -> Result<ArenaRef<Scope>> {
+        let scope = self.sym.add_scope(self.scope.as_ref(), span);
+        for variable in variables {
+            self.sym
+                .declare_variable(&scope, variable.clone(), span.start);
+        }
+
+        let parent = self.scope.replace(scope.clone());
+        resolve(self)?;
+        self.scope = parent;
+
+        Ok(scope)
+    }
+
+    /// Resolves the variants inside of a pattern of a match arm
+    fn resolve_pattern(&self, pattern: &mut Pattern) -> Result<()> {
+        match &mut pattern.kind {
+            PatternKind::Variant(variant, fields) => {
+                resolve_variant(self.sym, variant)?;
+                fields
+                    .iter_mut()
+                    .try_for_each(|field| self.resolve_pattern(field))
+            }
+            PatternKind::Tuple(elements) => elements
+                .iter_mut()
+                .try_for_each(|element| self.resolve_pattern(element)),
+            PatternKind::Wildcard | PatternKind::Binding(_) | PatternKind::Literal(_) => Ok(()),
+        }
+    }
+
+    /// Finds the variable called `name` that is visible at `location`
+    fn lookup_variable(&self, name: &str, location: Location) -> Option<ArenaRef<Variable>> {
+        self.scope
+            .as_ref()
+            .and_then(|scope| self.sym.lookup_variable(scope, name, location))
+    }
+
+    fn resolve_expr(&mut self, expr: &mut Expr) -> Result<()> {
+        match &mut expr.kind {
+            ExprKind::FnCall(call) => {
+                // The symbol table must not stay borrowed, because the arguments may declare variables
+                let function = self
+                    .sym
+                    .get_function_by_name(&call.name)
+                    .map(|function| function.clone());
+                let Some(function) = function else {
+                    let candidates = self
+                        .sym
+                        .function_names()
+                        .into_iter()
+                        .chain(Builtin::ALL.iter().map(|builtin| builtin.name().to_owned()));
+
+                    match find_similar_name(&call.name, candidates) {
+                        Some(similar) => bail!(
+                            "{}: Unknown function `{}`, did you mean `{similar}`?",
+                            expr.span,
+                            call.name
+                        ),
+                        None => bail!("{}: Unknown function `{}`", expr.span, call.name),
+                    }
+                };
+
+                call.function = Some(function);
+                call.args
+                    .iter_mut()
+                    .try_for_each(|arg| self.resolve_expr(arg))
+            }
+            ExprKind::Variable(variable_ref) => {
+                if let Some(variable) = self.lookup_variable(&variable_ref.name, expr.span.start) {
+                    variable_ref.variable = Some(variable);
+                    return Ok(());
+                }
+
+                if let Some(constant) = self.sym.get_constant_by_name(&variable_ref.name) {
+                    expr.kind = ExprKind::Constant(constant.clone());
+                    return Ok(());
+                }
+
+                if let Some(static_ref) = self.sym.get_static_by_name(&variable_ref.name) {
+                    expr.kind = ExprKind::Static(static_ref.clone());
+                    return Ok(());
+                }
+
+                let visible_variables = self
+                    .scope
+                    .as_ref()
+                    .map(|scope| self.sym.visible_variables(scope, expr.span.start))
+                    .unwrap_or_default();
+                let candidates = visible_variables
+                    .iter()
+                    .map(|variable| variable.get().name.clone())
+                    .chain(self.sym.constant_names())
+                    .chain(self.sym.static_names());
+
+                match find_similar_name(&variable_ref.name, candidates) {
+                    Some(similar) => bail!(
+                        "{}: Unknown variable `{}`, did you mean `{similar}`?",
+                        expr.span,
+                        variable_ref.name
+                    ),
+                    None => bail!("{}: Unknown variable `{}`", expr.span, variable_ref.name),
+                }
+            }
+            ExprKind::Assign(assignment) => {
+                self.resolve_expr(&mut assignment.value)?;
+
+                let span = assignment.span;
+                let name = &assignment.name;
+                if self.lookup_variable(name, span.start).is_some() {
+                    bail!("{span}: Cannot assign to variable `{name}`, because variables cannot be changed, use a `static mut` instead");
+                }
+                if self.sym.get_constant_by_name(name).is_some() {
+                    bail!("{span}: Cannot assign to constant `{name}`");
+                }
+
+                let Some(target) = self.sym.get_static_by_name(name).map(|s| s.clone()) else {
+                    match find_similar_name(name, self.sym.static_names()) {
+                        Some(similar) => {
+                            bail!("{span}: Unknown static `{name}`, did you mean `{similar}`?")
+                        }
+                        None => bail!("{span}: Unknown static `{name}`"),
+                    }
+                };
+
+                if !target.get().is_mutable {
+                    bail!("{span}: Cannot assign to immutable static `{name}`, declare it as `static mut` instead");
+                }
+
+                assignment.target = Some(target);
+                Ok(())
+            }
+            ExprKind::Let(let_expr) => {
+                if let Some(ty) = &mut let_expr.ty {
+                    resolve_type(self.sym, ty, let_expr.pattern.span)?;
+                }
+                self.resolve_pattern(&mut let_expr.pattern)?;
+                let bindings = collect_bindings(&let_expr.pattern)?;
+
+                // The variables are not visible in their own initial value
+                self.resolve_expr(&mut let_expr.value)?;
+
+                let scope = self.scope.as_ref().expect("let to be inside of a compound");
+                for variable in bindings {
+                    self.sym.declare_variable(scope, variable, expr.span.end);
+                }
+                Ok(())
+            }
+            ExprKind::Array(elements) | ExprKind::Tuple(elements) => elements
+                .iter_mut()
+                .try_for_each(|element| self.resolve_expr(element)),
+            ExprKind::ArrayRepeat(element, _) => self.resolve_expr(element),
+            ExprKind::Binary(lhs, _, rhs) | ExprKind::Index(lhs, rhs) => {
+                self.resolve_expr(lhs)?;
+                self.resolve_expr(rhs)
+            }
+            ExprKind::BuiltinCall(_, args) => {
+                args.iter_mut().try_for_each(|arg| self.resolve_expr(arg))
+            }
+            ExprKind::Cast(value, target_ty) => {
+                resolve_type(self.sym, target_ty, expr.span)?;
+                self.resolve_expr(value)
+            }
+            ExprKind::Field(value, _) | ExprKind::Len(value) | ExprKind::Return(value) => {
+                self.resolve_expr(value)
+            }
+            ExprKind::Struct(literal) => {
+                if self.sym.get_struct_by_name(&literal.name).is_none() {
+                    return Err(unknown_name(
+                        "struct",
+                        &literal.name,
+                        self.sym.struct_names(),
+                        expr.span,
+                    ));
+                }
+
+                literal
+                    .fields
+                    .iter_mut()
+                    .try_for_each(|field_init| self.resolve_expr(&mut field_init.value))
+            }
+            ExprKind::Variant(literal) => {
+                resolve_variant(self.sym, &mut literal.variant)?;
+                literal
+                    .args
+                    .iter_mut()
+                    .try_for_each(|arg| self.resolve_expr(arg))
+            }
+            ExprKind::Match(match_expr) => {
+                self.resolve_expr(&mut match_expr.value)?;
+
+                for arm in &mut match_expr.arms {
+                    self.resolve_pattern(&mut arm.pattern)?;
+                    let bindings = collect_bindings(&arm.pattern)?;
+
+                    // The bound variables are visible in the whole body of the arm
+                    let span = arm.pattern.span.to(arm.body.span);
+                    match &mut arm.body.kind {
+                        ExprKind::Compound(body) => {
+                            self.resolve_compound_declaring(body, &bindings)?
+                        }
+                        _ => {
+                            let body = &mut arm.body;
+                            let _ = self.resolve_in_scope(span, &bindings, |resolver| {
+                                resolver.resolve_expr(body)
+                            })?;
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+            ExprKind::Slice(array, start, end) => {
+                self.resolve_expr(array)?;
+                start
+                    .iter_mut()
+                    .chain(end)
+                    .try_for_each(|bound| self.resolve_expr(bound))
+            }
+            ExprKind::For(for_loop) => {
+                // The variable is not visible in the iterated value
+                self.resolve_expr(&mut for_loop.iterable)?;
+                self.resolve_compound_declaring(
+                    &mut for_loop.body,
+                    std::slice::from_ref(&for_loop.variable),
+                )
+            }
+            ExprKind::Compound(compound) | ExprKind::Loop(compound) => {
+                self.resolve_compound(compound)
+            }
+            ExprKind::If(condition, then_branch, else_branch) => {
+                self.resolve_expr(condition)?;
+                self.resolve_compound(then_branch)?;
+                else_branch
+                    .iter_mut()
+                    .try_for_each(|else_branch| self.resolve_compound(else_branch))
+            }
+            ExprKind::While(condition, body) => {
+                self.resolve_expr(condition)?;
+                self.resolve_compound(body)
+            }
+            ExprKind::Constant(_)
+            | ExprKind::Exit(_)
+            | ExprKind::Literal(_)
+            | ExprKind::Static(_) => Ok(()),
+        }
+    }
+}
+
+/// Returns the variables bound by `pattern`, which must have different names
+fn collect_bindings(pattern: &Pattern) -> Result<Vec<ArenaRef<Variable>>> {
+    let mut names = HashSet::new();
+    let mut bindings = Vec::new();
+    for (variable, span) in pattern.bindings() {
+        let name = variable.get().name.clone();
+        if !names.insert(name.clone()) {
+            bail!("{span}: Variable `{name}` is bound more than once in the same pattern");
+        }
+        bindings.push(variable.clone());
+    }
+
+    Ok(bindings)
+}
+
+/// Checks that all names inside of `ty`, which is used at `span`, refer to a declared struct or enum. Names of enums are
+/// replaced by enum types.
+fn resolve_type(sym: &Sym, ty: &mut Type, span: Span) -> Result<()> {
+    match ty {
+        Type::Struct(name) if sym.get_enum_by_name(name).is_some() => {
+            *ty = Type::Enum(name.clone());
+            Ok(())
+        }
+        Type::Struct(name) if sym.get_struct_by_name(name).is_none() => {
+            let candidates = sym.struct_names().into_iter().chain(sym.enum_names());
+            Err(unknown_name("type", name, candidates, span))
+        }
+        Type::Array(element_ty, _) | Type::Slice(element_ty) => resolve_type(sym, element_ty, span),
+        Type::Tuple(element_tys) => element_tys
+            .iter_mut()
+            .try_for_each(|element_ty| resolve_type(sym, element_ty, span)),
+        Type::Unit
+        | Type::Int
+        | Type::Str
+        | Type::Char
+        | Type::Bool
+        | Type::Never
+        | Type::Struct(_)
+        | Type::Enum(_) => Ok(()),
+    }
+}
+
+/// Binds a reference to a variant to the position of the variant in its enum
+fn resolve_variant(sym: &Sym, variant: &mut VariantRef) -> Result<()> {
+    let Some(enum_ref) = sym
+        .get_enum_by_name(&variant.enum_name)
+        .map(|enum_ref| enum_ref.clone())
+    else {
+        return Err(unknown_name(
+            "enum",
+            &variant.enum_name,
+            sym.enum_names(),
+            variant.span,
+        ));
+    };
+
+    let enum_sym = enum_ref.get();
+    let Some((index, _)) = enum_sym.variant(&variant.name) else {
+        let candidates = enum_sym.variants.iter().map(|variant| variant.name.clone());
+        match find_similar_name(&variant.name, candidates) {
+            Some(similar) => bail!(
+                "{}: Enum `{}` has no variant `{}`, did you mean `{similar}`?",
+                variant.span,
+                variant.enum_name,
+                variant.name
+            ),
+            None => bail!(
+                "{}: Enum `{}` has no variant `{}`",
+                variant.span,
+                variant.enum_name,
+                variant.name
+            ),
+        }
+    };
+
+    variant.index = Some(index);
+    Ok(())
+}
+
+/// Error for an unknown `name` at `span`, which is described by `description`. Suggests a similar name from `candidates`
+/// if there is one.
+fn unknown_name(
+    description: &str,
+    name: &str,
+    candidates: impl IntoIterator<Item = String>,
+    span: Span,
+) -> Error {
+    match find_similar_name(name, candidates) {
+        Some(similar) => {
+            anyhow!("{span}: Unknown {description} `{name}`, did you mean `{similar}`?")
+        }
+        None => anyhow!("{span}: Unknown {description} `{name}`"),
+    }
+}
+
+/// Finds the candidate that is most similar to `name`, if any is similar enough to be a likely typo
+fn find_similar_name(name: &str, candidates: impl IntoIterator<Item = String>) -> Option<String> {
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, &candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance, the number of single char insertions, deletions or substitutions needed to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+
+    // Distances between the processed prefix of `a` and every prefix of `b`
+    let mut distances: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut previous_diagonal = distances[0];
+        distances[0] = i + 1;
+
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous_diagonal + usize::from(a_char != b_char);
+            previous_diagonal = distances[j + 1];
+            distances[j + 1] = substitution
+                .min(distances[j] + 1)
+                .min(previous_diagonal + 1);
+        }
+    }
+
+    distances[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::semantic_analysis::name_resolution::edit_distance;
+
+    #[test]
+    pub fn edit_distances() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("hello", "hello"), 0);
+        assert_eq!(edit_distance("helo", "hello"), 1);
+        assert_eq!(edit_distance("wrold", "world"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
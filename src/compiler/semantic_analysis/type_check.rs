@@ -0,0 +1,925 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::compiler::parser::ast::{
+    Builtin, CompoundExpr, EnumDefinition, Expr, ExprKind, FieldRef, Literal, Pattern, PatternKind,
+    Root, StructDefinition, Type,
+};
+use crate::compiler::semantic_analysis::exhaustiveness;
+use crate::compiler::span::Span;
+use crate::compiler::symbol_table::{ConstValue, Sym};
+
+/// Checks the types of all expressions and annotates every expression with its type
+pub fn check(root: &mut Root, sym: &Sym) -> Result<()> {
+    for struct_def in &root.structs {
+        check_struct(struct_def, sym)?;
+    }
+    for enum_def in &root.enums {
+        check_enum(enum_def, sym)?;
+    }
+
+    for constant in &mut root.constants {
+        let (name, ty) = {
+            let sym = constant.sym.get();
+            (sym.name.clone(), sym.ty.clone())
+        };
+        check_global(
+            "constant",
+            &name,
+            ty,
+            constant.span,
+            &mut constant.value,
+            sym,
+        )?;
+    }
+
+    for static_def in &mut root.statics {
+        let (name, ty) = {
+            let sym = static_def.sym.get();
+            (sym.name.clone(), sym.ty.clone())
+        };
+        check_global(
+            "static",
+            &name,
+            ty,
+            static_def.span,
+            &mut static_def.value,
+            sym,
+        )?;
+    }
+
+    root.functions.iter_mut().try_for_each(|function| {
+        let (name, return_ty) = {
+            let sym = function.sym.get();
+            (sym.name.clone(), sym.return_ty.clone())
+        };
+        for param in &function.sym.get().params {
+            let param_ty = param.variable.get().ty();
+            if matches!(param_ty, Type::Unit | Type::Never) {
+                bail!(
+                    "{}: Parameters of type `{param_ty}` are not supported",
+                    param.span
+                );
+            }
+        }
+        if contains_slice(&return_ty, sym) {
+            bail!(
+                "{}: Function `{name}` cannot return `{return_ty}`, because a slice may point into memory of the function, return an array instead",
+                function.span
+            );
+        }
+
+        let mut checker = TypeChecker { return_ty, sym };
+        checker.check_compound(&mut function.compound)
+    })
+}
+
+/// Checks the field types of a struct, which must have a value and must not contain the struct itself
+fn check_struct(struct_def: &StructDefinition, sym: &Sym) -> Result<()> {
+    let struct_sym = struct_def.sym.get();
+
+    for field in &struct_sym.fields {
+        if matches!(field.ty, Type::Unit | Type::Never) {
+            bail!(
+                "{}: Fields of type `{}` are not supported",
+                field.span,
+                field.ty
+            );
+        }
+
+        if contains_named_type(&field.ty, &struct_sym.name, sym, &mut HashSet::new()) {
+            bail!(
+                "{}: Struct `{}` contains itself through field `{}`, so it would have an infinite size",
+                field.span,
+                struct_sym.name,
+                field.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the variants of an enum, which must exist and whose fields must have a value and must not contain the enum
+/// itself
+fn check_enum(enum_def: &EnumDefinition, sym: &Sym) -> Result<()> {
+    let enum_sym = enum_def.sym.get();
+
+    if enum_sym.variants.is_empty() {
+        bail!(
+            "{}: Enum `{}` has no variants, which is not supported",
+            enum_def.span,
+            enum_sym.name
+        );
+    }
+
+    for variant in &enum_sym.variants {
+        for ty in &variant.fields {
+            if matches!(ty, Type::Unit | Type::Never) {
+                bail!("{}: Fields of type `{ty}` are not supported", variant.span);
+            }
+
+            if contains_named_type(ty, &enum_sym.name, sym, &mut HashSet::new()) {
+                bail!(
+                    "{}: Enum `{}` contains itself through variant `{}`, so it would have an infinite size",
+                    variant.span,
+                    enum_sym.name,
+                    variant.name
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a value of type `ty` contains a value of the struct or enum called `name`. `visited` contains the structs
+/// and enums that were already searched.
+fn contains_named_type(ty: &Type, name: &str, sym: &Sym, visited: &mut HashSet<String>) -> bool {
+    match ty {
+        Type::Struct(other) | Type::Enum(other) if other == name => true,
+        Type::Struct(other) => {
+            if !visited.insert(other.clone()) {
+                return false;
+            }
+
+            let struct_ref = sym.get_struct(other);
+            let struct_sym = struct_ref.get();
+            struct_sym
+                .fields
+                .iter()
+                .any(|field| contains_named_type(&field.ty, name, sym, visited))
+        }
+        Type::Enum(other) => {
+            if !visited.insert(other.clone()) {
+                return false;
+            }
+
+            let enum_ref = sym.get_enum(other);
+            let enum_sym = enum_ref.get();
+            enum_sym
+                .variants
+                .iter()
+                .flat_map(|variant| &variant.fields)
+                .any(|field_ty| contains_named_type(field_ty, name, sym, visited))
+        }
+        // The elements of a slice are stored outside of it
+        Type::Array(element_ty, _) => contains_named_type(element_ty, name, sym, visited),
+        Type::Tuple(element_tys) => element_tys
+            .iter()
+            .any(|element_ty| contains_named_type(element_ty, name, sym, visited)),
+        _ => false,
+    }
+}
+
+/// Checks the value of a constant or static, which is described by `description` in error messages
+fn check_global(
+    description: &str,
+    name: &str,
+    declared_ty: Type,
+    span: Span,
+    value: &mut Expr,
+    sym: &Sym,
+) -> Result<()> {
+    if !is_supported_global_type(&declared_ty, sym) {
+        bail!("{span}: Type `{declared_ty}` is not supported for {description} `{name}`");
+    }
+
+    // The value is a single expression, so it cannot contain a `return`
+    let mut checker = TypeChecker {
+        return_ty: declared_ty.clone(),
+        sym,
+    };
+    let value_ty = checker.check_expr(value)?;
+
+    if !value_ty.coerces_to(&declared_ty) {
+        bail!(
+            "{}: Mismatched types in {description} `{name}`: expected `{declared_ty}`, found `{value_ty}`",
+            value.span
+        );
+    }
+
+    Ok(())
+}
+
+/// Whether values of this type can be evaluated at compile time, which is required for constants and statics. Values of
+/// enums are always built at runtime.
+fn is_supported_global_type(ty: &Type, sym: &Sym) -> bool {
+    match ty {
+        Type::Int | Type::Str | Type::Char | Type::Bool => true,
+        Type::Array(element_ty, _) => is_supported_global_type(element_ty, sym),
+        Type::Struct(name) => sym
+            .get_struct(name)
+            .get()
+            .fields
+            .iter()
+            .all(|field| is_supported_global_type(&field.ty, sym)),
+        Type::Tuple(element_tys) => element_tys
+            .iter()
+            .all(|element_ty| is_supported_global_type(element_ty, sym)),
+        Type::Unit | Type::Never | Type::Slice(_) | Type::Enum(_) => false,
+    }
+}
+
+/// Whether a value of type `ty` contains a slice, which may point into the memory of the function that created it.
+fn contains_slice(ty: &Type, sym: &Sym) -> bool {
+    match ty {
+        Type::Slice(_) => true,
+        Type::Array(element_ty, _) => contains_slice(element_ty, sym),
+        Type::Struct(name) => sym
+            .get_struct(name)
+            .get()
+            .fields
+            .iter()
+            .any(|field| contains_slice(&field.ty, sym)),
+        Type::Enum(name) => sym
+            .get_enum(name)
+            .get()
+            .variants
+            .iter()
+            .flat_map(|variant| &variant.fields)
+            .any(|field_ty| contains_slice(field_ty, sym)),
+        Type::Tuple(element_tys) => element_tys
+            .iter()
+            .any(|element_ty| contains_slice(element_ty, sym)),
+        _ => false,
+    }
+}
+
+struct TypeChecker<'a> {
+    /// Return type of the function that is currently checked
+    return_ty: Type,
+    sym: &'a Sym,
+}
+
+impl TypeChecker<'_> {
+    fn check_compound(&mut self, compound: &mut CompoundExpr) -> Result<()> {
+        compound
+            .expressions
+            .iter_mut()
+            .try_for_each(|expr| self.check_expr(expr).map(|_| ()))
+    }
+
+    fn check_condition(&mut self, condition: &mut Expr) -> Result<()> {
+        let condition_ty = self.check_expr(condition)?;
+
+        if !condition_ty.coerces_to(&Type::Bool) {
+            bail!(
+                "{}: Mismatched types in condition: expected `bool`, found `{condition_ty}`",
+                condition.span
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Checks that both operands of a comparison have the same type, which can be compared. Returns that type.
+    fn check_comparison(&mut self, lhs: &mut Expr, rhs: &mut Expr, span: Span) -> Result<Type> {
+        let lhs_ty = self.check_expr(lhs)?;
+        let rhs_ty = self.check_expr(rhs)?;
+
+        if !rhs_ty.coerces_to(&lhs_ty) && !lhs_ty.coerces_to(&rhs_ty) {
+            bail!("{span}: Mismatched types in comparison: expected `{lhs_ty}`, found `{rhs_ty}`");
+        }
+
+        let operand_ty = if lhs_ty == Type::Never {
+            rhs_ty
+        } else {
+            lhs_ty
+        };
+        if !matches!(
+            operand_ty,
+            Type::Int | Type::Char | Type::Bool | Type::Never
+        ) {
+            bail!("{span}: Values of type `{operand_ty}` cannot be compared");
+        }
+
+        Ok(operand_ty)
+    }
+
+    /// Checks that values of type `element_ty` can be stored in an array
+    fn check_element_ty(&self, element_ty: &Type, span: Span) -> Result<()> {
+        if *element_ty == Type::Unit {
+            bail!("{span}: Arrays of type `()` are not supported");
+        }
+
+        Ok(())
+    }
+
+    /// Checks an expression that has to be an `int`, which is described by `description` in error messages
+    fn check_int(&mut self, expr: &mut Expr, description: &str) -> Result<Type> {
+        let ty = self.check_expr(expr)?;
+
+        if !ty.coerces_to(&Type::Int) {
+            bail!(
+                "{}: Mismatched types in {description}: expected `int`, found `{ty}`",
+                expr.span
+            );
+        }
+
+        Ok(ty)
+    }
+
+    /// Checks an expression that has to be an array or slice and returns the type of its elements, or `None` if it
+    /// diverges. `action` describes what is done with the value in error messages.
+    fn check_elements(&mut self, expr: &mut Expr, action: &str) -> Result<Option<Type>> {
+        let ty = self.check_expr(expr)?;
+
+        if ty == Type::Never {
+            return Ok(None);
+        }
+
+        ty.element_ty()
+            .map(|element_ty| Some(element_ty.clone()))
+            .ok_or_else(|| anyhow!("{}: Cannot {action} a value of type `{ty}`", expr.span))
+    }
+
+    /// Resolves the field referenced by `field_ref` of a value of type `ty` and returns the type of the field
+    fn check_field(&self, ty: &Type, field_ref: &mut FieldRef, span: Span) -> Result<Type> {
+        if let Type::Tuple(element_tys) = ty {
+            let Some((index, element_ty)) = field_ref
+                .name
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| Some((index, element_tys.get(index)?)))
+            else {
+                bail!(
+                    "{}: Tuple `{ty}` has no field `{}`",
+                    field_ref.span,
+                    field_ref.name
+                );
+            };
+
+            field_ref.index = Some(index as u32);
+            return Ok(element_ty.clone());
+        }
+
+        let Type::Struct(name) = ty else {
+            bail!("{span}: Values of type `{ty}` have no fields");
+        };
+
+        let struct_ref = self.sym.get_struct(name);
+        let struct_sym = struct_ref.get();
+        let Some((index, field)) = struct_sym.field(&field_ref.name) else {
+            bail!(
+                "{}: Struct `{name}` has no field `{}`",
+                field_ref.span,
+                field_ref.name
+            );
+        };
+
+        field_ref.index = Some(index);
+        Ok(field.ty.clone())
+    }
+
+    /// Checks that `pattern` can match values of type `ty` and sets the types of the variables it binds
+    fn check_pattern(&self, pattern: &mut Pattern, ty: &Type) -> Result<()> {
+        let span = pattern.span;
+
+        match &mut pattern.kind {
+            PatternKind::Wildcard => {}
+            PatternKind::Binding(variable) => variable.get_mut().ty = Some(ty.clone()),
+            PatternKind::Literal(value) => {
+                let value_ty = match value {
+                    ConstValue::Int(_) => Type::Int,
+                    ConstValue::Char(_) => Type::Char,
+                    ConstValue::Bool(_) => Type::Bool,
+                    other => unreachable!("`{other}` to not be parsed as a pattern"),
+                };
+
+                if !ty.coerces_to(&value_ty) {
+                    bail!(
+                        "{span}: Mismatched types in pattern: expected `{ty}`, found `{value_ty}`"
+                    );
+                }
+            }
+            PatternKind::Variant(variant, fields) => {
+                let variant_ty = Type::Enum(variant.enum_name.clone());
+                if !ty.coerces_to(&variant_ty) {
+                    bail!("{span}: Mismatched types in pattern: expected `{ty}`, found `{variant_ty}`");
+                }
+
+                let field_tys = self.variant_field_tys(variant.enum_name.as_str(), variant.index());
+                if fields.len() != field_tys.len() {
+                    bail!(
+                        "{span}: Variant `{variant}` expects {}, got {}",
+                        count(field_tys.len(), "field"),
+                        fields.len()
+                    );
+                }
+
+                for (field, field_ty) in fields.iter_mut().zip(&field_tys) {
+                    self.check_pattern(field, field_ty)?;
+                }
+            }
+            PatternKind::Tuple(elements) => {
+                let element_tys = match ty {
+                    // Every element of a value that diverges diverges as well
+                    Type::Never => vec![Type::Never; elements.len()],
+                    Type::Tuple(element_tys) if element_tys.len() == elements.len() => {
+                        element_tys.clone()
+                    }
+                    _ => bail!(
+                        "{span}: Mismatched types in pattern: expected `{ty}`, found a tuple of {} elements",
+                        elements.len()
+                    ),
+                };
+
+                for (element, element_ty) in elements.iter_mut().zip(&element_tys) {
+                    self.check_pattern(element, element_ty)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the types of the fields of the variant at position `index` of the enum called `enum_name`
+    fn variant_field_tys(&self, enum_name: &str, index: u32) -> Vec<Type> {
+        let enum_ref = self.sym.get_enum(enum_name);
+        let field_tys = enum_ref.get().variants[index as usize].fields.clone();
+        field_tys
+    }
+
+    /// Checks an expression, annotates it with its type and returns that type
+    fn check_expr(&mut self, expr: &mut Expr) -> Result<Type> {
+        let span = expr.span;
+
+        let ty = match &mut expr.kind {
+            ExprKind::Assign(assignment) => {
+                let value_ty = self.check_expr(&mut assignment.value)?;
+                let mut target_ty = assignment.target().get().ty.clone();
+                let mut target = assignment.name.clone();
+                for field_ref in &mut assignment.fields {
+                    target_ty = self.check_field(&target_ty, field_ref, span)?;
+                    target = format!("{target}.{}", field_ref.name);
+                }
+
+                if !value_ty.coerces_to(&target_ty) {
+                    bail!(
+                        "{}: Mismatched types in assignment to `{target}`: expected `{target_ty}`, found `{value_ty}`",
+                        assignment.value.span
+                    );
+                }
+
+                if value_ty == Type::Never {
+                    Type::Never
+                } else {
+                    Type::Unit
+                }
+            }
+            ExprKind::Binary(lhs, op, rhs) if !op.is_comparison() => {
+                for operand in [lhs, rhs] {
+                    let operand_ty = self.check_expr(operand)?;
+
+                    if !operand_ty.coerces_to(&Type::Int) {
+                        bail!(
+                            "{}: Operator `{op}` expects operands of type `int`, found `{operand_ty}`",
+                            operand.span
+                        );
+                    }
+                }
+
+                Type::Int
+            }
+            ExprKind::Binary(lhs, _, rhs) => {
+                self.check_comparison(lhs, rhs, span)?;
+                Type::Bool
+            }
+            ExprKind::BuiltinCall(Builtin::Assert, args) => {
+                let [condition] = args.as_mut_slice() else {
+                    bail!(
+                        "{span}: `assert` expects exactly one argument, got {}",
+                        args.len()
+                    );
+                };
+
+                self.check_condition(condition)?;
+                Type::Unit
+            }
+            ExprKind::BuiltinCall(Builtin::AssertEq, args) => {
+                let [lhs, rhs] = args.as_mut_slice() else {
+                    bail!(
+                        "{span}: `assert_eq` expects exactly two arguments, got {}",
+                        args.len()
+                    );
+                };
+
+                self.check_comparison(lhs, rhs, span)?;
+                Type::Unit
+            }
+            ExprKind::BuiltinCall(
+                builtin @ (Builtin::WrappingAdd | Builtin::CheckedAdd | Builtin::SaturatingAdd),
+                args,
+            ) => {
+                if args.len() != 2 {
+                    bail!(
+                        "{span}: `{}` expects exactly two arguments, got {}",
+                        builtin.name(),
+                        args.len()
+                    );
+                }
+
+                for arg in args {
+                    let arg_ty = self.check_expr(arg)?;
+
+                    if !arg_ty.coerces_to(&Type::Int) {
+                        bail!(
+                            "{}: Mismatched types in argument of `{}`: expected `int`, found `{arg_ty}`",
+                            arg.span,
+                            builtin.name()
+                        );
+                    }
+                }
+
+                Type::Int
+            }
+            ExprKind::BuiltinCall(Builtin::Panic, args) => {
+                let [message] = args.as_mut_slice() else {
+                    bail!(
+                        "{span}: `panic` expects exactly one argument, got {}",
+                        args.len()
+                    );
+                };
+
+                let message_ty = self.check_expr(message)?;
+                if !message_ty.coerces_to(&Type::Str) {
+                    bail!(
+                        "{}: Mismatched types in argument of `panic`: expected `str`, found `{message_ty}`",
+                        message.span
+                    );
+                }
+
+                Type::Never
+            }
+            ExprKind::BuiltinCall(builtin, args) => {
+                match (*builtin, args.len()) {
+                    (Builtin::Print, 1) | (Builtin::Println, 0 | 1) => {}
+                    (Builtin::Print, n) => {
+                        bail!("{span}: `print` expects exactly one argument, got {n}")
+                    }
+                    (Builtin::Println, n) => {
+                        bail!("{span}: `println` expects at most one argument, got {n}")
+                    }
+                    (other, _) => unreachable!("`{}` to be checked separately", other.name()),
+                }
+
+                for arg in args {
+                    let arg_ty = self.check_expr(arg)?;
+
+                    if !matches!(
+                        arg_ty,
+                        Type::Int | Type::Str | Type::Char | Type::Bool | Type::Never
+                    ) {
+                        bail!("{}: Values of type `{arg_ty}` cannot be printed", arg.span);
+                    }
+                }
+
+                Type::Unit
+            }
+            ExprKind::Cast(value, target_ty) => {
+                let value_ty = self.check_expr(value)?;
+
+                match (value_ty, &*target_ty) {
+                    (Type::Int | Type::Char | Type::Bool | Type::Never, Type::Int) => {}
+                    (Type::Int | Type::Char | Type::Never, Type::Char) => {}
+                    (value_ty, target_ty) => {
+                        bail!("{span}: Cannot cast a value of type `{value_ty}` to `{target_ty}`")
+                    }
+                }
+
+                target_ty.clone()
+            }
+            ExprKind::Compound(compound) => {
+                self.check_compound(compound)?;
+                Type::Unit
+            }
+            ExprKind::Array(elements) => {
+                let Some((first, rest)) = elements.split_first_mut() else {
+                    bail!("{span}: Empty array literals are not supported, use a repeat literal like `[0; 0]` instead");
+                };
+
+                let mut element_ty = self.check_expr(first)?;
+                for element in rest {
+                    let ty = self.check_expr(element)?;
+
+                    if element_ty == Type::Never {
+                        element_ty = ty;
+                    } else if !ty.coerces_to(&element_ty) {
+                        bail!(
+                            "{}: Mismatched types in array element: expected `{element_ty}`, found `{ty}`",
+                            element.span
+                        );
+                    }
+                }
+
+                // An array never completes if one of its elements diverges
+                if elements.iter().any(|element| element.ty() == Type::Never) {
+                    Type::Never
+                } else {
+                    self.check_element_ty(&element_ty, span)?;
+                    Type::Array(Box::new(element_ty), elements.len() as u32)
+                }
+            }
+            ExprKind::ArrayRepeat(element, len) => {
+                let element_ty = self.check_expr(element)?;
+
+                if element_ty == Type::Never {
+                    Type::Never
+                } else {
+                    self.check_element_ty(&element_ty, span)?;
+                    Type::Array(Box::new(element_ty), *len)
+                }
+            }
+            ExprKind::Index(array, index) => {
+                let element_ty = self.check_elements(array, "index into")?;
+                let index_ty = self.check_int(index, "index")?;
+
+                match element_ty {
+                    Some(element_ty) if index_ty != Type::Never => element_ty,
+                    _ => Type::Never,
+                }
+            }
+            ExprKind::Slice(array, start, end) => {
+                let element_ty = self.check_elements(array, "slice")?;
+
+                let mut bounds_diverge = false;
+                for bound in start.iter_mut().chain(end) {
+                    bounds_diverge |= self.check_int(bound, "range bound")? == Type::Never;
+                }
+
+                match element_ty {
+                    Some(element_ty) if !bounds_diverge => Type::Slice(Box::new(element_ty)),
+                    _ => Type::Never,
+                }
+            }
+            ExprKind::Len(array) => match self.check_elements(array, "get the length of")? {
+                Some(_) => Type::Int,
+                None => Type::Never,
+            },
+            ExprKind::For(for_loop) => {
+                let element_ty = self.check_elements(&mut for_loop.iterable, "iterate over")?;
+                for_loop.variable.get_mut().ty = Some(element_ty.clone().unwrap_or(Type::Never));
+                self.check_compound(&mut for_loop.body)?;
+
+                // A loop never completes if the iterated value diverges
+                match element_ty {
+                    Some(_) => Type::Unit,
+                    None => Type::Never,
+                }
+            }
+            ExprKind::Constant(constant) => constant.get().ty.clone(),
+            ExprKind::Exit(_) => Type::Never,
+            ExprKind::Field(value, field_ref) => {
+                let value_ty = self.check_expr(value)?;
+
+                if value_ty == Type::Never {
+                    Type::Never
+                } else {
+                    self.check_field(&value_ty, field_ref, span)?
+                }
+            }
+            ExprKind::FnCall(call) => {
+                let (name, param_tys, return_ty) = {
+                    let function = call.function().get();
+                    let return_ty = function.return_ty.clone();
+                    (function.name.clone(), function.param_tys(), return_ty)
+                };
+                if call.args.len() != param_tys.len() {
+                    bail!(
+                        "{span}: Function `{name}` expects {}, got {}",
+                        count(param_tys.len(), "argument"),
+                        call.args.len()
+                    );
+                }
+
+                for (arg, param_ty) in call.args.iter_mut().zip(&param_tys) {
+                    let arg_ty = self.check_expr(arg)?;
+                    if !arg_ty.coerces_to(param_ty) {
+                        bail!(
+                            "{}: Mismatched types in argument of `{name}`: expected `{param_ty}`, found `{arg_ty}`",
+                            arg.span
+                        );
+                    }
+                }
+
+                // A function is never called if one of its arguments diverges
+                if call.args.iter().any(|arg| arg.ty() == Type::Never) {
+                    Type::Never
+                } else {
+                    return_ty
+                }
+            }
+            ExprKind::If(condition, then_branch, else_branch) => {
+                self.check_condition(condition)?;
+                self.check_compound(then_branch)?;
+                if let Some(else_branch) = else_branch {
+                    self.check_compound(else_branch)?;
+                }
+
+                Type::Unit
+            }
+            ExprKind::Let(let_expr) => {
+                let value_ty = self.check_expr(&mut let_expr.value)?;
+
+                let ty = match &let_expr.ty {
+                    Some(declared_ty) if !value_ty.coerces_to(declared_ty) => bail!(
+                        "{}: Mismatched types in declaration of `{}`: expected `{declared_ty}`, found `{value_ty}`",
+                        let_expr.value.span,
+                        let_expr.pattern
+                    ),
+                    Some(declared_ty) => declared_ty.clone(),
+                    None => value_ty.clone(),
+                };
+                self.check_pattern(&mut let_expr.pattern, &ty)?;
+
+                // A declaration never completes if its initial value diverges
+                if value_ty == Type::Never {
+                    Type::Never
+                } else {
+                    if let Some(missing) =
+                        exhaustiveness::find_missing_pattern([&let_expr.pattern], &ty, self.sym)
+                    {
+                        bail!(
+                            "{}: Refutable pattern in `let`: `{missing}` is not covered",
+                            let_expr.pattern.span
+                        );
+                    }
+
+                    Type::Unit
+                }
+            }
+            ExprKind::Loop(body) => {
+                self.check_compound(body)?;
+                Type::Never
+            }
+            ExprKind::Static(static_ref) => static_ref.get().ty.clone(),
+            ExprKind::Struct(literal) => {
+                let struct_ty = Type::Struct(literal.name.clone());
+                let field_count = self.sym.get_struct(&literal.name).get().fields.len();
+                let mut initialized = vec![false; field_count];
+
+                for field_init in &mut literal.fields {
+                    let field_ty = self.check_field(&struct_ty, &mut field_init.field, span)?;
+                    let index = field_init.field.index() as usize;
+                    if initialized[index] {
+                        bail!(
+                            "{}: Field `{}` is initialized more than once",
+                            field_init.field.span,
+                            field_init.field.name
+                        );
+                    }
+                    initialized[index] = true;
+
+                    let value_ty = self.check_expr(&mut field_init.value)?;
+                    if !value_ty.coerces_to(&field_ty) {
+                        bail!(
+                            "{}: Mismatched types in field `{}`: expected `{field_ty}`, found `{value_ty}`",
+                            field_init.value.span,
+                            field_init.field.name
+                        );
+                    }
+                }
+
+                if let Some(index) = initialized.iter().position(|initialized| !initialized) {
+                    let struct_ref = self.sym.get_struct(&literal.name);
+                    bail!(
+                        "{span}: Missing field `{}` in literal of struct `{}`",
+                        struct_ref.get().fields[index].name,
+                        literal.name
+                    );
+                }
+
+                // A struct literal never completes if one of its field values diverges
+                if literal
+                    .fields
+                    .iter()
+                    .any(|field_init| field_init.value.ty() == Type::Never)
+                {
+                    Type::Never
+                } else {
+                    struct_ty
+                }
+            }
+            ExprKind::Tuple(elements) => {
+                let mut element_tys = Vec::new();
+                for element in elements.iter_mut() {
+                    let element_ty = self.check_expr(element)?;
+                    if element_ty == Type::Unit {
+                        bail!(
+                            "{}: Tuples with elements of type `()` are not supported",
+                            element.span
+                        );
+                    }
+                    element_tys.push(element_ty);
+                }
+
+                // A tuple never completes if one of its elements diverges
+                if element_tys.contains(&Type::Never) {
+                    Type::Never
+                } else {
+                    Type::Tuple(element_tys)
+                }
+            }
+            ExprKind::Variant(literal) => {
+                let variant = &literal.variant;
+                let field_tys = self.variant_field_tys(&variant.enum_name, variant.index());
+                if literal.args.len() != field_tys.len() {
+                    bail!(
+                        "{span}: Variant `{variant}` expects {}, got {}",
+                        count(field_tys.len(), "field"),
+                        literal.args.len()
+                    );
+                }
+
+                for (arg, field_ty) in literal.args.iter_mut().zip(&field_tys) {
+                    let arg_ty = self.check_expr(arg)?;
+                    if !arg_ty.coerces_to(field_ty) {
+                        bail!(
+                            "{}: Mismatched types in argument of `{variant}`: expected `{field_ty}`, found `{arg_ty}`",
+                            arg.span
+                        );
+                    }
+                }
+
+                // A variant never completes if one of its field values diverges
+                if literal.args.iter().any(|arg| arg.ty() == Type::Never) {
+                    Type::Never
+                } else {
+                    Type::Enum(variant.enum_name.clone())
+                }
+            }
+            ExprKind::Match(match_expr) => {
+                let value_ty = self.check_expr(&mut match_expr.value)?;
+
+                // The type of the arms that do not diverge
+                let mut arms_ty: Option<Type> = None;
+                for arm in &mut match_expr.arms {
+                    self.check_pattern(&mut arm.pattern, &value_ty)?;
+
+                    let arm_ty = self.check_expr(&mut arm.body)?;
+                    match &arms_ty {
+                        _ if arm_ty == Type::Never => {}
+                        Some(expected_ty) if arm_ty != *expected_ty => bail!(
+                            "{}: Mismatched types in match arm: expected `{expected_ty}`, found `{arm_ty}`",
+                            arm.body.span
+                        ),
+                        Some(_) => {}
+                        None => arms_ty = Some(arm_ty),
+                    }
+                }
+
+                // Every pattern is accepted for a value that diverges, because no arm is ever reached
+                if value_ty == Type::Never {
+                    Type::Never
+                } else {
+                    let patterns = match_expr.arms.iter().map(|arm| &arm.pattern);
+                    if let Some(missing) =
+                        exhaustiveness::find_missing_pattern(patterns, &value_ty, self.sym)
+                    {
+                        bail!("{span}: Non-exhaustive patterns in `match`: `{missing}` is not covered");
+                    }
+
+                    arms_ty.unwrap_or(Type::Never)
+                }
+            }
+            ExprKind::Variable(variable_ref) => variable_ref.variable().get().ty(),
+            ExprKind::While(condition, body) => {
+                self.check_condition(condition)?;
+                self.check_compound(body)?;
+                Type::Unit
+            }
+            ExprKind::Literal(literal) => match literal {
+                Literal::Int(_) => Type::Int,
+                Literal::Str(_) => Type::Str,
+                Literal::Char(_) => Type::Char,
+                Literal::Bool(_) => Type::Bool,
+            },
+            ExprKind::Return(value) => {
+                let value_ty = self.check_expr(value)?;
+
+                if !value_ty.coerces_to(&self.return_ty) {
+                    bail!(
+                        "{}: Mismatched return type: expected `{}`, found `{value_ty}`",
+                        value.span,
+                        self.return_ty
+                    );
+                }
+
+                Type::Never
+            }
+        };
+
+        expr.ty = Some(ty.clone());
+        Ok(ty)
+    }
+}
+
+/// Describes a number of things called `noun` like `1 argument` or `2 arguments`
+fn count(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {noun}"),
+        _ => format!("{count} {noun}s"),
+    }
+}
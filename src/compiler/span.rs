@@ -0,0 +1,95 @@
+use std::fmt::{Display, Formatter};
+
+/// A position inside of a source file. Both line and column start at 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Location {
+    pub fn new(line: u32, column: u32) -> Self {
+        Self { line, column }
+    }
+}
+
+impl Default for Location {
+    fn default() -> Self {
+        Self::new(1, 1)
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A range inside of a source file. `end` is the location directly after the last char of the range.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+impl Span {
+    pub fn new(start: Location, end: Location) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns a span from the start of `self` to the end of `other`
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start, other.end)
+    }
+
+    /// Returns the part of the source code `src` covered by this span
+    pub fn source_text<'a>(&self, src: &'a str) -> &'a str {
+        let mut location = Location::default();
+        let mut start = src.len();
+
+        for (index, char) in src.char_indices() {
+            if location == self.start {
+                start = index;
+            }
+            if location == self.end {
+                return &src[start.min(index)..index];
+            }
+
+            if char == '\n' {
+                location.line += 1;
+                location.column = 1;
+            } else {
+                location.column += 1;
+            }
+        }
+
+        &src[start..]
+    }
+
+    /// Whether `other` lies completely inside of this span
+    pub fn contains(&self, other: Span) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::span::{Location, Span};
+
+    #[test]
+    pub fn source_text() {
+        let src = "fun main() {\n\tassert(1 < 2)\n}";
+
+        let condition = Span::new(Location::new(2, 9), Location::new(2, 14));
+        assert_eq!(condition.source_text(src), "1 < 2");
+
+        let whole = Span::new(Location::new(1, 1), Location::new(3, 2));
+        assert_eq!(whole.source_text(src), src);
+    }
+}
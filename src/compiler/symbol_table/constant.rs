@@ -0,0 +1,154 @@
+use std::cell::Ref;
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+
+use crate::compiler::parser::ast;
+use crate::compiler::ref_arena::ArenaRef;
+use crate::compiler::symbol_table::Sym;
+
+#[derive(Debug)]
+pub struct Constant {
+    /// name must be unique among all constants and statics
+    pub name: String,
+    pub ty: ast::Type,
+    /// The value is `None` until it is evaluated during semantic analysis
+    pub value: Option<ConstValue>,
+}
+
+impl Constant {
+    pub fn new(name: String, ty: ast::Type) -> Self {
+        Self {
+            name,
+            ty,
+            value: None,
+        }
+    }
+
+    /// Returns the value of this constant. Must only be called after constant evaluation.
+    pub fn value(&self) -> &ConstValue {
+        self.value
+            .as_ref()
+            .expect("constant to be evaluated during semantic analysis")
+    }
+}
+
+/// A value that is known at compile time
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConstValue {
+    Unit,
+    Int(i32),
+    Str(String),
+    Char(char),
+    Bool(bool),
+    /// The elements of an array of type `[element_ty; elements.len()]`
+    Array(ast::Type, Vec<ConstValue>),
+    /// The names and values of the fields of a struct in the order of their declaration
+    Struct(String, Vec<(String, ConstValue)>),
+    /// The elements of a tuple like `(1, true)`
+    Tuple(Vec<ConstValue>),
+    /// A variant of an enum like `Shape::Circle(3)`
+    Variant {
+        enum_name: String,
+        name: String,
+        /// Position of the variant in its enum
+        index: u32,
+        fields: Vec<ConstValue>,
+    },
+}
+
+impl ConstValue {
+    /// Whether this value is or contains a value of an enum
+    pub fn contains_variant(&self) -> bool {
+        match self {
+            ConstValue::Variant { .. } => true,
+            ConstValue::Array(_, elements) | ConstValue::Tuple(elements) => {
+                elements.iter().any(ConstValue::contains_variant)
+            }
+            ConstValue::Struct(_, fields) => {
+                fields.iter().any(|(_, value)| value.contains_variant())
+            }
+            ConstValue::Unit
+            | ConstValue::Int(_)
+            | ConstValue::Str(_)
+            | ConstValue::Char(_)
+            | ConstValue::Bool(_) => false,
+        }
+    }
+}
+
+impl Display for ConstValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstValue::Unit => write!(f, "()"),
+            ConstValue::Int(value) => write!(f, "{value}"),
+            ConstValue::Str(value) => write!(f, "{value:?}"),
+            ConstValue::Char(value) => write!(f, "{value:?}"),
+            ConstValue::Bool(value) => write!(f, "{value}"),
+            ConstValue::Array(_, elements) => {
+                let elements: Vec<_> = elements.iter().map(ToString::to_string).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            ConstValue::Struct(name, fields) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(field, value)| format!("{field}: {value}"))
+                    .collect();
+                write!(f, "{name} {{ {} }}", fields.join(", "))
+            }
+            ConstValue::Tuple(elements) => write!(f, "({})", ast::format_tuple(elements)),
+            ConstValue::Variant {
+                enum_name,
+                name,
+                fields,
+                ..
+            } => {
+                write!(f, "{enum_name}::{name}")?;
+                if !fields.is_empty() {
+                    let fields: Vec<_> = fields.iter().map(ToString::to_string).collect();
+                    write!(f, "({})", fields.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Sym {
+    /// Tries to find a constant with specified name. Returns `None` if no constant was found.
+    pub fn get_constant_by_name<'a>(
+        &'a self,
+        name: &str,
+    ) -> Option<impl Deref<Target = ArenaRef<Constant>> + 'a> {
+        Ref::filter_map(self.inner.borrow(), |inner_sym| {
+            inner_sym.constant_name_lookup.get(name)
+        })
+        .ok()
+    }
+
+    /// Returns the names of all constants in the symbol table
+    pub fn constant_names(&self) -> Vec<String> {
+        self.inner
+            .borrow()
+            .constant_name_lookup
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Adds a new constant to the symbol table. It can only be found by its name after it was declared.
+    pub fn add_constant(&self, constant: Constant) -> ArenaRef<Constant> {
+        self.inner.borrow().constants.insert(constant)
+    }
+
+    /// Makes a constant findable by its name. Its name must not be declared yet.
+    pub fn declare_constant(&self, constant: &ArenaRef<Constant>) {
+        let name = constant.get().name.clone();
+        let previous = self
+            .inner
+            .borrow_mut()
+            .constant_name_lookup
+            .insert(name, constant.clone());
+
+        assert!(previous.is_none(), "constants to be declared only once");
+    }
+}
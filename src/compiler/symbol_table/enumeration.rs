@@ -0,0 +1,88 @@
+use std::cell::Ref;
+use std::ops::Deref;
+
+use crate::compiler::parser::ast;
+use crate::compiler::ref_arena::ArenaRef;
+use crate::compiler::span::Span;
+use crate::compiler::symbol_table::Sym;
+
+/// An enum type like `enum Shape { Circle(int), Rect(int, int), Empty }`, whose variants are stored in the order of
+/// their declaration
+#[derive(Debug)]
+pub struct Enum {
+    /// name must be unique among all structs and enums
+    pub name: String,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Debug)]
+pub struct Variant {
+    pub name: String,
+    /// Types of the unnamed fields of the variant, which is empty for variants like `Empty`
+    pub fields: Vec<ast::Type>,
+    /// Span of the variant name
+    pub span: Span,
+}
+
+impl Enum {
+    pub fn new(name: String, variants: Vec<Variant>) -> Self {
+        Self { name, variants }
+    }
+
+    /// Returns the position of the variant called `name` and the variant itself. Returns `None` if there is no such
+    /// variant.
+    pub fn variant(&self, name: &str) -> Option<(u32, &Variant)> {
+        self.variants
+            .iter()
+            .enumerate()
+            .find(|(_, variant)| variant.name == name)
+            .map(|(index, variant)| (index as u32, variant))
+    }
+}
+
+impl Sym {
+    /// Tries to find an enum with specified name. Returns `None` if no enum was found.
+    pub fn get_enum_by_name<'a>(
+        &'a self,
+        name: &str,
+    ) -> Option<impl Deref<Target = ArenaRef<Enum>> + 'a> {
+        Ref::filter_map(self.inner.borrow(), |inner_sym| {
+            inner_sym.enum_name_lookup.get(name)
+        })
+        .ok()
+    }
+
+    /// Returns the enum called `name`. Must only be called after all type names were resolved.
+    pub fn get_enum(&self, name: &str) -> ArenaRef<Enum> {
+        self.get_enum_by_name(name)
+            .map(|enum_ref| enum_ref.clone())
+            .expect("enum names to be resolved during name resolution")
+    }
+
+    /// Returns the names of all enums in the symbol table
+    pub fn enum_names(&self) -> Vec<String> {
+        self.inner
+            .borrow()
+            .enum_name_lookup
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Adds a new enum to the symbol table. It can only be found by its name after it was declared.
+    pub fn add_enum(&self, enum_def: Enum) -> ArenaRef<Enum> {
+        self.inner.borrow().enums.insert(enum_def)
+    }
+
+    /// Makes an enum findable by its name. Its name must not be declared yet.
+    pub fn declare_enum(&self, enum_ref: &ArenaRef<Enum>) {
+        let name = enum_ref.get().name.clone();
+        let previous = self
+            .inner
+            .borrow_mut()
+            .enum_name_lookup
+            .insert(name, enum_ref.clone());
+
+        assert!(previous.is_none(), "enums to be declared only once");
+    }
+}
@@ -1,23 +1,47 @@
 use std::cell::Ref;
 use std::ops::Deref;
 
-use anyhow::bail;
-use anyhow::Result;
-
 use crate::compiler::parser::ast;
 use crate::compiler::ref_arena::ArenaRef;
-use crate::compiler::symbol_table::{InnerSym, Sym};
+use crate::compiler::span::Span;
+use crate::compiler::symbol_table::{InnerSym, Sym, Variable};
 
 #[derive(Debug)]
 pub struct Function {
     /// name must be unique
     pub name: String,
+    /// Parameters in the order of their declaration
+    pub params: Vec<Parameter>,
     pub return_ty: ast::Type,
+    /// Whether this is a `const fun`, which can be evaluated at compile time
+    pub is_const: bool,
+}
+
+/// A parameter of a function like `values: [int]`, which is a variable initialized with the argument of a call
+#[derive(Debug)]
+pub struct Parameter {
+    /// The variable holding the argument, its type is always declared
+    pub variable: ArenaRef<Variable>,
+    /// Span of the parameter name
+    pub span: Span,
 }
 
 impl Function {
-    pub fn new(name: String, return_ty: ast::Type) -> Self {
-        Self { name, return_ty }
+    pub fn new(name: String, params: Vec<Parameter>, return_ty: ast::Type, is_const: bool) -> Self {
+        Self {
+            name,
+            params,
+            return_ty,
+            is_const,
+        }
+    }
+
+    /// Returns the types of the parameters in the order of their declaration
+    pub fn param_tys(&self) -> Vec<ast::Type> {
+        self.params
+            .iter()
+            .map(|param| param.variable.get().ty())
+            .collect()
     }
 }
 impl Sym {
@@ -32,27 +56,35 @@ impl Sym {
         .ok()
     }
 
-    /// Adds a new function to the symbol table. This can fail if a function is already defined.
-    pub fn add_function(&self, f: Function) -> Result<ArenaRef<Function>> {
-        self.inner.borrow_mut().add_function(f)
+    /// Returns the names of all functions in the symbol table
+    pub fn function_names(&self) -> Vec<String> {
+        self.inner
+            .borrow()
+            .function_name_lookup
+            .keys()
+            .cloned()
+            .collect()
     }
-}
 
-impl InnerSym {
-    fn add_function(&mut self, f: Function) -> Result<ArenaRef<Function>> {
-        if self.function_name_lookup.contains_key(&f.name) {
-            bail!("Redefinition of function `{}`", f.name);
-        }
-
-        let name = f.name.clone();
-
-        let fn_ref = self.functions.insert(f);
+    /// Adds a new function to the symbol table. It can only be found by its name after it was declared.
+    pub fn add_function(&self, f: Function) -> ArenaRef<Function> {
+        self.inner.borrow().functions.insert(f)
+    }
 
-        self.function_name_lookup.insert(name, fn_ref.clone());
+    /// Makes a function findable by its name. Its name must not be declared yet.
+    pub fn declare_function(&self, fn_ref: &ArenaRef<Function>) {
+        let name = fn_ref.get().name.clone();
+        let previous = self
+            .inner
+            .borrow_mut()
+            .function_name_lookup
+            .insert(name, fn_ref.clone());
 
-        Ok(fn_ref)
+        assert!(previous.is_none(), "functions to be declared only once");
     }
+}
 
+impl InnerSym {
     fn get_function_by_name<'a>(&'a self, name: &'_ str) -> Option<&'a ArenaRef<Function>> {
         self.function_name_lookup.get(name)
     }
@@ -1,14 +1,26 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::compiler::ref_arena::{Arena, ArenaRef};
 
+pub mod constant;
+pub mod enumeration;
 pub mod function;
+pub mod scope;
+pub mod static_variable;
+pub mod structure;
+pub mod variable;
+pub use constant::*;
+pub use enumeration::*;
 pub use function::*;
+pub use scope::*;
+pub use static_variable::*;
+pub use structure::*;
+pub use variable::*;
 
-/// A symbol table containing information about all functions.
+/// A symbol table containing information about all functions, constants, statics, structs, enums and variables.
+/// Variables are organized in a tree of scopes for every function body, which can be queried by location.
 /// This is a wrapper type for `InnerSym` which actually contains all the data.
 /// This is needed so its data can be immutably referenced from the AST and new entries can be added to it at the same time.
 #[derive(Clone)]
@@ -23,9 +35,27 @@ impl Sym {
     }
 }
 
+impl Default for Sym {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 struct InnerSym {
     functions: Arena<Function>,
     function_name_lookup: HashMap<String, ArenaRef<Function>>,
+    constants: Arena<Constant>,
+    constant_name_lookup: HashMap<String, ArenaRef<Constant>>,
+    statics: Arena<Static>,
+    static_name_lookup: HashMap<String, ArenaRef<Static>>,
+    structs: Arena<Struct>,
+    struct_name_lookup: HashMap<String, ArenaRef<Struct>>,
+    enums: Arena<Enum>,
+    enum_name_lookup: HashMap<String, ArenaRef<Enum>>,
+    variables: Arena<Variable>,
+    scopes: Arena<Scope>,
+    /// Scopes of all function bodies, in the order of their definition
+    root_scopes: Vec<ArenaRef<Scope>>,
 }
 
 impl InnerSym {
@@ -33,6 +63,17 @@ impl InnerSym {
         Self {
             functions: Arena::new(),
             function_name_lookup: HashMap::new(),
+            constants: Arena::new(),
+            constant_name_lookup: HashMap::new(),
+            statics: Arena::new(),
+            static_name_lookup: HashMap::new(),
+            structs: Arena::new(),
+            struct_name_lookup: HashMap::new(),
+            enums: Arena::new(),
+            enum_name_lookup: HashMap::new(),
+            variables: Arena::new(),
+            scopes: Arena::new(),
+            root_scopes: Vec::new(),
         }
     }
 }
@@ -0,0 +1,217 @@
+use crate::compiler::ref_arena::ArenaRef;
+use crate::compiler::span::{Location, Span};
+use crate::compiler::symbol_table::{Constant, Enum, Function, Static, Struct, Sym, Variable};
+
+/// A block scope, which is opened by a compound expression. The scopes of a function form a tree, whose root is the
+/// scope of the function body.
+///
+/// Scopes only hold local variables. Structs and enums can only be defined at the top level of a file, so they are
+/// visible everywhere and cannot be shadowed. They are in a separate namespace from variables, so a variable never
+/// hides a type of the same name.
+#[derive(Debug)]
+pub struct Scope {
+    /// The surrounding scope, it is `None` for the scope of a function body
+    pub parent: Option<ArenaRef<Scope>>,
+    /// Scopes of the compounds directly inside of this scope, in source order
+    pub children: Vec<ArenaRef<Scope>>,
+    /// Span of the compound that opens this scope
+    pub span: Span,
+    /// Variables declared directly inside of this scope, in the order of their declaration
+    pub variables: Vec<LocalVariable>,
+}
+
+impl Scope {
+    pub fn new(parent: Option<ArenaRef<Scope>>, span: Span) -> Self {
+        Self {
+            parent,
+            children: Vec::new(),
+            span,
+            variables: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LocalVariable {
+    pub variable: ArenaRef<Variable>,
+    /// Variables are visible from the end of their declaration on, so they are not visible in their own initial value
+    pub visible_from: Location,
+}
+
+/// Anything that can be referenced by its name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Symbol {
+    Function(ArenaRef<Function>),
+    Constant(ArenaRef<Constant>),
+    Static(ArenaRef<Static>),
+    Struct(ArenaRef<Struct>),
+    Enum(ArenaRef<Enum>),
+    Variable(ArenaRef<Variable>),
+}
+
+impl Symbol {
+    pub fn name(&self) -> String {
+        match self {
+            Symbol::Function(function) => function.get().name.clone(),
+            Symbol::Constant(constant) => constant.get().name.clone(),
+            Symbol::Static(static_ref) => static_ref.get().name.clone(),
+            Symbol::Struct(struct_ref) => struct_ref.get().name.clone(),
+            Symbol::Enum(enum_ref) => enum_ref.get().name.clone(),
+            Symbol::Variable(variable) => variable.get().name.clone(),
+        }
+    }
+}
+
+impl Sym {
+    /// Adds a new scope inside of `parent`, or the scope of a function body if there is no parent
+    pub fn add_scope(&self, parent: Option<&ArenaRef<Scope>>, span: Span) -> ArenaRef<Scope> {
+        let scope = self
+            .inner
+            .borrow()
+            .scopes
+            .insert(Scope::new(parent.cloned(), span));
+
+        match parent {
+            Some(parent) => parent.get_mut().children.push(scope.clone()),
+            None => self.inner.borrow_mut().root_scopes.push(scope.clone()),
+        }
+
+        scope
+    }
+
+    /// Declares a variable inside of `scope`, which is visible from `visible_from` until the end of the scope
+    pub fn declare_variable(
+        &self,
+        scope: &ArenaRef<Scope>,
+        variable: ArenaRef<Variable>,
+        visible_from: Location,
+    ) {
+        scope.get_mut().variables.push(LocalVariable {
+            variable,
+            visible_from,
+        });
+    }
+
+    /// Returns all variables that are visible at `location` inside of `scope`. Variables that are shadowed by a later
+    /// declaration or by a declaration in an inner scope are left out. The innermost and latest variables come first.
+    pub fn visible_variables(
+        &self,
+        scope: &ArenaRef<Scope>,
+        location: Location,
+    ) -> Vec<ArenaRef<Variable>> {
+        let mut visible: Vec<ArenaRef<Variable>> = Vec::new();
+        let mut current = Some(scope.clone());
+
+        while let Some(scope) = current {
+            let scope = scope.get();
+
+            for local in scope.variables.iter().rev() {
+                let name = &local.variable.get().name;
+                let is_shadowed = visible.iter().any(|other| other.get().name == *name);
+
+                if local.visible_from <= location && !is_shadowed {
+                    visible.push(local.variable.clone());
+                }
+            }
+
+            current = scope.parent.clone();
+        }
+
+        visible
+    }
+
+    /// Finds the variable called `name` that is visible at `location` inside of `scope`, considering shadowing
+    pub fn lookup_variable(
+        &self,
+        scope: &ArenaRef<Scope>,
+        name: &str,
+        location: Location,
+    ) -> Option<ArenaRef<Variable>> {
+        self.visible_variables(scope, location)
+            .into_iter()
+            .find(|variable| variable.get().name == name)
+    }
+
+    /// Returns the innermost scope that contains `span`, or `None` if `span` is not inside of a function body
+    pub fn scope_at(&self, span: Span) -> Option<ArenaRef<Scope>> {
+        let root_scopes = self.inner.borrow().root_scopes.clone();
+        let mut innermost = root_scopes
+            .into_iter()
+            .find(|scope| scope.get().span.contains(span))?;
+
+        loop {
+            let child = innermost
+                .get()
+                .children
+                .iter()
+                .find(|child| child.get().span.contains(span))
+                .cloned();
+
+            match child {
+                Some(child) => innermost = child,
+                None => return Some(innermost),
+            }
+        }
+    }
+
+    /// Returns all symbols that are visible at the start of `span`: the variables in scope, followed by all constants
+    /// and statics, all structs and enums and all functions, each group sorted by name. Constants and statics that are
+    /// shadowed by a variable are left out. Types are never shadowed, because they are in a separate namespace.
+    pub fn lookup_at(&self, span: Span) -> Vec<Symbol> {
+        let mut symbols: Vec<Symbol> = self
+            .scope_at(span)
+            .map(|scope| self.visible_variables(&scope, span.start))
+            .unwrap_or_default()
+            .into_iter()
+            .map(Symbol::Variable)
+            .collect();
+        symbols.sort_by_key(Symbol::name);
+
+        let is_shadowed = |name: &String| {
+            symbols
+                .iter()
+                .any(|symbol| matches!(symbol, Symbol::Variable(_)) && symbol.name() == *name)
+        };
+
+        let inner = self.inner.borrow();
+        let mut globals: Vec<Symbol> = inner
+            .constant_name_lookup
+            .iter()
+            .filter(|(name, _)| !is_shadowed(name))
+            .map(|(_, constant)| Symbol::Constant(constant.clone()))
+            .chain(
+                inner
+                    .static_name_lookup
+                    .iter()
+                    .filter(|(name, _)| !is_shadowed(name))
+                    .map(|(_, static_ref)| Symbol::Static(static_ref.clone())),
+            )
+            .collect();
+        globals.sort_by_key(Symbol::name);
+
+        let mut types: Vec<Symbol> = inner
+            .struct_name_lookup
+            .values()
+            .map(|struct_ref| Symbol::Struct(struct_ref.clone()))
+            .chain(
+                inner
+                    .enum_name_lookup
+                    .values()
+                    .map(|enum_ref| Symbol::Enum(enum_ref.clone())),
+            )
+            .collect();
+        types.sort_by_key(Symbol::name);
+
+        let mut functions: Vec<Symbol> = inner
+            .function_name_lookup
+            .values()
+            .map(|function| Symbol::Function(function.clone()))
+            .collect();
+        functions.sort_by_key(Symbol::name);
+
+        symbols.extend(globals);
+        symbols.extend(types);
+        symbols.extend(functions);
+        symbols
+    }
+}
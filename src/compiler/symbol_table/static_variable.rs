@@ -0,0 +1,76 @@
+use std::cell::Ref;
+use std::ops::Deref;
+
+use crate::compiler::parser::ast;
+use crate::compiler::ref_arena::ArenaRef;
+use crate::compiler::symbol_table::{ConstValue, Sym};
+
+/// A global variable, which lives as long as the program and can be accessed from every function
+#[derive(Debug)]
+pub struct Static {
+    /// name must be unique among all constants and statics
+    pub name: String,
+    pub ty: ast::Type,
+    /// Whether this is a `static mut`, which can be assigned to
+    pub is_mutable: bool,
+    /// The initial value is `None` until it is evaluated during semantic analysis
+    pub initial_value: Option<ConstValue>,
+}
+
+impl Static {
+    pub fn new(name: String, ty: ast::Type, is_mutable: bool) -> Self {
+        Self {
+            name,
+            ty,
+            is_mutable,
+            initial_value: None,
+        }
+    }
+
+    /// Returns the initial value of this static. Must only be called after constant evaluation.
+    pub fn initial_value(&self) -> &ConstValue {
+        self.initial_value
+            .as_ref()
+            .expect("initial value of static to be evaluated during semantic analysis")
+    }
+}
+
+impl Sym {
+    /// Tries to find a static with specified name. Returns `None` if no static was found.
+    pub fn get_static_by_name<'a>(
+        &'a self,
+        name: &str,
+    ) -> Option<impl Deref<Target = ArenaRef<Static>> + 'a> {
+        Ref::filter_map(self.inner.borrow(), |inner_sym| {
+            inner_sym.static_name_lookup.get(name)
+        })
+        .ok()
+    }
+
+    /// Returns the names of all statics in the symbol table
+    pub fn static_names(&self) -> Vec<String> {
+        self.inner
+            .borrow()
+            .static_name_lookup
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Adds a new static to the symbol table. It can only be found by its name after it was declared.
+    pub fn add_static(&self, static_variable: Static) -> ArenaRef<Static> {
+        self.inner.borrow().statics.insert(static_variable)
+    }
+
+    /// Makes a static findable by its name. Its name must not be declared yet.
+    pub fn declare_static(&self, static_ref: &ArenaRef<Static>) {
+        let name = static_ref.get().name.clone();
+        let previous = self
+            .inner
+            .borrow_mut()
+            .static_name_lookup
+            .insert(name, static_ref.clone());
+
+        assert!(previous.is_none(), "statics to be declared only once");
+    }
+}
@@ -0,0 +1,85 @@
+use std::cell::Ref;
+use std::ops::Deref;
+
+use crate::compiler::parser::ast;
+use crate::compiler::ref_arena::ArenaRef;
+use crate::compiler::span::Span;
+use crate::compiler::symbol_table::Sym;
+
+/// A struct type like `struct Point { x: int, y: int }`, whose fields are stored in the order of their declaration
+#[derive(Debug)]
+pub struct Struct {
+    /// name must be unique among all structs and enums
+    pub name: String,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug)]
+pub struct Field {
+    pub name: String,
+    pub ty: ast::Type,
+    /// Span of the field name
+    pub span: Span,
+}
+
+impl Struct {
+    pub fn new(name: String, fields: Vec<Field>) -> Self {
+        Self { name, fields }
+    }
+
+    /// Returns the position of the field called `name` and the field itself. Returns `None` if there is no such field.
+    pub fn field(&self, name: &str) -> Option<(u32, &Field)> {
+        self.fields
+            .iter()
+            .enumerate()
+            .find(|(_, field)| field.name == name)
+            .map(|(index, field)| (index as u32, field))
+    }
+}
+
+impl Sym {
+    /// Tries to find a struct with specified name. Returns `None` if no struct was found.
+    pub fn get_struct_by_name<'a>(
+        &'a self,
+        name: &str,
+    ) -> Option<impl Deref<Target = ArenaRef<Struct>> + 'a> {
+        Ref::filter_map(self.inner.borrow(), |inner_sym| {
+            inner_sym.struct_name_lookup.get(name)
+        })
+        .ok()
+    }
+
+    /// Returns the struct called `name`. Must only be called after all type names were resolved.
+    pub fn get_struct(&self, name: &str) -> ArenaRef<Struct> {
+        self.get_struct_by_name(name)
+            .map(|struct_ref| struct_ref.clone())
+            .expect("struct names to be resolved during name resolution")
+    }
+
+    /// Returns the names of all structs in the symbol table
+    pub fn struct_names(&self) -> Vec<String> {
+        self.inner
+            .borrow()
+            .struct_name_lookup
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Adds a new struct to the symbol table. It can only be found by its name after it was declared.
+    pub fn add_struct(&self, struct_def: Struct) -> ArenaRef<Struct> {
+        self.inner.borrow().structs.insert(struct_def)
+    }
+
+    /// Makes a struct findable by its name. Its name must not be declared yet.
+    pub fn declare_struct(&self, struct_ref: &ArenaRef<Struct>) {
+        let name = struct_ref.get().name.clone();
+        let previous = self
+            .inner
+            .borrow_mut()
+            .struct_name_lookup
+            .insert(name, struct_ref.clone());
+
+        assert!(previous.is_none(), "structs to be declared only once");
+    }
+}
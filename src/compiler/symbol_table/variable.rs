@@ -0,0 +1,31 @@
+use crate::compiler::parser::ast;
+use crate::compiler::ref_arena::ArenaRef;
+use crate::compiler::symbol_table::Sym;
+
+#[derive(Debug)]
+pub struct Variable {
+    /// Names of variables do not have to be unique, variables can shadow each other
+    pub name: String,
+    /// The declared type or, if there is none, the type of the initial value, which is inferred during type checking
+    pub ty: Option<ast::Type>,
+}
+
+impl Variable {
+    pub fn new(name: String, ty: Option<ast::Type>) -> Self {
+        Self { name, ty }
+    }
+
+    /// Returns the type of this variable. Must only be called after the declaration of the variable was type checked.
+    pub fn ty(&self) -> ast::Type {
+        self.ty
+            .clone()
+            .expect("variable to be declared and type checked before it is used")
+    }
+}
+
+impl Sym {
+    /// Adds a new variable to the symbol table. Variables are looked up by scope during name resolution instead of by name.
+    pub fn add_variable(&self, variable: Variable) -> ArenaRef<Variable> {
+        self.inner.borrow().variables.insert(variable)
+    }
+}
@@ -0,0 +1,7 @@
+//! The yyn compiler as a library, so that other tools can compile yyn source code or inspect the results of its
+//! semantic analysis, like the call graph of a program
+
+pub use compiler::options::{Emit, Options};
+pub use compiler::{analyse, compile, AnalyzedAST, CallGraph};
+
+mod compiler;
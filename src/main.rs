@@ -5,21 +5,46 @@ use std::process::exit;
 use anyhow::Context;
 use anyhow::Result;
 
-mod compiler;
+use yyn_rs::Options;
 
 fn main() -> Result<()> {
+    let mut options = Options::default();
+    let mut paths = Vec::new();
+
     // First argument is program name, it can be skipped
     let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if let Some(flag) = arg.strip_prefix("--") {
+            options.apply_flag(flag)?;
+        } else if arg == "-C" {
+            let option = args
+                .next()
+                .context("Missing code generation option after `-C`")?;
+            options.apply_codegen_option(&option)?;
+        } else if let Some(option) = arg.strip_prefix("-C") {
+            options.apply_codegen_option(option)?;
+        } else {
+            paths.push(PathBuf::from(arg));
+        }
+    }
+    let mut paths = paths.into_iter();
 
-    let Some(src_file): Option<PathBuf> = args.next().map(PathBuf::from).into() else {
+    let Some(src_file) = paths.next() else {
         println!("Please specify a path to a file containing yyn source code");
+        println!(
+            "Lints can be configured with `--allow=<lint>`, `--warn=<lint>` and `--deny=<lint>`"
+        );
+        println!("Libraries without a `main` function can be compiled with `--lib`");
+        println!("Arithmetic panics on overflow unless `--release` or `-C overflow-checks=off` is given, `-C overflow-checks=on` enables the checks in release builds");
+        println!("Assertions are compiled out with `--release` or `-C debug-assertions=off`, `-C debug-assertions=on` keeps them in release builds");
+        println!("The call graph can be written in the DOT format instead of LLVM IR with `--emit=callgraph`");
+        println!("The tokens or the syntax tree can be written for debugging with `--emit=tokens` or `--emit=ast`");
         exit(1);
     };
 
-    let dest_file = args
+    let dest_file = paths
         .next()
-        .map(PathBuf::from)
-        .unwrap_or_else(|| src_file.with_extension("ll"));
+        .unwrap_or_else(|| src_file.with_extension(options.emit.extension()));
 
-    compiler::compile(&src_file, &dest_file).context("Failed compilation")
+    yyn_rs::compile(&src_file, &dest_file, &options).context("Failed compilation")
 }